@@ -0,0 +1,198 @@
+//! Creation of several modules at once, from a spec.
+//!
+//! A spec is either TOML, listing modules with their options,
+//! ```toml
+//! [[module]]
+//! path = "src/payments"
+//! dir = true
+//!
+//! [[module]]
+//! path = "src/payments/client"
+//! kind = "service"
+//! vis = "pub(crate)"
+//! test-style = "inline"
+//! cfg = "unix"
+//! ```
+//! or a list of module paths, one per line. Paths ending with `/` are created as
+//! directory modules, and blank lines and lines starting with `#` are ignored.
+use crate::config::TestStyle;
+use crate::creator::{DiskFileSystem, ModuleCreator};
+use crate::kind::ModuleKind;
+use crate::result::Result;
+use crate::visibility::Visibility;
+use crate::{CreateOptions, Created, Plan, TestLocation};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Modules to create.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Spec {
+    /// Modules, created in order.
+    #[serde(rename = "module")]
+    pub modules: Vec<Entry>,
+}
+
+impl Spec {
+    /// Load a spec from a file.
+    /// Files with a `.toml` extension are read as TOML, others as a list of paths.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        match path.extension() {
+            Some(ext) if ext == "toml" => Self::from_toml(&content),
+            _ => Self::parse(&content),
+        }
+    }
+
+    /// Parse a spec, as TOML if it contains a `[[module]]` table, otherwise as a list of paths.
+    pub fn parse(content: &str) -> Result<Self> {
+        match content.lines().any(|line| line.trim() == "[[module]]") {
+            true => Self::from_toml(content),
+            false => Ok(Self::from_list(content)),
+        }
+    }
+
+    /// Parse a TOML spec.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parse a list of module paths.
+    pub fn from_list(content: &str) -> Self {
+        let modules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| Entry {
+                path: PathBuf::from(line.trim_end_matches('/')),
+                dir: line.ends_with('/'),
+                ..Entry::default()
+            })
+            .collect();
+
+        Self { modules }
+    }
+}
+
+/// A module to create, with options overriding the batch's.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Entry {
+    /// Path of the module, without extension. e.g. `src/my_mod`.
+    pub path: PathBuf,
+
+    /// Create the module as a directory.
+    pub dir: bool,
+
+    /// Visibility of the module's declaration.
+    pub vis: Option<Visibility>,
+
+    /// Starter skeleton of the module.
+    pub kind: Option<ModuleKind>,
+
+    /// Create a test file for the module.
+    pub test: Option<bool>,
+
+    /// Where the module's tests are written.
+    /// Overridden by `test`, if set.
+    pub test_style: Option<TestStyle>,
+
+    /// Predicate gating the module's declaration. e.g. `unix` for `#[cfg(unix)]`.
+    pub cfg: Option<String>,
+
+    /// Feature gating the module's declaration.
+    pub feature: Option<String>,
+
+    /// Module documentation.
+    pub doc: Option<String>,
+}
+
+impl Entry {
+    /// Options for creating the module, overriding those of the batch.
+    ///
+    /// # Arguments
+    /// + `base`: Options of the batch. Its path is replaced by the entry's.
+    pub fn options(&self, base: &CreateOptions) -> CreateOptions {
+        let mut options = base.clone();
+        options.path = self.path.clone();
+        options.dir = self.dir;
+        if let Some(vis) = &self.vis {
+            options.super_options.vis = Some(vis.clone());
+        }
+
+        if let Some(kind) = self.kind {
+            options.content.kind = Some(kind);
+        }
+
+        match self.test_style {
+            Some(TestStyle::File) => {
+                options.with_test = true;
+                if options.test.location == TestLocation::Inline {
+                    options.test.location = TestLocation::Sibling;
+                }
+            },
+
+            Some(TestStyle::Inline) => {
+                options.with_test = true;
+                options.test.location = TestLocation::Inline;
+            },
+
+            Some(TestStyle::None) => options.with_test = false,
+            None => {},
+        }
+
+        if let Some(test) = self.test {
+            options.with_test = test;
+        }
+
+        if let Some(cfg) = &self.cfg {
+            options.super_options.attributes.push(format!("#[cfg({cfg})]"));
+        }
+
+        if let Some(feature) = &self.feature {
+            options.super_options.feature = Some(feature.clone());
+        }
+
+        if let Some(doc) = &self.doc {
+            options.content.doc = Some(doc.clone());
+        }
+
+        options
+    }
+}
+
+/// Create the modules of a spec, in order.
+/// Their missing ancestors are created if the options of the batch create them.
+///
+/// Creation is all-or-nothing: if any module can not be created,
+/// every change made by the batch is undone.
+///
+/// # Arguments
+/// + `spec`: Modules to create.
+/// + `base`: Options shared by the modules. Its path is ignored.
+/// + `pre_create`: Called with the plan of each module before it is created.
+///   Returning an error aborts the batch.
+///
+/// # Returns
+/// The created modules.
+pub fn create(
+    spec: &Spec,
+    base: &CreateOptions,
+    mut pre_create: impl FnMut(&Plan) -> Result,
+) -> Result<Vec<Created>> {
+    ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| {
+        let mut created = Vec::new();
+        for entry in &spec.modules {
+            let options = entry.options(base);
+            pre_create(&creator.plan(&options)?)?;
+            created.push(creator.create(&options)?);
+        }
+
+        Ok(created)
+    })
+}
+
+#[cfg(test)]
+#[path = "./batch_test.rs"]
+mod batch_test;
@@ -0,0 +1,93 @@
+use super::*;
+use crate::fixtures::project_with_modules;
+use crate::ContentOptions;
+
+#[test]
+fn spec_should_parse_toml_and_lists() {
+    let spec = Spec::parse("[[module]]\npath = \"src/a\"\ndir = true\nvis = \"pub(crate)\"\nkind = \"enum\"\n").unwrap();
+    assert_eq!(spec.modules, [Entry {
+        path: PathBuf::from("src/a"),
+        dir: true,
+        vis: Some(Visibility::Crate),
+        kind: Some(ModuleKind::Enum),
+        ..Entry::default()
+    }]);
+
+    let spec = Spec::parse("# feature\nsrc/a/\n\nsrc/a/b\n").unwrap();
+    let paths: Vec<_> = spec.modules.iter().map(|entry| (entry.path.clone(), entry.dir)).collect();
+    assert_eq!(paths, [(PathBuf::from("src/a"), true), (PathBuf::from("src/a/b"), false)]);
+}
+
+#[test]
+fn batch_should_create_modules_with_their_options() {
+    let (_dir, src) = project_with_modules(&[]);
+    let spec = Spec {
+        modules: vec![
+            Entry { path: src.join("a"), dir: true, ..Entry::default() },
+            Entry { path: src.join("a/b"), vis: Some(Visibility::Private), test: Some(false), ..Entry::default() },
+            Entry { path: src.join("c/d"), ..Entry::default() },
+        ],
+    };
+
+    let base = CreateOptions::new("").parents(ContentOptions::default());
+    let created = create(&spec, &base, |_| Ok(())).unwrap();
+    assert_eq!(created.len(), 3);
+    assert_eq!(created[2].parents[0].module_path, src.join("c"));
+    assert!(src.join("a/mod_test.rs").exists());
+    assert!(!src.join("a/b_test.rs").exists());
+    assert!(src.join("c/d.rs").exists());
+    assert!(fs::read_to_string(src.join("a/mod.rs")).unwrap().starts_with("mod b;\n"));
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\npub mod c;\n");
+}
+
+#[test]
+fn failed_batch_should_be_rolled_back() {
+    let (_dir, src) = project_with_modules(&[]);
+    fs::write(src.join("lib.rs"), "use std::fs;\n").unwrap();
+    fs::write(src.join("e.rs"), "").unwrap();
+    let spec = Spec::from_list("src/a/\nsrc/a/b\nsrc/c/d\nsrc/e\n");
+    let spec = Spec {
+        modules: spec.modules
+            .into_iter()
+            .map(|entry| Entry { path: src.parent().unwrap().join(entry.path), ..entry })
+            .collect(),
+    };
+
+    let base = CreateOptions::new("").parents(ContentOptions::default());
+    assert!(create(&spec, &base, |_| Ok(())).is_err());
+    assert!(!src.join("a").exists());
+    assert!(!src.join("a_test.rs").exists());
+    assert!(!src.join("c").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "use std::fs;\n");
+
+    let mut spec = Spec::from_list("src/f\n");
+    spec.modules[0].path = src.join("f");
+    assert!(create(&spec, &base, |_| {
+        Err(std::io::Error::other("rejected").into())
+    }).is_err());
+    assert!(!src.join("f.rs").exists());
+}
+
+#[test]
+fn entries_should_override_test_style_and_gates() {
+    let spec = Spec::parse(
+        "[[module]]\npath = \"src/a\"\ntest-style = \"inline\"\ncfg = \"unix\"\n\n[[module]]\npath = \"src/b\"\nfeature = \"fancy\"\ntest-style = \"none\"\n"
+    ).unwrap();
+
+    let (_dir, src) = project_with_modules(&[]);
+    let spec = Spec {
+        modules: spec.modules
+            .into_iter()
+            .map(|entry| Entry { path: src.parent().unwrap().join(entry.path), ..entry })
+            .collect(),
+    };
+
+    create(&spec, &CreateOptions::new(""), |_| Ok(())).unwrap();
+    assert!(fs::read_to_string(src.join("a.rs")).unwrap().contains("#[cfg(test)]\nmod tests {"));
+    assert!(!src.join("a_test.rs").exists());
+    assert!(!src.join("b_test.rs").exists());
+    assert_eq!(
+        fs::read_to_string(src.join("lib.rs")).unwrap(),
+        "#[cfg(unix)]\npub mod a;\n#[cfg(feature = \"fancy\")]\npub mod b;\n"
+    );
+}
@@ -0,0 +1,344 @@
+//! Project configuration, read from `.mkmod.toml` in the crate root.
+//! Templates may also be set for all projects in the user configuration,
+//! `$XDG_CONFIG_HOME/mkmod/config.toml`.
+use crate::result::{Error, Result};
+use crate::test_framework::TestFramework;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file in the crate root.
+pub const CONFIG_FILE: &str = ".mkmod.toml";
+
+/// Project configuration.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Layout of the project.
+    pub project: ProjectConfig,
+
+    /// Contents of generated modules.
+    pub module: ModuleConfig,
+
+    /// Format of generated module declarations.
+    pub declaration: DeclarationFormat,
+
+    /// Commands run around operations.
+    pub hooks: Hooks,
+
+    /// Templates of generated files.
+    pub templates: Templates,
+}
+
+impl Config {
+    /// Load the configuration of a project.
+    ///
+    /// # Arguments
+    /// + `root`: Crate root.
+    ///
+    /// # Returns
+    /// The project's configuration, or the default configuration if the project has no
+    /// configuration file.
+    /// Templates not set by the project are taken from the user configuration.
+    pub fn load(root: &Path) -> Result<Self> {
+        Self::load_with_user(root, user_path().as_deref())
+    }
+
+    /// Load the configuration of a project, taking templates it does not set
+    /// from a user configuration file.
+    ///
+    /// # Arguments
+    /// + `root`: Crate root.
+    /// + `user`: Path to the user configuration file, if any.
+    fn load_with_user(root: &Path, user: Option<&Path>) -> Result<Self> {
+        let mut config = read(&path(root))?.unwrap_or_default();
+        if let Some(user) = user.map(read).transpose()?.flatten() {
+            config.templates = config.templates.or(user.templates);
+        }
+
+        Ok(config)
+    }
+
+    /// Serialize the configuration as it would appear in a configuration file.
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+/// Path to the configuration file of a project.
+///
+/// # Arguments
+/// + `root`: Crate root.
+pub fn path(root: &Path) -> PathBuf {
+    root.join(CONFIG_FILE)
+}
+
+/// Path to the user configuration file.
+///
+/// # Returns
+/// `$XDG_CONFIG_HOME/mkmod/config.toml`, falling back to `$HOME/.config/mkmod/config.toml`,
+/// or `None` if neither variable is set.
+pub fn user_path() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME").filter(|dir| !dir.is_empty())?).join(".config"),
+    };
+
+    Some(config_home.join("mkmod").join("config.toml"))
+}
+
+/// Read a configuration file.
+///
+/// # Returns
+/// The configuration, or `None` if the file does not exist.
+fn read(path: &Path) -> Result<Option<Config>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(Some(toml::from_str(&content)?))
+}
+
+/// Layout of the project.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Source root, relative to the crate root, if not `src`. e.g. `rust/src`.
+    pub src_root: Option<PathBuf>,
+
+    /// Tree of test support modules, relative to the source root,
+    /// if not `test_support`. e.g. `testing`.
+    pub test_support: Option<PathBuf>,
+}
+
+/// Contents of generated modules.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModuleConfig {
+    /// Inner attributes added to the top of every new module.
+    /// e.g. `#![deny(missing_docs)]`.
+    pub attributes: Vec<String>,
+
+    /// Where new modules' tests are written.
+    pub test_style: TestStyle,
+
+    /// Framework new modules' tests are written for.
+    pub test_framework: TestFramework,
+}
+
+/// Format of a generated module declaration.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeclarationFormat {
+    /// Template of the declaration statement.
+    /// `{vis}`, `{name}`, and `{comment}` are replaced with the module's visibility,
+    /// name, and comment, respectively. Leading and trailing whitespace is removed.
+    pub template: String,
+
+    /// Text placed between the statement and its comment.
+    pub comment_prefix: String,
+
+    /// Where attributes of the declaration are placed.
+    pub attributes: AttributePlacement,
+
+    /// Where the declaration is inserted in the super file.
+    pub insert: InsertPosition,
+}
+
+impl Default for DeclarationFormat {
+    fn default() -> Self {
+        Self {
+            template: String::from("{vis} mod {name};{comment}"),
+            comment_prefix: String::from(" // "),
+            attributes: AttributePlacement::Preceding,
+            insert: InsertPosition::End,
+        }
+    }
+}
+
+impl DeclarationFormat {
+    /// Render a module declaration.
+    ///
+    /// # Arguments
+    /// + `vis`: Visibility of the module. e.g. `pub`, or an empty string for private modules.
+    /// + `name`: Name of the module.
+    /// + `attributes`: Attributes of the declaration. e.g. `#[cfg(unix)]`.
+    /// + `comment`: Comment to add to the declaration.
+    ///
+    /// # Returns
+    /// The declaration. Contains multiple lines if attributes are placed on preceding lines.
+    pub fn render(
+        &self,
+        vis: &str,
+        name: &str,
+        attributes: &[String],
+        comment: Option<&str>,
+    ) -> String {
+        let comment = match comment {
+            Some(comment) => format!("{}{comment}", self.comment_prefix),
+            None => String::new(),
+        };
+
+        let statement = self.template
+            .replace("{vis}", vis)
+            .replace("{name}", name)
+            .replace("{comment}", &comment);
+
+        let statement = statement.trim();
+        if attributes.is_empty() {
+            return statement.to_string();
+        }
+
+        match self.attributes {
+            AttributePlacement::Preceding => format!("{}\n{statement}", attributes.join("\n")),
+            AttributePlacement::Inline => format!("{} {statement}", attributes.join(" ")),
+        }
+    }
+}
+
+/// Templates of generated files, rendered with [minijinja](https://docs.rs/minijinja).
+///
+/// Templates may use the variables
+/// + `mod_name`: Name of the module. For directory modules, the name of the directory.
+///   For integration tests, benches, and examples, the name of the target.
+/// + `crate_name`: Name of the crate, with `-` replaced by `_`.
+/// + `date`: Current UTC date. e.g. `2024-01-31`.
+/// + `year`: Current UTC year. e.g. `2024`.
+/// + `author`: Name of the package's first author, or the git user if it lists none.
+///   Empty if neither is known.
+/// + `rust_version`: The package's `rust-version`, if it declares one.
+///
+/// and the function `rust_supports(version)`, whether the package's `rust-version` is at least `version`,
+/// or `true` if it declares none.
+///
+/// Test file templates may also use `imports`, the paths of additional test imports.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Templates {
+    /// Body of file modules, placed after their documentation and attributes.
+    /// The test module declaration is added after it.
+    pub module: Option<String>,
+
+    /// Body of directory modules' `mod.rs`. Falls back to `module` if not set.
+    pub dir_module: Option<String>,
+
+    /// Contents of test files. Replaces `use super::*;` and the additional imports.
+    pub test: Option<String>,
+
+    /// Body of integration tests in `tests/`.
+    pub integration_test: Option<String>,
+
+    /// Body of benches in `benches/`.
+    pub bench: Option<String>,
+
+    /// Body of examples in `examples/`.
+    pub example: Option<String>,
+
+    /// Header at the top of every generated file, before its documentation.
+    /// e.g. `// Copyright {{ year }} {{ author }}`.
+    pub header: Option<String>,
+
+    /// Documentation of modules created without any, written as `//!` comments.
+    /// e.g. `TODO: document {{ mod_name }}`.
+    pub doc: Option<String>,
+
+    /// Module bodies selected by name with `--template <NAME>`, used instead of `module` and `dir_module`.
+    /// e.g. a `handler` template for request handlers.
+    pub named: BTreeMap<String, String>,
+}
+
+impl Templates {
+    /// Fill the templates that are not set from another set of templates.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            module: self.module.or(other.module),
+            dir_module: self.dir_module.or(other.dir_module),
+            test: self.test.or(other.test),
+            integration_test: self.integration_test.or(other.integration_test),
+            bench: self.bench.or(other.bench),
+            example: self.example.or(other.example),
+            header: self.header.or(other.header),
+            doc: self.doc.or(other.doc),
+            named: other.named.into_iter().chain(self.named).collect(),
+        }
+    }
+
+    /// Use a named template as the body of file and directory modules.
+    ///
+    /// # Errors
+    /// + [`Error::TemplateNotFound`] if no template has the name.
+    pub fn select(mut self, name: &str) -> Result<Self> {
+        let template = match self.named.get(name) {
+            Some(template) => template.clone(),
+            None => return Err(Error::TemplateNotFound(name.to_string())),
+        };
+
+        self.module = Some(template.clone());
+        self.dir_module = Some(template);
+        Ok(self)
+    }
+}
+
+/// Commands run around operations.
+/// See [`crate::hooks::run`] for how they are invoked.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Commands run before a module is created.
+    /// Receive the paths that will be created or modified, and the planned operation as JSON
+    /// on their standard input. Exiting unsuccessfully aborts the operation.
+    pub pre_create: Vec<String>,
+
+    /// Commands run after a module is successfully created.
+    /// Receive the created and modified paths.
+    pub post_create: Vec<String>,
+}
+
+/// Placement of attributes on a module declaration.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttributePlacement {
+    /// Each attribute on its own line before the statement.
+    Preceding,
+
+    /// On the same line as the statement.
+    Inline,
+}
+
+/// Position of a new module declaration in its super file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InsertPosition {
+    /// At the end of the leading `use`, `extern crate`, and `mod` declarations,
+    /// or before the first item if there are none.
+    #[default]
+    End,
+
+    /// In alphabetical order among the existing module declarations,
+    /// falling back to `End` if there are none.
+    Sorted,
+}
+
+/// Where a new module's tests are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestStyle {
+    /// In a separate test file, declared with `#[path]` at the bottom of the module.
+    #[default]
+    File,
+
+    /// In a `#[cfg(test)] mod tests` block at the bottom of the module.
+    Inline,
+
+    /// No tests are scaffolded.
+    None,
+}
+
+#[cfg(test)]
+#[path = "./config_test.rs"]
+mod config_test;
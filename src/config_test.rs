@@ -0,0 +1,125 @@
+use super::*;
+
+#[test]
+fn missing_config_file_should_load_default() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(Config::load_with_user(dir.path(), None).unwrap(), Config::default());
+}
+
+#[test]
+fn user_templates_should_fill_unset_project_templates() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    fs::write(path(dir.path()), "[templates]\nmodule = \"// project\"\n").unwrap();
+    fs::write(&user, "[templates]\nmodule = \"// user\"\ntest = \"// user test\"\n").unwrap();
+
+    let config = Config::load_with_user(dir.path(), Some(&user)).unwrap();
+    assert_eq!(config.templates.module.as_deref(), Some("// project"));
+    assert_eq!(config.templates.test.as_deref(), Some("// user test"));
+    assert_eq!(config.templates.dir_module, None);
+}
+
+#[test]
+fn named_templates_should_be_merged_and_selected() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    fs::write(path(dir.path()), "[templates.named]\nhandler = \"// project handler\"\n").unwrap();
+    fs::write(&user, "[templates.named]\nhandler = \"// user handler\"\nmodel = \"// model\"\n").unwrap();
+
+    let config = Config::load_with_user(dir.path(), Some(&user)).unwrap();
+    let templates = config.templates.clone().select("handler").unwrap();
+    assert_eq!(templates.module.as_deref(), Some("// project handler"));
+    assert_eq!(templates.dir_module.as_deref(), Some("// project handler"));
+    assert_eq!(config.templates.clone().select("model").unwrap().module.as_deref(), Some("// model"));
+    assert!(matches!(config.templates.select("view"), Err(Error::TemplateNotFound(name)) if name == "view"));
+}
+
+#[test]
+fn declaration_format_should_be_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(path(dir.path()), r#"
+[declaration]
+template = "{vis} mod {name};  {comment}"
+comment_prefix = "/* "
+attributes = "inline"
+insert = "sorted"
+"#).unwrap();
+
+    let config = Config::load(dir.path()).unwrap();
+    assert_eq!(config.declaration.template, "{vis} mod {name};  {comment}");
+    assert_eq!(config.declaration.comment_prefix, "/* ");
+    assert_eq!(config.declaration.attributes, AttributePlacement::Inline);
+    assert_eq!(config.declaration.insert, InsertPosition::Sorted);
+}
+
+#[test]
+fn unknown_config_keys_should_error() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(path(dir.path()), "[declaration]\ntemplat = \"\"\n").unwrap();
+    assert!(Config::load(dir.path()).is_err());
+}
+
+#[test]
+fn default_declaration_format_should_render_standard_statement() {
+    let format = DeclarationFormat::default();
+    assert_eq!(format.render("pub", "a", &[], None), "pub mod a;");
+    assert_eq!(format.render("", "a", &[], None), "mod a;");
+    assert_eq!(format.render("pub", "a", &[], Some("note")), "pub mod a; // note");
+
+    let attrs = vec![String::from("#[cfg(unix)]")];
+    assert_eq!(format.render("", "a", &attrs, None), "#[cfg(unix)]\nmod a;");
+}
+
+#[test]
+fn inline_attributes_should_share_statement_line() {
+    let format = DeclarationFormat {
+        attributes: AttributePlacement::Inline,
+        ..DeclarationFormat::default()
+    };
+
+    let attrs = vec![String::from("#[cfg(unix)]"), String::from("#[macro_use]")];
+    assert_eq!(format.render("pub", "a", &attrs, None), "#[cfg(unix)] #[macro_use] pub mod a;");
+}
+
+#[test]
+fn hooks_should_be_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(path(dir.path()), "[hooks]\npre_create = [\"./policy.sh\"]\npost_create = [\"cargo fmt\", \"git add \\\"$@\\\"\"]\n").unwrap();
+
+    let config = Config::load(dir.path()).unwrap();
+    assert_eq!(config.hooks.pre_create, vec!["./policy.sh"]);
+    assert_eq!(config.hooks.post_create, vec!["cargo fmt", "git add \"$@\""]);
+}
+
+#[test]
+fn serialized_config_should_load_identically() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::default();
+    config.hooks.post_create.push(String::from("cargo fmt"));
+    config.declaration.attributes = AttributePlacement::Inline;
+
+    fs::write(path(dir.path()), config.to_toml().unwrap()).unwrap();
+    assert_eq!(Config::load(dir.path()).unwrap(), config);
+}
+
+#[test]
+fn module_attributes_should_be_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        path(dir.path()),
+        "[module]\nattributes = [\"#![deny(missing_docs)]\"]\ntest_style = \"inline\"\n"
+    ).unwrap();
+
+    let config = Config::load(dir.path()).unwrap();
+    assert_eq!(config.module.attributes, vec!["#![deny(missing_docs)]"]);
+    assert_eq!(config.module.test_style, TestStyle::Inline);
+}
+
+#[test]
+fn src_root_should_be_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(path(dir.path()), "[project]\nsrc_root = \"rust/src\"\n").unwrap();
+
+    let config = Config::load(dir.path()).unwrap();
+    assert_eq!(config.project.src_root, Some(PathBuf::from("rust/src")));
+}
@@ -0,0 +1,115 @@
+//! Conversion between file and directory modules.
+use crate::project;
+use crate::remove::test_file;
+use crate::result::{Error, Result};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Convert a file module into a directory module.
+///
+/// `a.rs` is moved to `a/mod.rs`, and a test file next to it, `a_test.rs`,
+/// is moved to `a/mod_test.rs`. Other test files stay where they are.
+/// The module's declaration does not change.
+///
+/// # Arguments
+/// + `file`: File of the module. e.g. `src/a.rs`.
+///
+/// # Returns
+/// Files removed or created.
+pub fn to_dir(file: &Path) -> Result<Vec<PathBuf>> {
+    let file = project::absolute_path(file)?;
+    let name = crate::module_name(&file)?.to_string_lossy().into_owned();
+    let test = test_file(&file, &fs::read_to_string(&file)?)?;
+
+    let mut touched = crate::convert_to_dir(&file)?;
+    let dir = file.with_extension("");
+    let sibling_test = file.with_file_name(format!("{name}_test.rs"));
+    if test.as_ref() == Some(&sibling_test) && sibling_test.is_file() {
+        let new_test = dir.join("mod_test.rs");
+        move_test(&dir.join("mod.rs"), &name, &sibling_test, "mod", &new_test)?;
+        touched.extend([sibling_test, new_test]);
+    }
+
+    Ok(touched)
+}
+
+/// Convert a directory module into a file module.
+///
+/// `a/mod.rs` is moved to `a.rs`, and its test file `a/mod_test.rs` is moved to `a_test.rs`.
+/// Other files in `a/` stay where they are, with `a.rs` as their super,
+/// and `a/` is removed if it is left empty.
+/// The module's declaration does not change.
+///
+/// # Arguments
+/// + `dir`: Directory of the module. e.g. `src/a`.
+///
+/// # Returns
+/// Files removed or created.
+pub fn to_file(dir: &Path) -> Result<Vec<PathBuf>> {
+    let dir = project::absolute_path(dir)?;
+    let mod_file = dir.join("mod.rs");
+    if !mod_file.is_file() {
+        return Err(Error::ModuleNotFound(dir));
+    }
+
+    let file = dir.with_extension("rs");
+    if file.exists() {
+        return Err(Error::ModuleExists(file));
+    }
+
+    let parent = match dir.parent() {
+        Some(parent) => parent,
+        None => return Err(Error::InvalidPath(dir.clone())),
+    };
+
+    let name = crate::module_name(&dir)?.to_string_lossy().into_owned();
+    let content = fs::read_to_string(&mod_file)?;
+    let test = test_file(&mod_file, &content)?;
+    fs::write(&file, crate::retarget_paths(&content, &dir, parent)?)?;
+    fs::remove_file(&mod_file)?;
+
+    let mut touched = vec![mod_file, file.clone()];
+    let dir_test = dir.join("mod_test.rs");
+    let new_test = parent.join(format!("{name}_test.rs"));
+    if test.as_ref() == Some(&dir_test) && dir_test.is_file() && !new_test.exists() {
+        move_test(&file, "mod", &dir_test, &name, &new_test)?;
+        touched.extend([dir_test, new_test]);
+    }
+
+    if fs::read_dir(&dir)?.next().is_none() {
+        fs::remove_dir(&dir)?;
+    }
+
+    Ok(touched)
+}
+
+/// Move a module's test file, updating its declaration in the module.
+///
+/// # Arguments
+/// + `file`: File of the module, after it moved.
+/// + `old_name`: Name of the module the test module is named after. e.g. `a` for `a_test`.
+/// + `old_test`: Current path of the test file.
+/// + `new_name`: Name of the module the test module should be named after.
+/// + `new_test`: New path of the test file.
+fn move_test(file: &Path, old_name: &str, old_test: &Path, new_name: &str, new_test: &Path) -> Result {
+    let re_test_decl = Regex::new(&format!(
+        r#"(#\[path\s*=\s*")[^"]+("\]\s*mod\s+){}_test(\s*;)"#,
+        regex::escape(old_name)
+    ))?;
+
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let test_path = project::relative_path(dir, new_test);
+    let content = fs::read_to_string(file)?;
+    let content = re_test_decl.replace(&content, |caps: &regex::Captures| {
+        format!("{}{test_path}{}{new_name}_test{}", &caps[1], &caps[2], &caps[3])
+    });
+
+    fs::rename(old_test, new_test)?;
+    fs::write(file, content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./convert_test.rs"]
+mod convert_test;
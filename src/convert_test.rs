@@ -0,0 +1,53 @@
+use super::*;
+use crate::fixtures::project_with_modules;
+use crate::{ContentOptions, SuperOptions};
+
+/// A file module `a` and a directory module `b`.
+const MODULES: [(&str, bool); 2] = [("a", false), ("b", true)];
+
+#[test]
+fn to_dir_should_move_module_and_test() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    to_dir(&src.join("a.rs")).unwrap();
+
+    assert!(!src.join("a.rs").exists());
+    assert!(!src.join("a_test.rs").exists());
+    assert!(src.join("a/mod_test.rs").exists());
+    assert_eq!(
+        fs::read_to_string(src.join("a/mod.rs")).unwrap(),
+        "#[cfg(test)]\n#[path = \"./mod_test.rs\"]\nmod mod_test;\n"
+    );
+}
+
+#[test]
+fn to_file_should_move_module_and_test() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    to_file(&src.join("b")).unwrap();
+
+    assert!(!src.join("b").exists());
+    assert!(src.join("b_test.rs").exists());
+    assert_eq!(
+        fs::read_to_string(src.join("b.rs")).unwrap(),
+        "#[cfg(test)]\n#[path = \"./b_test.rs\"]\nmod b_test;\n"
+    );
+}
+
+#[test]
+fn to_file_should_keep_submodules() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    let options = SuperOptions::default();
+    crate::fixtures::create(&src.join("b/c"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    to_file(&src.join("b")).unwrap();
+
+    assert!(src.join("b/c.rs").exists());
+    assert!(fs::read_to_string(src.join("b.rs")).unwrap().contains("pub mod c;"));
+    assert_eq!(crate::super_path(&crate::creator::DiskFileSystem, &src.join("b/c"), false, None, None).unwrap(), src.join("b.rs"));
+}
+
+#[test]
+fn to_file_should_not_overwrite_file() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    fs::write(src.join("b.rs"), "").unwrap();
+    assert!(to_file(&src.join("b")).is_err());
+    assert!(src.join("b/mod.rs").exists());
+}
@@ -0,0 +1,994 @@
+//! Module creation for embedding, e.g. in build scripts and editor extensions.
+//! Files are accessed through a [`FileSystem`], and progress is reported through events instead of printed.
+use crate::git::{Git, MemoryVcs, Vcs};
+use crate::metrics::Metrics;
+use crate::result::{Error, Result};
+use crate::{
+    ContentOptions, CreateOptions, Created, Exists, Location, Plan, Preview, SuperOptions, TestLocation, TestOptions,
+};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// File system modules are created in.
+pub trait FileSystem {
+    /// Read a file.
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Write a file, replacing it if it exists. Its directory must exist.
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()>;
+
+    /// Create a directory. Its parent must exist.
+    fn create_dir(&mut self, path: &Path) -> io::Result<()>;
+
+    /// Remove a file.
+    fn remove_file(&mut self, path: &Path) -> io::Result<()>;
+
+    /// Remove an empty directory.
+    fn remove_dir(&mut self, path: &Path) -> io::Result<()>;
+
+    /// Get the paths of the entries of a directory, sorted.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Whether a file exists.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Whether a directory exists.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Get the canonical form of an existing path.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Directory relative paths are relative to.
+    fn current_dir(&self) -> io::Result<PathBuf>;
+
+    /// Whether a file or directory exists.
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    /// Version control the files are in, checked before modifying existing files.
+    fn vcs(&self) -> &dyn Vcs;
+}
+
+/// The machine's file system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        // existing files keep their permissions
+        match path.is_file() {
+            true => crate::write_atomic(path, content),
+            false => fs::write(path, content),
+        }
+    }
+
+    fn create_dir(&mut self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path)
+    }
+
+    fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&mut self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        std::env::current_dir()
+    }
+
+    fn vcs(&self) -> &dyn Vcs {
+        &Git
+    }
+}
+
+/// File system held in memory, e.g. for tests.
+/// Paths are relative to `/`, and directories containing a file exist implicitly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, String>,
+    dirs: BTreeSet<PathBuf>,
+    vcs: MemoryVcs,
+}
+
+impl MemoryFileSystem {
+    /// Create an empty file system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file.
+    pub fn with_file(mut self, path: impl AsRef<Path>, content: impl Into<String>) -> Self {
+        self.files.insert(memory_path(path.as_ref()), content.into());
+        self
+    }
+
+    /// Set the version control state of the files.
+    pub fn with_vcs(mut self, vcs: MemoryVcs) -> Self {
+        self.vcs = vcs;
+        self
+    }
+
+    /// Files and their contents, by absolute path, sorted.
+    pub fn files(&self) -> &BTreeMap<PathBuf, String> {
+        &self.files
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.files.get(&memory_path(path)) {
+            Some(content) => Ok(content.clone()),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        let path = memory_path(path);
+        match path.parent() {
+            Some(dir) if self.is_dir(dir) => {},
+            _ => return Err(not_found(path.parent().unwrap_or(&path))),
+        }
+
+        if self.is_dir(&path) {
+            return Err(io::Error::new(io::ErrorKind::IsADirectory, format!("{} is a directory", path.display())));
+        }
+
+        self.files.insert(path, content.to_string());
+        Ok(())
+    }
+
+    fn create_dir(&mut self, path: &Path) -> io::Result<()> {
+        let path = memory_path(path);
+        if self.exists(&path) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists", path.display())));
+        }
+
+        match path.parent() {
+            Some(dir) if self.is_dir(dir) => {},
+            _ => return Err(not_found(path.parent().unwrap_or(&path))),
+        }
+
+        self.dirs.insert(path);
+        Ok(())
+    }
+
+    fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        match self.files.remove(&memory_path(path)) {
+            Some(_) => Ok(()),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn remove_dir(&mut self, path: &Path) -> io::Result<()> {
+        if !self.read_dir(path)?.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::DirectoryNotEmpty, format!("{} is not empty", path.display())
+            ));
+        }
+
+        self.dirs.remove(&memory_path(path));
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let path = memory_path(path);
+        if !self.is_dir(&path) {
+            return Err(not_found(&path));
+        }
+
+        // files imply their ancestors
+        let entries: BTreeSet<PathBuf> = self.files
+            .keys()
+            .chain(self.dirs.iter())
+            .filter_map(|entry| entry.ancestors().find(|ancestor| ancestor.parent() == Some(&path)))
+            .map(Path::to_path_buf)
+            .collect();
+
+        Ok(entries.into_iter().collect())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&memory_path(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let path = memory_path(path);
+        path.parent().is_none()
+            || self.dirs.iter().chain(self.files.keys()).any(|entry| entry != &path && entry.starts_with(&path))
+            || self.dirs.contains(&path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        match self.exists(path) {
+            true => Ok(memory_path(path)),
+            false => Err(not_found(path)),
+        }
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        Ok(PathBuf::from("/"))
+    }
+
+    fn vcs(&self) -> &dyn Vcs {
+        &self.vcs
+    }
+}
+
+/// Get the absolute path of a path in a [`MemoryFileSystem`].
+pub(crate) fn memory_path(path: &Path) -> PathBuf {
+    crate::project::normalize_path(&Path::new("/").join(path))
+}
+
+/// Error for a path that does not exist.
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display()))
+}
+
+/// File system recording changes in memory, on top of another whose contents it reads.
+/// e.g. to preview the changes creating a module would make.
+pub struct OverlayFileSystem<'a> {
+    base: &'a dyn FileSystem,
+
+    /// Absolute paths of files written, and their contents, or `None` if they were removed.
+    files: BTreeMap<PathBuf, Option<String>>,
+
+    /// Absolute paths of directories created.
+    dirs: BTreeSet<PathBuf>,
+
+    /// Absolute paths of files written and directories created, in the order they were first touched.
+    touched: Vec<PathBuf>,
+}
+
+impl<'a> OverlayFileSystem<'a> {
+    /// Create an overlay without changes.
+    pub fn new(base: &'a dyn FileSystem) -> Self {
+        Self { base, files: BTreeMap::new(), dirs: BTreeSet::new(), touched: Vec::new() }
+    }
+
+    /// Absolute paths of files written and directories created, in the order they were first touched.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.touched.clone()
+    }
+
+    /// Get the changes made on top of the underlying file system.
+    /// Removed files are not included.
+    pub fn preview(&self) -> Preview {
+        let mut preview = Preview { created: Vec::new(), modified: Vec::new() };
+        for path in &self.touched {
+            let Some(Some(content)) = self.files.get(path) else {
+                continue;
+            };
+
+            match self.base.read_to_string(path) {
+                Ok(original) if original == *content => {},
+                Ok(original) => preview.modified.push((path.clone(), original, content.clone())),
+                Err(_) => preview.created.push((path.clone(), content.clone())),
+            }
+        }
+
+        preview
+    }
+
+    /// Get the absolute path of a path, resolving its existing ancestors in the underlying file system.
+    fn absolute(&self, path: &Path) -> PathBuf {
+        crate::project::absolute_path_in(self.base, path).unwrap_or_else(|_| crate::project::normalize_path(path))
+    }
+
+    /// Record that a path was touched.
+    fn touch(&mut self, path: &Path) {
+        if !self.touched.iter().any(|touched| touched == path) {
+            self.touched.push(path.to_path_buf());
+        }
+    }
+}
+
+impl FileSystem for OverlayFileSystem<'_> {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.files.get(&self.absolute(path)) {
+            Some(Some(content)) => Ok(content.clone()),
+            Some(None) => Err(not_found(path)),
+            None => self.base.read_to_string(path),
+        }
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        let path = self.absolute(path);
+        match path.parent() {
+            Some(dir) if self.is_dir(dir) => {},
+            _ => return Err(not_found(path.parent().unwrap_or(&path))),
+        }
+
+        self.touch(&path);
+        self.files.insert(path, Some(content.to_string()));
+        Ok(())
+    }
+
+    fn create_dir(&mut self, path: &Path) -> io::Result<()> {
+        let path = self.absolute(path);
+        if self.exists(&path) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists", path.display())));
+        }
+
+        match path.parent() {
+            Some(dir) if self.is_dir(dir) => {},
+            _ => return Err(not_found(path.parent().unwrap_or(&path))),
+        }
+
+        self.touch(&path);
+        self.dirs.insert(path);
+        Ok(())
+    }
+
+    fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        if !self.is_file(path) {
+            return Err(not_found(path));
+        }
+
+        let path = self.absolute(path);
+        self.files.insert(path, None);
+        Ok(())
+    }
+
+    fn remove_dir(&mut self, path: &Path) -> io::Result<()> {
+        if !self.read_dir(path)?.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::DirectoryNotEmpty, format!("{} is not empty", path.display())
+            ));
+        }
+
+        match self.dirs.remove(&self.absolute(path)) {
+            true => Ok(()),
+            false => Err(io::Error::new(
+                io::ErrorKind::Unsupported, "directories of the underlying file system can not be removed"
+            )),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if !self.is_dir(path) {
+            return Err(not_found(path));
+        }
+
+        let abs_path = self.absolute(path);
+        let mut names = BTreeSet::new();
+        if self.base.is_dir(path) {
+            names.extend(self.base.read_dir(path)?.iter().filter_map(|entry| entry.file_name()).map(OsStr::to_os_string));
+        }
+
+        let written = self.files.keys().filter(|file| self.files[*file].is_some());
+        names.extend(
+            written
+                .chain(self.dirs.iter())
+                .filter(|entry| entry.parent() == Some(&abs_path))
+                .filter_map(|entry| entry.file_name())
+                .map(OsStr::to_os_string)
+        );
+
+        let removed = |name: &std::ffi::OsString| self.files.get(&abs_path.join(name)) == Some(&None);
+        Ok(names.into_iter().filter(|name| !removed(name)).map(|name| path.join(name)).collect())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        match self.files.get(&self.absolute(path)) {
+            Some(content) => content.is_some(),
+            None => self.base.is_file(path),
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.base.is_dir(path) || self.dirs.contains(&self.absolute(path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        match self.exists(path) {
+            true => Ok(self.absolute(path)),
+            false => Err(not_found(path)),
+        }
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        self.base.current_dir()
+    }
+
+    fn vcs(&self) -> &dyn Vcs {
+        self.base.vcs()
+    }
+}
+
+/// Something that happened while creating a module.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A file was created.
+    Created(PathBuf),
+
+    /// A file was modified.
+    Modified(PathBuf),
+
+    /// Something to bring to the user's attention, that did not stop the module from being created.
+    Warning(String),
+}
+
+/// A change made by a [`ModuleCreator`], so it can be undone.
+enum Change {
+    /// A file was written. Contains its content before it was first written, or `None` if it did not exist.
+    File(PathBuf, Option<String>),
+
+    /// A directory was created.
+    Dir(PathBuf),
+}
+
+/// Creates modules in a [`FileSystem`], reporting what it does through a callback.
+/// [`CreateOptions::create`] creates modules on disk with a `ModuleCreator`.
+///
+/// Changes are undone if a module can not be created,
+/// and events are reported once it has been.
+///
+/// ```
+/// # use mkmod::creator::{MemoryFileSystem, ModuleCreator};
+/// let mut fs = MemoryFileSystem::new()
+///     .with_file("Cargo.toml", "[package]\nname = \"my_crate\"\n")
+///     .with_file("src/lib.rs", "");
+///
+/// let mut events = Vec::new();
+/// ModuleCreator::new(&mut fs)
+///     .on_event(|event| events.push(event))
+///     .create(&mkmod::CreateOptions::new("src/my_mod"))?;
+///
+/// assert_eq!(events.len(), 3);
+/// # Ok::<(), mkmod::result::Error>(())
+/// ```
+pub struct ModuleCreator<'a> {
+    fs: &'a mut dyn FileSystem,
+    on_event: Box<dyn FnMut(Event) + 'a>,
+
+    /// Changes made by the pending operation.
+    changes: Vec<Change>,
+
+    /// Events of the pending operation.
+    events: Vec<Event>,
+
+    /// Number of operations running, so nested operations are undone with the outermost.
+    depth: usize,
+}
+
+impl<'a> ModuleCreator<'a> {
+    /// Creator of modules in a file system, ignoring events.
+    pub fn new(fs: &'a mut dyn FileSystem) -> Self {
+        Self { fs, on_event: Box::new(|_| {}), changes: Vec::new(), events: Vec::new(), depth: 0 }
+    }
+
+    /// Call a function with every event.
+    pub fn on_event(mut self, on_event: impl FnMut(Event) + 'a) -> Self {
+        self.on_event = Box::new(on_event);
+        self
+    }
+
+    /// Create a module.
+    /// If it already exists, it is completed instead, as set by [`CreateOptions::exists`].
+    ///
+    /// # Returns
+    /// Files created or modified.
+    ///
+    /// # Errors
+    /// See [`CreateOptions::create`].
+    pub fn create(&mut self, options: &CreateOptions) -> Result<Created> {
+        self.transaction(|creator| {
+            let path = options.checked_path()?;
+            let add_to_super = options.super_options_if_added();
+            let parents = match &options.parents {
+                Some(content) => creator.create_parents(&path, content, add_to_super)?,
+                None => Vec::new(),
+            };
+
+            let exists = options.exists != Exists::Error && existing_module_file(&*creator.fs, &path).is_some();
+            let mut created = match exists {
+                true => creator.complete(
+                    &path, &options.content, options.test_options(), add_to_super, options.exists == Exists::Update,
+                )?,
+
+                false => creator.create_module(&path, options.dir, &options.content, options.test_options(), add_to_super)?,
+            };
+
+            created.parents = parents;
+            Ok(created)
+        })
+    }
+
+    /// Plan the creation of a module, without modifying anything.
+    /// Fails if the module already exists, regardless of [`CreateOptions::exists`].
+    ///
+    /// # Errors
+    /// See [`CreateOptions::create`].
+    pub fn plan(&self, options: &CreateOptions) -> Result<Plan> {
+        let path = options.checked_path()?;
+        let test = options.test_options();
+        let add_to_super = options.super_options_if_added();
+        let content = match &options.parents {
+            Some(content) if !missing_ancestors(&*self.fs, &path).is_empty() => content,
+            _ => return crate::plan_in(&*self.fs, &path, options.dir, test, add_to_super),
+        };
+
+        // the module is planned within its planned ancestors
+        let mut overlay = OverlayFileSystem::new(&*self.fs);
+        ModuleCreator::new(&mut overlay).transaction(|creator| creator.create_parents(&path, content, add_to_super))?;
+        let mut plan = crate::plan_in(&overlay, &path, options.dir, test, add_to_super)?;
+        plan.parents = overlay.paths();
+        Ok(plan)
+    }
+
+    /// Preview the changes creating a module would make, without modifying anything.
+    ///
+    /// # Errors
+    /// See [`CreateOptions::create`].
+    pub fn preview(&self, options: &CreateOptions) -> Result<Preview> {
+        let mut overlay = OverlayFileSystem::new(&*self.fs);
+        ModuleCreator::new(&mut overlay).create(options)?;
+        Ok(overlay.preview())
+    }
+
+    /// Run an operation, undoing its changes if it fails.
+    /// Events are reported once the outermost operation succeeds, and changes are kept until then,
+    /// so they are undone if it fails.
+    pub(crate) fn transaction<T>(&mut self, operation: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let (changes, events) = (self.changes.len(), self.events.len());
+        self.depth += 1;
+        let res = operation(self);
+        self.depth -= 1;
+        match res {
+            Ok(value) => {
+                if self.depth == 0 {
+                    self.changes.clear();
+                    std::mem::take(&mut self.events).into_iter().for_each(|event| (self.on_event)(event));
+                }
+
+                Ok(value)
+            },
+
+            Err(err) => {
+                self.events.truncate(events);
+
+                // the original error is more useful than one from restoring
+                for change in self.changes.split_off(changes).into_iter().rev() {
+                    let _ = match change {
+                        Change::File(path, Some(content)) => self.fs.write(&path, &content),
+                        Change::File(path, None) => self.fs.remove_file(&path),
+                        Change::Dir(path) => self.fs.remove_dir(&path),
+                    };
+                }
+
+                Err(err)
+            },
+        }
+    }
+
+    /// Create a new module.
+    ///
+    /// # Arguments
+    /// + `path`: Path of the module, without extension. e.g. `src/my_mod`.
+    /// + `dir`: If the module is a directory or a file.
+    /// + `content`: Options for the module's contents.
+    /// + `test`: Options for the test module, or `None` to not create one.
+    /// + `add_to_super`: Options for adding the module to its super, or `None` to not add it.
+    ///
+    /// # Errors
+    /// + If a module of the given name already exists.
+    /// + If the super file has unstaged changes, unless allowed.
+    pub(crate) fn create_module(
+        &mut self,
+        path: &Path,
+        dir: bool,
+        content: &ContentOptions,
+        test: Option<&TestOptions>,
+        add_to_super: Option<&SuperOptions>,
+    ) -> Result<Created> {
+        // ensure module can be created before creating anything
+        let plan = crate::plan_in(&*self.fs, path, dir, test, add_to_super)?;
+        let mut created = match dir {
+            true => self.make_mod_dir(path, content, test)?,
+            false => self.make_mod_file(path, content, test)?,
+        };
+
+        if let (Some(super_file), Some(options)) = (&plan.super_file, add_to_super) {
+            let start = Instant::now();
+            let (location, lines) = self.declare(&plan.name, super_file, options)?;
+            created.metrics.super_rewrite.record(start.elapsed());
+            created.declaration_location = Some(location);
+            created.declaration_lines = lines;
+            created.super_modified = Some(super_file.clone());
+        }
+
+        Ok(created)
+    }
+
+    /// Make a file module. See [`crate::make_mod_file`].
+    pub(crate) fn make_mod_file(
+        &mut self,
+        path: &Path,
+        content: &ContentOptions,
+        test: Option<&TestOptions>,
+    ) -> Result<Created> {
+        let mod_path = path.with_extension("rs");
+        if self.fs.exists(&mod_path) {
+            return Err(Error::ModuleExists(mod_path));
+        }
+
+        let mut metrics = Metrics::default();
+        let (mod_content, test_file) = metrics
+            .template_render
+            .time(|| crate::render_mod_file(&*self.fs, path, content, test))?;
+
+        // the super of a module in the directory of a named file exists, but the directory may not
+        if let Some(dir) = mod_path.parent() {
+            self.create_dir_all(dir)?;
+        }
+
+        self.write(&mod_path, &mod_content)?;
+        let mut created = Created {
+            module_path: mod_path.clone(),
+            test_path: None,
+            super_modified: None,
+            declaration_location: None,
+            declaration_lines: Vec::new(),
+            test_location: None,
+            existed: false,
+            module_modified: None,
+            parents: Vec::new(),
+            metrics,
+        };
+
+        if let Some((test_path, test_content)) = test_file {
+            if let Some(parent) = test_path.parent() {
+                self.create_dir_all(parent)?;
+            }
+
+            self.write(&test_path, &test_content)?;
+            created.test_location = Some(Location {
+                path: test_path.clone(),
+                line: test_content.lines().count() + 1,
+                column: 1,
+            });
+
+            created.test_path = Some(test_path);
+        } else if test.is_some_and(|test| test.location == TestLocation::Inline) {
+            // before the closing brace of the inline test module
+            created.test_location = Some(Location {
+                path: mod_path,
+                line: mod_content.lines().count(),
+                column: 1,
+            });
+        }
+
+        Ok(created)
+    }
+
+    /// Make a directory module. See [`crate::make_mod_dir`].
+    pub(crate) fn make_mod_dir(
+        &mut self,
+        path: &Path,
+        content: &ContentOptions,
+        test: Option<&TestOptions>,
+    ) -> Result<Created> {
+        self.create_dir(path)?;
+
+        let mut created = self.make_mod_file(&path.join("mod"), content, test)?;
+        created.module_path = path.to_path_buf();
+        Ok(created)
+    }
+
+    /// Complete an existing module, adding the pieces creating it would have that are missing.
+    ///
+    /// The module is declared in its super if it is not, and its test file is declared in it
+    /// if the test file exists but is not declared. Modules that declare a test file,
+    /// or have inline tests, are left as they are.
+    ///
+    /// # Arguments
+    /// + `path`: Path of the module, without extension. e.g. `src/my_mod`.
+    /// + `content`: Options for the contents of a created test file.
+    /// + `test`: Options for the test module, or `None` to leave the module's tests as they are.
+    /// + `add_to_super`: Options for adding the module to its super, or `None` to not add it.
+    /// + `update`: Create the module's test file if it is missing.
+    ///
+    /// # Errors
+    /// + If the module has no file, e.g. a directory without a `mod.rs`.
+    /// + If a file that must be modified has unstaged changes, unless allowed.
+    /// + [`Error::Declared`] if the module's declaration can not be widened to the requested visibility.
+    pub(crate) fn complete(
+        &mut self,
+        path: &Path,
+        content: &ContentOptions,
+        test: Option<&TestOptions>,
+        add_to_super: Option<&SuperOptions>,
+        update: bool,
+    ) -> Result<Created> {
+        let file = match existing_module_file(&*self.fs, path) {
+            Some(file) => file,
+            None => return Err(Error::ModuleExists(path.to_path_buf())),
+        };
+
+        let module_path = match file.file_name().is_some_and(|name| name == "mod.rs") {
+            true => path.to_path_buf(),
+            false => file.clone(),
+        };
+
+        let mut created = Created {
+            module_path,
+            test_path: None,
+            super_modified: None,
+            declaration_location: None,
+            declaration_lines: Vec::new(),
+            test_location: None,
+            existed: true,
+            module_modified: None,
+            parents: Vec::new(),
+            metrics: Metrics::default(),
+        };
+
+        if let Some(test) = test.filter(|test| test.location != TestLocation::Inline) {
+            if let Some(test_file) = crate::test_path(&*self.fs, &file, test)? {
+                self.complete_tests(&file, &test_file, content, test, add_to_super, update, &mut created)?;
+            }
+        }
+
+        let Some(options) = add_to_super else {
+            return Ok(created);
+        };
+
+        let super_file = crate::resolve_super(&*self.fs, path, options)?;
+        let name = crate::module_name(path)?.to_string_lossy().into_owned();
+        let super_content = self.fs.read_to_string(&super_file)?;
+        if let crate::Addition::Keep(_) = crate::addition(&super_content, &super_file, &name, options)? {
+            return Ok(created);
+        }
+
+        let super_file = crate::checked_super_path(&*self.fs, path, options)?;
+        let start = Instant::now();
+        let (location, lines) = self.declare(&name, &super_file, options)?;
+        created.metrics.super_rewrite.record(start.elapsed());
+        created.declaration_location = Some(location);
+        created.declaration_lines = lines;
+        created.super_modified = Some(super_file);
+        Ok(created)
+    }
+
+    /// Declare an existing module's test file in it, creating the test file if requested.
+    /// Modules that declare a test file, or have inline tests, are left as they are.
+    ///
+    /// # Arguments
+    /// + `file`: File of the module.
+    /// + `test_file`: Path of the module's test file.
+    /// + `created`: Record of the completed module, updated with the files touched.
+    /// + See [`Self::complete`] for the others.
+    #[allow(clippy::too_many_arguments)]
+    fn complete_tests(
+        &mut self,
+        file: &Path,
+        test_file: &Path,
+        content: &ContentOptions,
+        test: &TestOptions,
+        add_to_super: Option<&SuperOptions>,
+        update: bool,
+        created: &mut Created,
+    ) -> Result {
+        let mod_content = self.fs.read_to_string(file)?;
+        let re_inline = Regex::new(r"(?m)^\s*mod\s+tests?\s*\{")?;
+        if crate::remove::test_file(file, &mod_content)?.is_some() || re_inline.is_match(&mod_content) {
+            return Ok(());
+        }
+
+        if !self.fs.exists(test_file) && !update {
+            return Ok(());
+        }
+
+        crate::check_modifiable(&*self.fs, file, add_to_super.unwrap_or(&SuperOptions::default()))?;
+
+        if !self.fs.exists(test_file) {
+            let (_, rendered) = created
+                .metrics
+                .template_render
+                .time(|| crate::render_mod_file(&*self.fs, &file.with_extension(""), content, Some(test)))?;
+            let test_content = match rendered {
+                Some((_, test_content)) => test_content,
+                None => return Ok(()),
+            };
+
+            if let Some(parent) = test_file.parent() {
+                self.create_dir_all(parent)?;
+            }
+
+            self.write(test_file, &test_content)?;
+            created.test_location = Some(Location {
+                path: test_file.to_path_buf(),
+                line: test_content.lines().count() + 1,
+                column: 1,
+            });
+
+            created.test_path = Some(test_file.to_path_buf());
+        }
+
+        let name = match file.file_stem().and_then(OsStr::to_str) {
+            Some(name) => name,
+            None => return Err(Error::InvalidPath(file.to_path_buf())),
+        };
+
+        let mod_dir = match crate::project::absolute_path_in(&*self.fs, file)?.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return Err(Error::InvalidPath(file.to_path_buf())),
+        };
+
+        let test_file = crate::project::absolute_path_in(&*self.fs, test_file)?;
+        let rel_test_path = crate::project::relative_path(&mod_dir, &test_file);
+        let mut new_content = mod_content.clone();
+        if !new_content.is_empty() {
+            if !new_content.ends_with('\n') {
+                new_content.push('\n');
+            }
+
+            new_content.push('\n');
+        }
+
+        new_content.push_str(&crate::file_template_with_test(name, &rel_test_path, &test.cfg));
+        crate::validate("module", &new_content)?;
+        self.write(file, &new_content)?;
+        created.module_modified = Some(file.to_path_buf());
+        Ok(())
+    }
+
+    /// Create the missing ancestors of a module, so it can be created. See [`crate::create_parents`].
+    ///
+    /// # Returns
+    /// The ancestors created, outermost first.
+    pub(crate) fn create_parents(
+        &mut self,
+        path: &Path,
+        content: &ContentOptions,
+        add_to_super: Option<&SuperOptions>,
+    ) -> Result<Vec<Created>> {
+        // the super given for the module is not the super of its ancestors
+        let parent_options = add_to_super.map(|options| SuperOptions { super_file: None, ..options.clone() });
+
+        let mut created = Vec::new();
+        for dir in missing_ancestors(&*self.fs, path).into_iter().rev() {
+            if self.fs.is_file(&dir.with_extension("rs")) {
+                self.create_dir(dir)?;
+            } else {
+                created.push(self.create_module(dir, true, content, None, parent_options.as_ref())?);
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// Declare a module in a super file, keeping or widening an existing declaration.
+    ///
+    /// # Arguments
+    /// + `mod_name`: Name of the module.
+    /// + `super_file`: File to declare the module in.
+    /// + `options`: Options for adding the module.
+    ///
+    /// # Returns
+    /// Location of the declaration, and the 1-based lines inserted.
+    pub(crate) fn declare(
+        &mut self,
+        mod_name: &str,
+        super_file: &Path,
+        options: &SuperOptions,
+    ) -> Result<(Location, Vec<usize>)> {
+        let content = self.fs.read_to_string(super_file)?;
+        if syn::parse_file(&content).is_err() {
+            self.events.push(Event::Warning(format!(
+                "{} does not parse, so the declaration was placed by matching lines", super_file.display()
+            )));
+        }
+
+        let (new, location, lines) = crate::declare(&content, super_file, mod_name, options)?;
+        if new != content {
+            self.write(super_file, &new)?;
+        }
+
+        Ok((location, lines))
+    }
+
+    /// Write a file, recording its original content.
+    fn write(&mut self, path: &Path, content: &str) -> Result {
+        let original = match self.fs.is_file(path) {
+            true => Some(self.fs.read_to_string(path)?),
+            false => None,
+        };
+
+        self.fs.write(path, content)?;
+        self.events.push(match original {
+            Some(_) => Event::Modified(path.to_path_buf()),
+            None => Event::Created(path.to_path_buf()),
+        });
+
+        self.changes.push(Change::File(path.to_path_buf(), original));
+        Ok(())
+    }
+
+    /// Create a directory, recording it.
+    fn create_dir(&mut self, path: &Path) -> Result {
+        self.fs.create_dir(path)?;
+        self.changes.push(Change::Dir(path.to_path_buf()));
+        Ok(())
+    }
+
+    /// Create a directory and its missing ancestors, recording them.
+    fn create_dir_all(&mut self, path: &Path) -> Result {
+        let missing: Vec<&Path> = path
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !self.fs.is_dir(dir))
+            .collect();
+
+        for dir in missing.into_iter().rev() {
+            self.create_dir(dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the file of an existing module.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `path`: Path of the module, without extension. e.g. `src/my_mod`.
+///
+/// # Returns
+/// `<path>.rs` if it exists, otherwise `<path>/mod.rs` if it exists.
+pub(crate) fn existing_module_file(fs: &dyn FileSystem, path: &Path) -> Option<PathBuf> {
+    [path.with_extension("rs"), path.join("mod.rs")].into_iter().find(|file| fs.is_file(file))
+}
+
+/// Get the missing ancestors of a path, from the innermost outwards,
+/// stopping at the first existing directory.
+pub(crate) fn missing_ancestors<'p>(fs: &dyn FileSystem, path: &'p Path) -> Vec<&'p Path> {
+    let mut missing = Vec::new();
+    let mut ancestor = path.parent();
+    while let Some(dir) = ancestor {
+        if dir.as_os_str().is_empty() || fs.is_dir(dir) {
+            break;
+        }
+
+        missing.push(dir);
+        ancestor = dir.parent();
+    }
+
+    missing
+}
+
+#[cfg(test)]
+#[path = "./creator_test.rs"]
+mod creator_test;
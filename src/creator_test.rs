@@ -0,0 +1,139 @@
+use super::*;
+use crate::visibility::Visibility;
+
+#[test]
+fn creator_should_create_modules_in_memory() {
+    let mut fs = MemoryFileSystem::new()
+        .with_file("Cargo.toml", "[package]\nname = \"a\"\n")
+        .with_file("src/lib.rs", "use std::fs;\n\nfn f() {}\n")
+        .with_file("src/net.rs", "mod tcp;\n");
+
+    let mut events = Vec::new();
+    let mut creator = ModuleCreator::new(&mut fs).on_event(|event| events.push(event));
+    let created = creator.create(&CreateOptions::new("src/net/udp")).unwrap();
+    let options = CreateOptions::new("src/util").dir(true).with_test(false).vis(Visibility::Crate);
+    creator.create(&options).unwrap();
+    drop(creator);
+
+    assert_eq!(created.module_path, PathBuf::from("src/net/udp.rs"));
+    assert_eq!(created.test_path, Some(PathBuf::from("src/net/udp_test.rs")));
+    assert_eq!(created.super_modified, Some(PathBuf::from("/src/net.rs")));
+    assert_eq!(created.declaration_lines, vec![2]);
+    assert_eq!(
+        events,
+        [
+            Event::Created(PathBuf::from("src/net/udp.rs")),
+            Event::Created(PathBuf::from("src/net/udp_test.rs")),
+            Event::Modified(PathBuf::from("/src/net.rs")),
+            Event::Created(PathBuf::from("src/util/mod.rs")),
+            Event::Modified(PathBuf::from("/src/lib.rs")),
+        ]
+    );
+
+    assert_eq!(fs.files()[Path::new("/src/net.rs")], "mod tcp;\npub mod udp;\n");
+    assert_eq!(fs.files()[Path::new("/src/lib.rs")], "use std::fs;\npub(crate) mod util;\n\nfn f() {}\n");
+    assert_eq!(fs.files()[Path::new("/src/util/mod.rs")], "");
+    assert!(fs.files()[Path::new("/src/net/udp.rs")].contains("#[path = \"./udp_test.rs\"]"));
+    assert!(!Path::new("src/net").exists());
+}
+
+#[test]
+fn creator_should_not_write_anything_on_errors() {
+    let mut fs = MemoryFileSystem::new()
+        .with_file("Cargo.toml", "[package]\nname = \"a\"\n")
+        .with_file("src/lib.rs", "mod a;\n")
+        .with_file("src/a.rs", "");
+
+    let original = fs.clone();
+    let mut events = Vec::new();
+    let mut creator = ModuleCreator::new(&mut fs).on_event(|event| events.push(event));
+    assert!(matches!(creator.create(&CreateOptions::new("src/a")), Err(Error::ModuleExists(_))));
+    assert!(matches!(creator.create(&CreateOptions::new("src/b/c")), Err(Error::ParentNotFound(_))));
+    assert!(matches!(creator.create(&CreateOptions::new("src/a b")), Err(Error::InvalidModuleName(_))));
+
+    let options = SuperOptions { super_file: Some(PathBuf::from("src/missing.rs")), ..SuperOptions::default() };
+    let res = creator.create(&CreateOptions::new("src/c").super_options(options));
+    assert!(matches!(res, Err(Error::ParentNotFound(_))));
+    drop(creator);
+
+    assert!(events.is_empty());
+    assert_eq!(fs, original);
+}
+
+#[test]
+fn creator_should_create_missing_parents_and_undo_them_on_errors() {
+    let mut fs = MemoryFileSystem::new()
+        .with_file("Cargo.toml", "[package]\nname = \"a\"\n")
+        .with_file("src/lib.rs", "")
+        .with_file("src/x.rs", "");
+
+    let mut creator = ModuleCreator::new(&mut fs);
+    let options = CreateOptions::new("src/a/b/c").with_test(false).parents(ContentOptions::default());
+    let plan = creator.plan(&options).unwrap();
+    assert_eq!(plan.module_path, PathBuf::from("/src/a/b/c.rs"));
+    assert_eq!(plan.super_file, Some(PathBuf::from("/src/a/b/mod.rs")));
+    assert_eq!(
+        plan.parents,
+        ["/src/a", "/src/a/mod.rs", "/src/lib.rs", "/src/a/b", "/src/a/b/mod.rs"].map(PathBuf::from)
+    );
+
+    let preview = creator.preview(&options).unwrap();
+    assert_eq!(preview.created.len(), 3);
+    assert_eq!(preview.modified.len(), 1);
+
+    let created = creator.create(&options).unwrap();
+    assert_eq!(created.parents.len(), 2);
+
+    // the parents of a module that can not be created are removed
+    let super_file = SuperOptions { super_file: Some(PathBuf::from("src/missing.rs")), ..SuperOptions::default() };
+    let options = CreateOptions::new("src/x/y/z").with_test(false).parents(ContentOptions::default());
+    assert!(creator.create(&options.clone().super_options(super_file)).is_err());
+    drop(creator);
+
+    assert_eq!(fs.files()[Path::new("/src/lib.rs")], "pub mod a;\n");
+    assert_eq!(fs.files()[Path::new("/src/a/mod.rs")], "pub mod b;\n");
+    assert_eq!(fs.files()[Path::new("/src/a/b/mod.rs")], "pub mod c;\n");
+    assert_eq!(fs.files()[Path::new("/src/x.rs")], "");
+    assert!(!fs.is_dir(Path::new("/src/x")));
+
+    let created = ModuleCreator::new(&mut fs).create(&options).unwrap();
+    assert_eq!(created.parents.len(), 1);
+    assert_eq!(created.parents[0].module_path, PathBuf::from("src/x/y"));
+    assert_eq!(fs.files()[Path::new("/src/x.rs")], "pub mod y;\n");
+    assert_eq!(fs.files()[Path::new("/src/x/y/mod.rs")], "pub mod z;\n");
+}
+
+#[test]
+fn creator_should_ask_the_file_system_version_control() {
+    let vcs = MemoryVcs::new()
+        .with_unstaged("src/lib.rs")
+        .with_staged("src/net.rs")
+        .with_user_name("Jane Doe");
+
+    let mut fs = MemoryFileSystem::new()
+        .with_file("Cargo.toml", "[package]\nname = \"a\"\n")
+        .with_file("src/lib.rs", "mod net;\n")
+        .with_file("src/net.rs", "")
+        .with_vcs(vcs);
+
+    let mut creator = ModuleCreator::new(&mut fs);
+    let res = creator.create(&CreateOptions::new("src/a").with_test(false));
+    assert!(matches!(res, Err(Error::Dirty(path)) if path == Path::new("/src/lib.rs")));
+
+    let require_clean = SuperOptions { require_clean: true, ..SuperOptions::default() };
+    let res = creator.create(&CreateOptions::new("src/net/tcp").with_test(false).super_options(require_clean));
+    assert!(matches!(res, Err(Error::Uncommitted(path)) if path == Path::new("/src/net.rs")));
+
+    let content = ContentOptions {
+        templates: crate::config::Templates {
+            header: Some(String::from("// Copyright {{ author }}")),
+            ..crate::config::Templates::default()
+        },
+        ..ContentOptions::default()
+    };
+
+    creator.create(&CreateOptions::new("src/net/udp").with_test(false).content(content)).unwrap();
+    drop(creator);
+
+    assert!(fs.files()[Path::new("/src/net/udp.rs")].starts_with("// Copyright Jane Doe\n"));
+}
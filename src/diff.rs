@@ -0,0 +1,252 @@
+//! Line based unified diffs, used to preview changes to files.
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Kind of a line in an edit script.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A line in an edit script.
+#[derive(Clone, Copy, Debug)]
+struct Op {
+    kind: Kind,
+
+    /// Index of the old line at this point of the script.
+    old: usize,
+
+    /// Index of the new line at this point of the script.
+    new: usize,
+}
+
+/// Result of a three-way merge.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merge {
+    /// Merged text, with conflicting changes marked as by git.
+    pub content: String,
+
+    /// Number of conflicting changes.
+    pub conflicts: usize,
+}
+
+/// Create a unified diff between two texts.
+///
+/// # Arguments
+/// + `old`: Original text.
+/// + `new`: Modified text.
+/// + `old_name`: Name of the original text in the diff header.
+/// + `new_name`: Name of the modified text in the diff header.
+///
+/// # Returns
+/// The diff, or an empty string if the texts have the same lines.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = edit_script(&old_lines, &new_lines);
+    if ops.iter().all(|op| op.kind == Kind::Equal) {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    for (start, end) in hunks(&ops) {
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|op| op.kind != Kind::Insert).count();
+        let new_len = hunk.iter().filter(|op| op.kind != Kind::Delete).count();
+        let old_start = if old_len == 0 { hunk[0].old } else { hunk[0].old + 1 };
+        let new_start = if new_len == 0 { hunk[0].new } else { hunk[0].new + 1 };
+        diff.push_str(&format!("@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"));
+
+        for op in hunk {
+            let line = match op.kind {
+                Kind::Equal => format!(" {}", old_lines[op.old]),
+                Kind::Delete => format!("-{}", old_lines[op.old]),
+                Kind::Insert => format!("+{}", new_lines[op.new]),
+            };
+
+            diff.push_str(&line);
+            diff.push('\n');
+        }
+    }
+
+    diff
+}
+
+/// Get the lines of a modified text that are not in the original, i.e. inserted or changed.
+///
+/// # Returns
+/// 1-based line numbers in the modified text.
+pub fn inserted_lines(old: &str, new: &str) -> Vec<usize> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    edit_script(&old_lines, &new_lines)
+        .into_iter()
+        .filter(|op| op.kind == Kind::Insert)
+        .map(|op| op.new + 1)
+        .collect()
+}
+
+/// Merge the changes two texts made to a common original, line by line.
+///
+/// Changes to the same lines conflict unless they are the same. Conflicts are marked with
+/// `<<<<<<< {ours_name}`, `=======`, and `>>>>>>> {theirs_name}` lines around both changes.
+///
+/// # Arguments
+/// + `base`: Original text.
+/// + `ours`: One modified text.
+/// + `theirs`: Other modified text.
+/// + `ours_name`: Name of `ours` in conflict markers.
+/// + `theirs_name`: Name of `theirs` in conflict markers.
+pub fn merge(base: &str, ours: &str, theirs: &str, ours_name: &str, theirs_name: &str) -> Merge {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let our_lines: Vec<&str> = ours.lines().collect();
+    let their_lines: Vec<&str> = theirs.lines().collect();
+    let our_kept = kept_lines(&base_lines, &our_lines);
+    let their_kept = kept_lines(&base_lines, &their_lines);
+
+    let mut content = String::new();
+    let mut conflicts = 0;
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // changes are the lines between original lines both texts kept
+        let stable = (b..base_lines.len()).find_map(|i| match (our_kept[i], their_kept[i]) {
+            (Some(oi), Some(ti)) => Some((i, oi, ti)),
+            _ => None,
+        });
+
+        let (end_b, end_o, end_t) = stable.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+        let base_chunk = &base_lines[b..end_b];
+        let our_chunk = &our_lines[o..end_o];
+        let their_chunk = &their_lines[t..end_t];
+        if our_chunk == base_chunk {
+            push_lines(&mut content, their_chunk);
+        } else if their_chunk == base_chunk || our_chunk == their_chunk {
+            push_lines(&mut content, our_chunk);
+        } else {
+            conflicts += 1;
+            push_lines(&mut content, &[&format!("<<<<<<< {ours_name}")]);
+            push_lines(&mut content, our_chunk);
+            push_lines(&mut content, &["======="]);
+            push_lines(&mut content, their_chunk);
+            push_lines(&mut content, &[&format!(">>>>>>> {theirs_name}")]);
+        }
+
+        match stable {
+            Some((end_b, end_o, end_t)) => {
+                push_lines(&mut content, &[base_lines[end_b]]);
+                (b, o, t) = (end_b + 1, end_o + 1, end_t + 1);
+            },
+
+            None => break,
+        }
+    }
+
+    Merge { content, conflicts }
+}
+
+/// Append lines to a text.
+fn push_lines(content: &mut String, lines: &[&str]) {
+    for line in lines {
+        content.push_str(line);
+        content.push('\n');
+    }
+}
+
+/// Map the lines of an original text to the lines of a modified text they are kept as.
+///
+/// # Returns
+/// For each original line, the index of the modified line it is kept as, or `None` if it was removed or changed.
+fn kept_lines(old: &[&str], new: &[&str]) -> Vec<Option<usize>> {
+    let mut kept = vec![None; old.len()];
+    for op in edit_script(old, new) {
+        if op.kind == Kind::Equal {
+            kept[op.old] = Some(op.new);
+        }
+    }
+
+    kept
+}
+
+/// Compute a minimal edit script transforming `old` into `new`.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Op> {
+    // strip common prefix and suffix to keep the table small
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0_usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    for k in 0..prefix {
+        ops.push(Op { kind: Kind::Equal, old: k, new: k });
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let (old, new) = (prefix + i, prefix + j);
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push(Op { kind: Kind::Equal, old, new });
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op { kind: Kind::Delete, old, new });
+            i += 1;
+        } else {
+            ops.push(Op { kind: Kind::Insert, old, new });
+            j += 1;
+        }
+    }
+
+    for k in 0..suffix {
+        let (old, new) = (prefix + a.len() + k, prefix + b.len() + k);
+        ops.push(Op { kind: Kind::Equal, old, new });
+    }
+
+    ops
+}
+
+/// Group changes of an edit script into hunks with surrounding context.
+///
+/// # Returns
+/// `(start, end)` ranges of the edit script.
+fn hunks(ops: &[Op]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, op) in ops.iter().enumerate() {
+        if op.kind == Kind::Equal {
+            continue;
+        }
+
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+#[cfg(test)]
+#[path = "./diff_test.rs"]
+mod diff_test;
@@ -0,0 +1,57 @@
+use super::*;
+
+#[test]
+fn identical_texts_should_have_empty_diff() {
+    assert_eq!(unified("a\nb\n", "a\nb\n", "a", "b"), "");
+}
+
+#[test]
+fn inserted_line_should_be_shown_with_context() {
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+    let new = "1\n2\n3\n4\nnew\n5\n6\n7\n8\n";
+    assert_eq!(
+        unified(old, new, "a/lib.rs", "b/lib.rs"),
+        "--- a/lib.rs\n+++ b/lib.rs\n@@ -2,6 +2,7 @@\n 2\n 3\n 4\n+new\n 5\n 6\n 7\n"
+    );
+}
+
+#[test]
+fn inserted_and_changed_lines_should_be_numbered_in_new_text() {
+    assert_eq!(inserted_lines("a\nb\nc\n", "a\nnew\nb\nC\n"), [2, 4]);
+    assert_eq!(inserted_lines("a\n", "a\n"), Vec::<usize>::new());
+}
+
+#[test]
+fn distant_changes_should_be_separate_hunks() {
+    let old = "a\n1\n2\n3\n4\n5\n6\n7\n8\nb\n";
+    let new = "A\n1\n2\n3\n4\n5\n6\n7\n8\nB\n";
+    let diff = unified(old, new, "old", "new");
+    assert_eq!(diff.matches("@@ -").count(), 2);
+    assert!(diff.contains("@@ -1,4 +1,4 @@\n-a\n+A\n 1\n"));
+    assert!(diff.contains("@@ -7,4 +7,4 @@\n 6\n 7\n 8\n-b\n+B\n"));
+}
+
+#[test]
+fn empty_original_should_be_all_insertions() {
+    assert_eq!(unified("", "a\n", "old", "new"), "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n");
+}
+
+#[test]
+fn merge_should_combine_changes_to_different_lines() {
+    let base = "a\nb\nc\nd\ne\n";
+    let merged = merge(base, "a\nB\nc\nd\ne\nf\n", "x\na\nb\nc\nD\ne\n", "ours", "theirs");
+    assert_eq!(merged, Merge { content: String::from("x\na\nB\nc\nD\ne\nf\n"), conflicts: 0 });
+
+    // changes to adjacent lines conflict, as with git
+    assert_eq!(merge(base, "a\nb\nc\nD\ne\n", "a\nb\nC\nd\ne\n", "ours", "theirs").conflicts, 1);
+}
+
+#[test]
+fn merge_should_mark_conflicting_changes() {
+    let merged = merge("a\nb\nc\n", "a\nours\nc\n", "a\ntheirs\nc\n", "current", "template");
+    assert_eq!(merged.content, "a\n<<<<<<< current\nours\n=======\ntheirs\n>>>>>>> template\nc\n");
+    assert_eq!(merged.conflicts, 1);
+
+    let merged = merge("a\nb\n", "a\nsame\n", "a\nsame\n", "current", "template");
+    assert_eq!(merged, Merge { content: String::from("a\nsame\n"), conflicts: 0 });
+}
@@ -0,0 +1,360 @@
+//! Extraction of items from a module into a new submodule.
+use crate::project::{self, Project};
+use crate::result::{Error, Result};
+use crate::visibility::Visibility;
+use crate::{name, DiskFileSystem, SuperOptions};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+
+/// Move items from a module into a new submodule, re-exporting them so callers keep compiling.
+///
+/// Each item is moved with its attributes, documentation, and the comments directly above it,
+/// along with the `impl` blocks for it. The new module starts with `use super::*;`,
+/// so the items can still refer to the rest of the module.
+///
+/// Private items, fields, and inherent methods become `pub(super)`, so the module can still
+/// use them, and visibilities relative to the module, such as `pub(super)`, become `pub(crate)`.
+/// Each item is re-exported with its original visibility.
+///
+/// # Arguments
+/// + `file`: File of the module containing the items.
+/// + `items`: Names of the items to move.
+/// + `into`: Name of the new module.
+/// + `options`: Options for declaring the new module in `file`.
+///
+/// # Returns
+/// Files created or modified.
+///
+/// # Errors
+/// + If `into` is not a valid module name, or a module of that name already exists.
+/// + If `file` does not parse, or an item is not defined at its top level.
+/// + If `file` has unstaged changes, unless allowed.
+pub fn extract(file: &Path, items: &[String], into: &str, options: &SuperOptions) -> Result<Vec<PathBuf>> {
+    name::validate(into)?;
+    let file = project::absolute_path(file)?;
+    let content = fs::read_to_string(&file)?;
+    let parsed = syn::parse_file(&content).map_err(|err| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} does not parse: {err}", file.display()))
+    })?;
+
+    let dir = submodule_dir(&file)?;
+    let new_file = dir.join(format!("{into}.rs"));
+    for existing in [&new_file, &dir.join(into)] {
+        if existing.exists() {
+            return Err(Error::ModuleExists(existing.clone()));
+        }
+    }
+
+    crate::check_modifiable(&DiskFileSystem, &file, options)?;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut cuts: Vec<Cut> = Vec::new();
+    let mut exports: Vec<(String, String)> = Vec::new();
+    for item_name in items {
+        let item = parsed
+            .items
+            .iter()
+            .find(|item| defined_name(item).is_some_and(|defined| defined == item_name.as_str()))
+            .ok_or_else(|| Error::ItemNotFound { path: file.clone(), item: item_name.clone() })?;
+
+        exports.push((visibility_text(item, &lines), item_name.clone()));
+        cuts.push(cut(item, &lines)?);
+
+        for item in &parsed.items {
+            if let syn::Item::Impl(item_impl) = item {
+                if impl_target(item_impl).is_some_and(|target| target == item_name.as_str()) {
+                    cuts.push(cut(item, &lines)?);
+                }
+            }
+        }
+    }
+
+    cuts.sort_by_key(|cut| cut.start);
+    cuts.dedup_by_key(|cut| cut.start);
+
+    // the new module, with the items in their original order
+    let mut module = String::from("use super::*;\n");
+    for cut in &cuts {
+        module.push('\n');
+        module.push_str(&cut.text);
+    }
+
+    crate::validate("module", &module)?;
+
+    // the module's file with the items removed, and the new module declared and re-exported
+    let mut remaining: Vec<&str> = Vec::new();
+    let mut index = 0;
+    for cut in &cuts {
+        remaining.extend(&lines[index..cut.start]);
+        index = cut.end + 1;
+
+        // do not leave two blank lines where an item was
+        let before_blank = remaining.last().is_none_or(|line| line.trim().is_empty());
+        if before_blank && lines.get(index).is_some_and(|line| line.trim().is_empty()) {
+            index += 1;
+        }
+    }
+
+    remaining.extend(&lines[index.min(lines.len())..]);
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut remaining = remaining.join(newline);
+    if content.ends_with('\n') {
+        remaining.push_str(newline);
+    }
+
+    let mut declaration = crate::declaration(into, options);
+    for (vis, names) in grouped(&exports) {
+        let names = match names.len() {
+            1 => names[0].clone(),
+            _ => format!("{{{}}}", names.join(", ")),
+        };
+
+        let vis = if vis.is_empty() { vis } else { format!("{vis} ") };
+        declaration.push_str(&format!("\n{vis}use {}::{names};", name::ident(into)));
+    }
+
+    let mut new = File::create_new(&new_file)?;
+    let res = new.write_all(module.as_bytes()).map_err(Error::from).and_then(|_| {
+        fs::write(&file, &remaining)?;
+        let insert = crate::insert_line(&remaining, into, options)?;
+        crate::insert_mod_at_line(&declaration, insert, &file)
+    });
+
+    if let Err(err) = res {
+        let _ = fs::write(&file, &content);
+        let _ = fs::remove_file(&new_file);
+        return Err(err);
+    }
+
+    Ok(vec![new_file, file])
+}
+
+/// Lines of an item to move, and its text in the new module.
+struct Cut {
+    /// 0-based first line.
+    start: usize,
+
+    /// 0-based last line.
+    end: usize,
+    text: String,
+}
+
+/// Cut an item's lines from a file, adjusting its visibilities for the new module.
+///
+/// # Errors
+/// + If the item shares a line with other code.
+fn cut(item: &syn::Item, lines: &[&str]) -> Result<Cut> {
+    let span = item.span();
+    let (mut start, end) = (span.start().line - 1, span.end().line - 1);
+    let shared = lines[start][..byte_offset(lines[start], span.start().column)].trim() != ""
+        || lines[end][byte_offset(lines[end], span.end().column)..].trim() != "";
+
+    if shared {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("item at line {} shares a line with other code, move it to its own lines first", start + 1),
+        )
+        .into());
+    }
+
+    // comments directly above the item belong to it
+    while start > 0 && lines[start - 1].trim_start().starts_with("//") {
+        start -= 1;
+    }
+
+    // edit from the end, so earlier positions stay valid
+    let mut item_lines: Vec<String> = lines[start..=end].iter().map(|line| line.to_string()).collect();
+    let mut edits = visibility_edits(item);
+    edits.sort_by_key(|(position, ..)| *position);
+    for (position, len, vis) in edits.into_iter().rev() {
+        let line = &mut item_lines[position.line - 1 - start];
+        let offset = byte_offset(line, position.column);
+        let replaced = byte_offset(line, position.column + len);
+        let vis = if len == 0 { format!("{vis} ") } else { vis.to_string() };
+        line.replace_range(offset..replaced, &vis);
+    }
+
+    let mut text = item_lines.join("\n");
+    text.push('\n');
+    Ok(Cut { start, end, text })
+}
+
+/// Edits to the visibilities of an item and its parts, so they mean the same one module deeper.
+/// Each is the position and character length of the visibility to replace, and its replacement.
+///
+/// Private parts become `pub(super)`, so the module the item is moved from can still use them,
+/// and visibilities relative to the module become `pub(crate)`.
+/// The item's fields, and the methods and constants of its inherent `impl` blocks are included.
+fn visibility_edits(item: &syn::Item) -> Vec<(proc_macro2::LineColumn, usize, &'static str)> {
+    let mut edits = Vec::new();
+    let mut edit = |vis: &syn::Visibility, start: proc_macro2::LineColumn| {
+        let replacement = match relative(vis) {
+            Some(Visibility::Private) => "pub(super)",
+            Some(_) => "pub(crate)",
+            None => return,
+        };
+
+        let (start, len) = match vis {
+            syn::Visibility::Inherited => (start, 0),
+            vis => {
+                let span = vis.span();
+                (span.start(), span.end().column - span.start().column)
+            },
+        };
+
+        edits.push((start, len, replacement));
+    };
+
+    let fields = |fields: &syn::Fields, edit: &mut dyn FnMut(&syn::Visibility, proc_macro2::LineColumn)| {
+        for field in fields {
+            let start = match &field.ident {
+                Some(ident) => ident.span().start(),
+                None => field.ty.span().start(),
+            };
+
+            edit(&field.vis, start);
+        }
+    };
+
+    match item {
+        syn::Item::Struct(item) => {
+            edit(&item.vis, item.struct_token.span.start());
+            fields(&item.fields, &mut edit);
+        },
+
+        syn::Item::Union(item) => {
+            edit(&item.vis, item.union_token.span.start());
+            fields(&syn::Fields::Named(item.fields.clone()), &mut edit);
+        },
+
+        syn::Item::Enum(item) => edit(&item.vis, item.enum_token.span.start()),
+        syn::Item::Fn(item) => edit(&item.vis, item.sig.span().start()),
+        syn::Item::Trait(item) => {
+            let start = match (&item.unsafety, &item.auto_token) {
+                (Some(unsafety), _) => unsafety.span.start(),
+                (None, Some(auto)) => auto.span.start(),
+                (None, None) => item.trait_token.span.start(),
+            };
+
+            edit(&item.vis, start);
+        },
+
+        syn::Item::Type(item) => edit(&item.vis, item.type_token.span.start()),
+        syn::Item::Const(item) => edit(&item.vis, item.const_token.span.start()),
+        syn::Item::Static(item) => edit(&item.vis, item.static_token.span.start()),
+        syn::Item::Impl(item) if item.trait_.is_none() => {
+            for impl_item in &item.items {
+                match impl_item {
+                    syn::ImplItem::Fn(f) => edit(&f.vis, f.sig.span().start()),
+                    syn::ImplItem::Const(c) => edit(&c.vis, c.const_token.span.start()),
+                    syn::ImplItem::Type(t) => edit(&t.vis, t.type_token.span.start()),
+                    _ => {},
+                }
+            }
+        },
+
+        _ => {},
+    }
+
+    edits
+}
+
+/// Visibility relative to the module an item is in, or `None` if it does not depend on the module.
+/// `pub(self)` is private, and `pub(super)` and `pub(in super::a)` are `pub(super)`.
+fn relative(vis: &syn::Visibility) -> Option<Visibility> {
+    match vis {
+        syn::Visibility::Public(_) => None,
+        syn::Visibility::Inherited => Some(Visibility::Private),
+        syn::Visibility::Restricted(restricted) => {
+            let first = restricted.path.segments.first().map(|segment| segment.ident.to_string());
+            match first.as_deref() {
+                Some("self") if restricted.path.segments.len() == 1 => Some(Visibility::Private),
+                Some("self" | "super") => Some(Visibility::Super),
+                _ => None,
+            }
+        },
+    }
+}
+
+/// Name an item defines, for items that can be extracted.
+fn defined_name(item: &syn::Item) -> Option<String> {
+    let ident = match item {
+        syn::Item::Struct(item) => &item.ident,
+        syn::Item::Enum(item) => &item.ident,
+        syn::Item::Union(item) => &item.ident,
+        syn::Item::Fn(item) => &item.sig.ident,
+        syn::Item::Trait(item) => &item.ident,
+        syn::Item::Type(item) => &item.ident,
+        syn::Item::Const(item) => &item.ident,
+        syn::Item::Static(item) => &item.ident,
+        _ => return None,
+    };
+
+    let ident = ident.to_string();
+    Some(ident.strip_prefix("r#").map(str::to_string).unwrap_or(ident))
+}
+
+/// Visibility of an item as written, or empty if it is private.
+fn visibility_text(item: &syn::Item, lines: &[&str]) -> String {
+    let vis = match item {
+        syn::Item::Struct(item) => &item.vis,
+        syn::Item::Enum(item) => &item.vis,
+        syn::Item::Union(item) => &item.vis,
+        syn::Item::Fn(item) => &item.vis,
+        syn::Item::Trait(item) => &item.vis,
+        syn::Item::Type(item) => &item.vis,
+        syn::Item::Const(item) => &item.vis,
+        syn::Item::Static(item) => &item.vis,
+        _ => return String::new(),
+    };
+
+    if matches!(vis, syn::Visibility::Inherited) {
+        return String::new();
+    }
+
+    let span = vis.span();
+    let line = lines[span.start().line - 1];
+    line[byte_offset(line, span.start().column)..byte_offset(line, span.end().column)].to_string()
+}
+
+/// Name of the type an `impl` block is for. e.g. `Foo` for `impl<T> Display for Foo<T>`.
+fn impl_target(item: &syn::ItemImpl) -> Option<String> {
+    match item.self_ty.as_ref() {
+        syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Group names by visibility, in order of first appearance.
+fn grouped(exports: &[(String, String)]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for (vis, name) in exports {
+        match groups.iter_mut().find(|(group, _)| group == vis) {
+            Some((_, names)) => names.push(name.clone()),
+            None => groups.push((vis.clone(), vec![name.clone()])),
+        }
+    }
+
+    groups
+}
+
+/// Directory the submodules of a module file are in.
+fn submodule_dir(file: &Path) -> Result<PathBuf> {
+    let is_root = Project::discover(file)?.targets()?.iter().any(|target| target.root == file);
+    let owns_dir = is_root || file.file_name().is_some_and(|name| name == "mod.rs");
+    Ok(match owns_dir {
+        true => file.parent().unwrap_or(Path::new("")).to_path_buf(),
+        false => file.with_extension(""),
+    })
+}
+
+/// Byte offset of a 0-based character column in a line.
+fn byte_offset(line: &str, column: usize) -> usize {
+    line.char_indices().nth(column).map(|(offset, _)| offset).unwrap_or(line.len())
+}
+
+#[cfg(test)]
+#[path = "./extract_test.rs"]
+mod extract_test;
@@ -0,0 +1,100 @@
+use super::*;
+
+/// Create a crate whose `lib.rs` contains the given content.
+fn project(lib: &str) -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), lib).unwrap();
+    (dir, root)
+}
+
+#[test]
+fn extract_should_move_items_and_impls_and_reexport_them() {
+    let lib = "\
+use std::fmt;
+
+/// A thing.
+pub struct Thing {
+    name: String,
+}
+
+// Construction.
+impl Thing {
+    fn new() -> Self {
+        Self { name: String::new() }
+    }
+}
+
+impl fmt::Display for Thing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, \"{}\", self.name)
+    }
+}
+
+pub(super) fn make() -> Thing {
+    Thing::new()
+}
+
+fn other() {}
+";
+
+    let (_dir, root) = project(lib);
+    let lib = root.join("src/lib.rs");
+    let items = [String::from("Thing"), String::from("make")];
+    let touched = extract(&lib, &items, "things", &SuperOptions::default()).unwrap();
+    assert_eq!(touched, vec![root.join("src/things.rs"), lib.clone()]);
+
+    assert_eq!(
+        fs::read_to_string(root.join("src/things.rs")).unwrap(),
+        "\
+use super::*;
+
+/// A thing.
+pub struct Thing {
+    pub(super) name: String,
+}
+
+// Construction.
+impl Thing {
+    pub(super) fn new() -> Self {
+        Self { name: String::new() }
+    }
+}
+
+impl fmt::Display for Thing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, \"{}\", self.name)
+    }
+}
+
+pub(crate) fn make() -> Thing {
+    Thing::new()
+}
+"
+    );
+
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "use std::fmt;\npub mod things;\npub use things::Thing;\npub(super) use things::make;\n\nfn other() {}\n"
+    );
+}
+
+#[test]
+fn extract_should_leave_files_untouched_on_errors() {
+    let lib = "struct A;\n\nfn b() {} fn c() {}\n";
+    let (_dir, root) = project(lib);
+    let lib_path = root.join("src/lib.rs");
+
+    let err = extract(&lib_path, &[String::from("Missing")], "m", &SuperOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::ItemNotFound { item, .. } if item == "Missing"));
+
+    assert!(extract(&lib_path, &[String::from("b")], "m", &SuperOptions::default()).is_err());
+    assert_eq!(fs::read_to_string(&lib_path).unwrap(), lib);
+    assert!(!root.join("src/m.rs").exists());
+
+    fs::write(root.join("src/m.rs"), "").unwrap();
+    let err = extract(&lib_path, &[String::from("A")], "m", &SuperOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::ModuleExists(_)));
+}
@@ -0,0 +1,61 @@
+//! Modules and crates in temporary directories, shared by tests.
+use crate::creator::ModuleCreator;
+use crate::result::Result;
+use crate::{ContentOptions, Created, DiskFileSystem, SuperOptions, TestOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Create a module on disk in a single transaction.
+pub(crate) fn create(
+    path: &Path, 
+    dir: bool, 
+    content: &ContentOptions,
+    test: Option<&TestOptions>, 
+    add_to_super: Option<&SuperOptions>,
+) -> Result<Created> {
+    ModuleCreator::new(&mut DiskFileSystem)
+        .transaction(|creator| creator.create_module(path, dir, content, test, add_to_super))
+}
+
+/// Create a crate with an empty manifest and `lib.rs`, along with other files.
+///
+/// # Arguments
+/// + `files`: Paths relative to the crate root, and contents, of files to write.
+///   They may replace the manifest or `lib.rs`.
+///
+/// # Returns
+/// The crate's directory, and its canonical root.
+pub(crate) fn project(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::write(root.join("Cargo.toml"), "").unwrap();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "").unwrap();
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    (dir, root)
+}
+
+/// Create a crate with modules, each with a test file and declared in its super.
+///
+/// # Arguments
+/// + `modules`: Paths of the modules relative to `src`, and whether each is a directory module.
+///
+/// # Returns
+/// The crate's directory, and its canonical `src`.
+pub(crate) fn project_with_modules(modules: &[(&str, bool)]) -> (TempDir, PathBuf) {
+    let (dir, root) = project(&[]);
+    let src = root.join("src");
+    let test = TestOptions::default();
+    let options = SuperOptions::default();
+    for (path, is_dir) in modules {
+        create(&src.join(path), *is_dir, &ContentOptions::default(), Some(&test), Some(&options)).unwrap();
+    }
+
+    (dir, src)
+}
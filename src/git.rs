@@ -0,0 +1,227 @@
+//! Git queries, and staging of touched files.
+use crate::creator::memory_path;
+use crate::result::Result;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Version control queries made while modifying files, so embedders and tests can answer them.
+pub trait Vcs {
+    /// Check if a file has unstaged modifications. See [`has_unstaged_changes`].
+    fn has_unstaged_changes(&self, path: &Path) -> Result<bool>;
+
+    /// Check if a file has uncommitted modifications, staged or not. See [`has_uncommitted_changes`].
+    fn has_uncommitted_changes(&self, path: &Path) -> Result<bool>;
+
+    /// Get the configured user name. See [`user_name`].
+    fn user_name(&self, dir: &Path) -> Option<String>;
+}
+
+/// Git, run as a subprocess.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Git;
+
+impl Vcs for Git {
+    fn has_unstaged_changes(&self, path: &Path) -> Result<bool> {
+        has_unstaged_changes(path)
+    }
+
+    fn has_uncommitted_changes(&self, path: &Path) -> Result<bool> {
+        has_uncommitted_changes(path)
+    }
+
+    fn user_name(&self, dir: &Path) -> Option<String> {
+        user_name(dir)
+    }
+}
+
+/// Version control state held in memory, e.g. for tests.
+/// Files are unmodified unless marked otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryVcs {
+    unstaged: BTreeSet<PathBuf>,
+    staged: BTreeSet<PathBuf>,
+    user_name: Option<String>,
+}
+
+impl MemoryVcs {
+    /// Create a state where every file is unmodified, and no user name is configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a file as having unstaged modifications.
+    pub fn with_unstaged(mut self, path: impl AsRef<Path>) -> Self {
+        self.unstaged.insert(memory_path(path.as_ref()));
+        self
+    }
+
+    /// Mark a file as having staged modifications.
+    pub fn with_staged(mut self, path: impl AsRef<Path>) -> Self {
+        self.staged.insert(memory_path(path.as_ref()));
+        self
+    }
+
+    /// Set the configured user name.
+    pub fn with_user_name(mut self, name: impl Into<String>) -> Self {
+        self.user_name = Some(name.into());
+        self
+    }
+}
+
+impl Vcs for MemoryVcs {
+    fn has_unstaged_changes(&self, path: &Path) -> Result<bool> {
+        Ok(self.unstaged.contains(&memory_path(path)))
+    }
+
+    fn has_uncommitted_changes(&self, path: &Path) -> Result<bool> {
+        let path = memory_path(path);
+        Ok(self.unstaged.contains(&path) || self.staged.contains(&path))
+    }
+
+    fn user_name(&self, _dir: &Path) -> Option<String> {
+        self.user_name.clone()
+    }
+}
+
+/// Check if a file has unstaged modifications.
+///
+/// # Arguments
+/// + `path`: Path to the file.
+///
+/// # Returns
+/// `false` if the file is unmodified, untracked, or not in a git repository,
+/// or if git is not available.
+pub fn has_unstaged_changes(path: &Path) -> Result<bool> {
+    // porcelain format is `XY path` where `Y` is the worktree status
+    let dirty = status(path).lines().any(|line| {
+        let mut codes = line.chars().skip(1);
+        !matches!(codes.next(), Some(' ') | Some('?') | None)
+    });
+
+    Ok(dirty)
+}
+
+/// Check if a file has uncommitted modifications, staged or not.
+/// Untracked files are uncommitted, as their contents could not be recovered with git.
+///
+/// # Arguments
+/// + `path`: Path to the file.
+///
+/// # Returns
+/// `false` if the file is unmodified, ignored, or not in a git repository,
+/// or if git is not available.
+pub fn has_uncommitted_changes(path: &Path) -> Result<bool> {
+    // porcelain format lists only files with changes, and `??` marks untracked files
+    let dirty = status(path).lines().any(|line| !line.is_empty());
+    Ok(dirty)
+}
+
+/// Get the porcelain status of a file.
+///
+/// # Returns
+/// The status, or empty if the file is unmodified, not in a git repository,
+/// or git is not available.
+fn status(path: &Path) -> String {
+    let (dir, file) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(file)) => (dir, file),
+        _ => return String::new(),
+    };
+
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .arg("--")
+        .arg(file)
+        .current_dir(dir)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Stage files.
+/// Files that do not exist are skipped.
+///
+/// # Arguments
+/// + `dir`: Directory in the repository.
+/// + `paths`: Files to stage, or directories whose files to stage.
+///
+/// # Errors
+/// + If git is not available, or `dir` is not in a repository.
+/// + If a path is outside of the repository, or ignored.
+pub fn stage(dir: &Path, paths: &[&Path]) -> Result {
+    let paths: Vec<&Path> = paths.iter().copied().filter(|path| path.exists()).collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let output = Command::new("git")
+        .args(["add", "--"])
+        .args(&paths)
+        .current_dir(dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(
+            format!("git add failed: {}", String::from_utf8_lossy(&output.stderr).trim()),
+        ).into());
+    }
+
+    Ok(())
+}
+
+/// Get the configured user name.
+///
+/// # Arguments
+/// + `dir`: Directory whose git configuration is read.
+///
+/// # Returns
+/// `None` if no user name is configured, or git is not available.
+pub fn user_name(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "user.name"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+/// Get the files added since a commit.
+///
+/// # Arguments
+/// + `dir`: Directory in the repository. Returned paths are absolute paths within it.
+/// + `reference`: Commit to compare against. e.g. `origin/main`.
+///
+/// # Errors
+/// + If git is not available, `dir` is not in a repository, or `reference` is not a commit.
+pub fn added_since(dir: &Path, reference: &str) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(["diff", "--name-only", "--relative", "--diff-filter=A", reference, "--"])
+        .current_dir(dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("git diff failed: {}", String::from_utf8_lossy(&output.stderr).trim()),
+        ).into());
+    }
+
+    let files = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|file| dir.join(file))
+        .collect();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+#[path = "./git_test.rs"]
+mod git_test;
@@ -0,0 +1,95 @@
+use super::*;
+use std::fs;
+
+/// Run a git command in the given directory.
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=mkmod", "-c", "user.email=mkmod@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+        .status;
+
+    assert!(status.success());
+}
+
+#[test]
+fn has_unstaged_changes_should_detect_modified_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    fs::write(&file, "pub mod a;\n").unwrap();
+    git(dir.path(), &["init", "-q"]);
+    assert!(!has_unstaged_changes(&file).unwrap());
+
+    git(dir.path(), &["add", "lib.rs"]);
+    git(dir.path(), &["commit", "-q", "-m", "init"]);
+    assert!(!has_unstaged_changes(&file).unwrap());
+
+    fs::write(&file, "pub mod a;\npub mod b;\n").unwrap();
+    assert!(has_unstaged_changes(&file).unwrap());
+
+    git(dir.path(), &["add", "lib.rs"]);
+    assert!(!has_unstaged_changes(&file).unwrap());
+}
+
+#[test]
+fn has_unstaged_changes_outside_repository_should_be_false() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    fs::write(&file, "").unwrap();
+    assert!(!has_unstaged_changes(&file).unwrap());
+}
+
+#[test]
+fn added_since_should_list_added_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::write(root.join("lib.rs"), "").unwrap();
+    git(&root, &["init", "-q"]);
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "-q", "-m", "init"]);
+
+    fs::write(root.join("lib.rs"), "pub mod a;\n").unwrap();
+    fs::write(root.join("a.rs"), "").unwrap();
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "-q", "-m", "a"]);
+
+    assert_eq!(added_since(&root, "HEAD~1").unwrap(), vec![root.join("a.rs")]);
+    assert!(added_since(&root, "HEAD").unwrap().is_empty());
+    assert!(added_since(&root, "missing").is_err());
+}
+
+#[test]
+fn staged_files_should_be_uncommitted_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    let created = dir.path().join("a.rs");
+    fs::write(&file, "").unwrap();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["add", "lib.rs"]);
+    git(dir.path(), &["commit", "-q", "-m", "init"]);
+
+    fs::write(&file, "pub mod a;\n").unwrap();
+    fs::write(&created, "").unwrap();
+    stage(dir.path(), &[&file, &created, &dir.path().join("missing.rs")]).unwrap();
+    assert!(!has_unstaged_changes(&file).unwrap());
+    assert!(has_uncommitted_changes(&file).unwrap());
+    assert!(has_uncommitted_changes(&created).unwrap());
+
+    git(dir.path(), &["commit", "-q", "-m", "add a"]);
+    assert!(!has_uncommitted_changes(&file).unwrap());
+}
+
+#[test]
+fn untracked_files_should_be_uncommitted_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    fs::write(&file, "").unwrap();
+    git(dir.path(), &["init", "-q"]);
+    assert!(!has_unstaged_changes(&file).unwrap());
+    assert!(has_uncommitted_changes(&file).unwrap());
+
+    fs::write(dir.path().join(".gitignore"), "lib.rs\n").unwrap();
+    assert!(!has_uncommitted_changes(&file).unwrap());
+}
@@ -0,0 +1,77 @@
+//! User defined commands run around operations.
+use crate::result::{Error, Result};
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Environment variable containing the created or modified paths,
+/// separated by the platform's path list separator.
+pub const PATHS_VAR: &str = "MKMOD_PATHS";
+
+/// Run hook commands.
+///
+/// Each command is run by the system shell from the crate root.
+/// The paths are passed as positional arguments, i.e. `$1`, `$2`, ... or `"$@"` on Unix,
+/// and in the [`PATHS_VAR`] environment variable.
+///
+/// # Arguments
+/// + `root`: Crate root.
+/// + `commands`: Commands to run, in order.
+/// + `paths`: Paths passed to the commands.
+/// + `input`: Data written to each command's standard input.
+///
+/// # Errors
+/// + If a command can not be run or exits unsuccessfully.
+///   Remaining commands are not run.
+pub fn run(root: &Path, commands: &[String], paths: &[&Path], input: Option<&str>) -> Result {
+    let paths_var = env::join_paths(paths).unwrap_or_default();
+    for command in commands {
+        let mut child = shell(command)
+            .args(paths)
+            .env(PATHS_VAR, &paths_var)
+            .current_dir(root)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::inherit() })
+            .spawn()?;
+
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            // commands are not required to read their input
+            if let Err(err) = stdin.write_all(input.as_bytes()) {
+                if err.kind() != io::ErrorKind::BrokenPipe {
+                    return Err(err.into());
+                }
+            }
+        }
+
+        let status = child.wait()?;
+
+        if !status.success() {
+            return Err(Error::Hook {
+                command: command.clone(),
+                code: status.code(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a command running the given command line in the system shell.
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command).arg("mkmod");
+    shell
+}
+
+/// Create a command running the given command line in the system shell.
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(test)]
+#[path = "./hooks_test.rs"]
+mod hooks_test;
@@ -0,0 +1,37 @@
+use super::*;
+use std::fs;
+
+#[test]
+#[cfg(unix)]
+fn hooks_should_receive_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let commands = vec![
+        String::from(r#"echo "$@" > args.txt"#),
+        String::from(r#"echo "$MKMOD_PATHS" > env.txt"#),
+    ];
+
+    run(dir.path(), &commands, &[Path::new("src/a.rs"), Path::new("src/lib.rs")], None).unwrap();
+    assert_eq!(fs::read_to_string(dir.path().join("args.txt")).unwrap(), "src/a.rs src/lib.rs\n");
+    assert_eq!(fs::read_to_string(dir.path().join("env.txt")).unwrap(), "src/a.rs:src/lib.rs\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn failing_hook_should_stop_remaining_hooks() {
+    let dir = tempfile::tempdir().unwrap();
+    let commands = vec![String::from("exit 3"), String::from("touch ran.txt")];
+
+    let res = run(dir.path(), &commands, &[], None);
+    assert!(matches!(res, Err(Error::Hook { code: Some(3), .. })));
+    assert!(!dir.path().join("ran.txt").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn hooks_should_receive_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let commands = vec![String::from("cat > input.txt"), String::from("true")];
+
+    run(dir.path(), &commands, &[], Some("{\"name\":\"a\"}")).unwrap();
+    assert_eq!(fs::read_to_string(dir.path().join("input.txt")).unwrap(), "{\"name\":\"a\"}");
+}
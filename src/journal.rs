@@ -0,0 +1,215 @@
+//! Append-only journal of operations performed on a project.
+use crate::result::Result;
+use crate::project;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory, relative to the crate root, holding mkmod's project data.
+pub const DATA_DIR: &str = ".mkmod";
+
+/// Name of the journal file in the data directory.
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// A single journaled operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Time of the operation, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// Command line arguments of the operation.
+    pub command: Vec<String>,
+
+    /// Files touched by the operation, relative to the crate root where possible.
+    pub files: Vec<PathBuf>,
+
+    /// Modules the operation generated from templates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated: Vec<Generated>,
+}
+
+/// A module generated from a template, recorded so it can be upgraded when the template changes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Generated {
+    /// Module file, relative to the crate root where possible.
+    pub file: PathBuf,
+
+    /// Name of the named template the module was generated from, or `None` for the module template.
+    pub template: Option<String>,
+
+    /// Content the module was generated with, the base edits to it are merged from on upgrades.
+    pub content: String,
+}
+
+impl Generated {
+    /// Record a module generated from a template.
+    ///
+    /// # Arguments
+    /// + `root`: Crate root.
+    /// + `file`: Module file.
+    /// + `template`: Name of the named template, or `None` for the module template.
+    /// + `content`: Content the module was generated with.
+    pub fn new(root: &Path, file: &Path, template: Option<String>, content: String) -> Result<Self> {
+        Ok(Self {
+            file: relative(root, file)?,
+            template,
+            content,
+        })
+    }
+}
+
+impl Entry {
+    /// Create an entry for an operation performed now.
+    ///
+    /// # Arguments
+    /// + `root`: Crate root.
+    /// + `command`: Command line arguments of the operation.
+    /// + `files`: Files touched by the operation.
+    pub fn new(root: &Path, command: Vec<String>, files: &[&Path]) -> Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        let files = files
+            .iter()
+            .map(|file| relative(root, file))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            timestamp,
+            command,
+            files,
+            generated: Vec::new(),
+        })
+    }
+
+    /// Set the modules the operation generated from templates.
+    pub fn generated(mut self, generated: Vec<Generated>) -> Self {
+        self.generated = generated;
+        self
+    }
+}
+
+/// Get the path of a file relative to the crate root, or its absolute path if it is outside of it.
+fn relative(root: &Path, file: &Path) -> Result<PathBuf> {
+    let file = project::absolute_path(file)?;
+    Ok(match file.strip_prefix(root) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => file,
+    })
+}
+
+/// Path to the journal of a project.
+///
+/// # Arguments
+/// + `root`: Crate root.
+pub fn path(root: &Path) -> PathBuf {
+    root.join(DATA_DIR).join(JOURNAL_FILE)
+}
+
+/// Append an entry to a project's journal.
+///
+/// # Arguments
+/// + `root`: Crate root.
+/// + `entry`: Entry to record.
+pub fn record(root: &Path, entry: &Entry) -> Result {
+    fs::create_dir_all(root.join(DATA_DIR))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path(root))?;
+
+    let line = serde_json::to_string(entry)?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+/// Read all entries of a project's journal, oldest first.
+///
+/// # Arguments
+/// + `root`: Crate root.
+///
+/// # Returns
+/// The journal's entries, or an empty list if the project has no journal.
+pub fn read(root: &Path) -> Result<Vec<Entry>> {
+    let file = match fs::File::open(path(root)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut entries = Vec::new();
+    for line in io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        entries.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(entries)
+}
+
+/// Get the latest generated version of each module a project's journal records as generated from a template.
+///
+/// # Arguments
+/// + `root`: Crate root.
+///
+/// # Returns
+/// The modules, ordered by file.
+pub fn generated_modules(root: &Path) -> Result<Vec<Generated>> {
+    let mut modules = BTreeMap::new();
+    for entry in read(root)? {
+        for generated in entry.generated {
+            modules.insert(generated.file.clone(), generated);
+        }
+    }
+
+    Ok(modules.into_values().collect())
+}
+
+/// Format a timestamp as a UTC date and time.
+///
+/// # Arguments
+/// + `timestamp`: Seconds since the Unix epoch.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let secs = timestamp % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02} UTC",
+        format_date(timestamp),
+        secs / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60
+    )
+}
+
+/// Format a timestamp as a UTC date. e.g. `2024-01-31`.
+///
+/// # Arguments
+/// + `timestamp`: Seconds since the Unix epoch.
+pub fn format_date(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+
+    // convert days since epoch to a civil date
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+#[path = "./journal_test.rs"]
+mod journal_test;
@@ -0,0 +1,55 @@
+use super::*;
+
+#[test]
+fn recorded_entries_should_be_read_back_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    assert!(read(&root).unwrap().is_empty());
+
+    let file = root.join("src/a.rs");
+    let first = Entry::new(&root, vec![String::from("mkmod"), String::from("a")], &[&file]).unwrap();
+    let second = Entry::new(&root, vec![String::from("mkmod"), String::from("b")], &[]).unwrap();
+    record(&root, &first).unwrap();
+    record(&root, &second).unwrap();
+
+    let entries = read(&root).unwrap();
+    assert_eq!(entries, vec![first, second]);
+    assert_eq!(entries[0].files, vec![PathBuf::from("src/a.rs")]);
+}
+
+#[test]
+fn format_timestamp_should_produce_utc_date_time() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+    assert_eq!(format_timestamp(1_792_065_601), "2026-10-15 12:00:01 UTC");
+}
+
+#[test]
+fn generated_modules_should_be_latest_version_of_each_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let (a, b) = (root.join("src/a.rs"), root.join("src/b.rs"));
+    let generated = |file: &Path, template: Option<&str>, content: &str| {
+        Generated::new(&root, file, template.map(String::from), String::from(content)).unwrap()
+    };
+
+    let first = Entry::new(&root, Vec::new(), &[&a, &b])
+        .unwrap()
+        .generated(vec![generated(&b, Some("handler"), "// b"), generated(&a, None, "// a")]);
+
+    let second = Entry::new(&root, Vec::new(), &[&a]).unwrap().generated(vec![generated(&a, None, "// a upgraded")]);
+    record(&root, &first).unwrap();
+    record(&root, &Entry::new(&root, Vec::new(), &[]).unwrap()).unwrap();
+    record(&root, &second).unwrap();
+
+    let modules = generated_modules(&root).unwrap();
+    assert_eq!(modules, vec![generated(&a, None, "// a upgraded"), generated(&b, Some("handler"), "// b")]);
+    assert_eq!(modules[0].file, PathBuf::from("src/a.rs"));
+}
+
+#[test]
+fn entries_without_generated_modules_should_be_read() {
+    let entry: Entry = serde_json::from_str(r#"{"timestamp":0,"command":["mkmod"],"files":[]}"#).unwrap();
+    assert!(entry.generated.is_empty());
+    assert!(!serde_json::to_string(&entry).unwrap().contains("generated"));
+}
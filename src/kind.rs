@@ -0,0 +1,227 @@
+//! Starter skeletons for common shapes of modules.
+use crate::rust_version::RustVersion;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Shape of a module's starter skeleton.
+/// The skeleton's type is named after the module, e.g. `PaymentMethod` for `payment_method`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleKind {
+    /// A struct with an `impl` block containing `new()`.
+    Struct,
+
+    /// A trait with a default method.
+    Trait,
+
+    /// An enum implementing `Display`.
+    Enum,
+
+    /// A service struct, e.g. `PaymentService` for `payment` or `payment_service`,
+    /// created from its configuration.
+    Service,
+}
+
+impl ModuleKind {
+    /// All kinds of modules.
+    pub const ALL: [Self; 4] = [Self::Struct, Self::Trait, Self::Enum, Self::Service];
+
+    /// Render the skeleton of the module.
+    ///
+    /// # Arguments
+    /// + `mod_name`: Name of the module.
+    pub fn module(&self, mod_name: &str) -> String {
+        self.module_for(mod_name, None)
+    }
+
+    /// Render the skeleton of the module for a minimum supported Rust version,
+    /// naming types instead of using `Self` where the version does not support it.
+    ///
+    /// # Arguments
+    /// + `mod_name`: Name of the module.
+    /// + `rust_version`: Minimum supported Rust version, or `None` for the latest.
+    pub fn module_for(&self, mod_name: &str, rust_version: Option<RustVersion>) -> String {
+        let name = self.base_name(mod_name);
+        let this = |version: RustVersion, name: &str| match rust_version.is_none_or(|msrv| msrv.supports(version)) {
+            true => String::from("Self"),
+            false => name.to_string(),
+        };
+
+        let new = this(RustVersion::SELF_STRUCT, &name);
+        let variant = this(RustVersion::SELF_VARIANT, &name);
+        let service = this(RustVersion::SELF_STRUCT, &format!("{name}Service"));
+        match self {
+            Self::Struct => format!(r#"/// A {name}.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct {name} {{}}
+
+impl {name} {{
+    /// Create a new `{name}`.
+    pub fn new() -> Self {{
+        {new} {{}}
+    }}
+}}
+"#),
+
+            Self::Trait => format!(r#"/// Behavior of a {name}.
+pub trait {name} {{
+    /// Name of the implementor.
+    fn name(&self) -> String {{
+        String::from("{name}")
+    }}
+}}
+"#),
+
+            Self::Enum => format!(r#"use std::fmt;
+
+/// Variants of a {name}.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum {name} {{
+    /// The first variant.
+    First,
+}}
+
+impl fmt::Display for {name} {{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {{
+        match self {{
+            {variant}::First => write!(f, "first"),
+        }}
+    }}
+}}
+"#),
+
+            Self::Service => format!(r#"/// Configuration of a [`{name}Service`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct {name}Config {{}}
+
+/// The {name} service.
+#[derive(Debug)]
+pub struct {name}Service {{
+    config: {name}Config,
+}}
+
+impl {name}Service {{
+    /// Create a new service.
+    pub fn new(config: {name}Config) -> Self {{
+        {service} {{ config }}
+    }}
+
+    /// Configuration of the service.
+    pub fn config(&self) -> &{name}Config {{
+        &self.config
+    }}
+
+    /// Run the service.
+    pub fn run(&self) -> Result<(), Box<dyn std::error::Error>> {{
+        Ok(())
+    }}
+}}
+"#),
+        }
+    }
+
+    /// Render the tests of the module's skeleton, placed after the test file's imports.
+    ///
+    /// # Arguments
+    /// + `mod_name`: Name of the module.
+    pub fn test(&self, mod_name: &str) -> String {
+        let name = self.base_name(mod_name);
+        match self {
+            Self::Struct => format!(r#"
+#[test]
+fn new_should_create_default() {{
+    assert_eq!({name}::new(), {name}::default());
+}}
+"#),
+
+            Self::Trait => format!(r#"
+struct Implementor;
+
+impl {name} for Implementor {{}}
+
+#[test]
+fn name_should_default_to_trait_name() {{
+    assert_eq!(Implementor.name(), "{name}");
+}}
+"#),
+
+            Self::Enum => format!(r#"
+#[test]
+fn variants_should_display_in_lowercase() {{
+    assert_eq!({name}::First.to_string(), "first");
+}}
+"#),
+
+            Self::Service => format!(r#"
+#[test]
+fn service_should_run() {{
+    let service = {name}Service::new({name}Config::default());
+    assert_eq!(service.config(), &{name}Config::default());
+    service.run().unwrap();
+}}
+"#),
+        }
+    }
+
+    /// Get the name the skeleton's types are based on.
+    fn base_name(&self, mod_name: &str) -> String {
+        let name = type_name(mod_name);
+        match self {
+            Self::Service => match name.strip_suffix("Service") {
+                Some(base) if !base.is_empty() => base.to_string(),
+                _ => name,
+            },
+
+            _ => name,
+        }
+    }
+}
+
+impl fmt::Display for ModuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Struct => "struct",
+            Self::Trait => "trait",
+            Self::Enum => "enum",
+            Self::Service => "service",
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
+impl FromStr for ModuleKind {
+    type Err = io::Error;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == kind)
+            .ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput, format!("unknown module kind `{kind}`")
+            ))
+    }
+}
+
+/// Get the name of a module's type, converting the module's name to upper camel case.
+/// e.g. `PaymentMethod` for `payment_method`.
+pub fn type_name(mod_name: &str) -> String {
+    let mod_name = mod_name.strip_prefix("r#").unwrap_or(mod_name);
+    mod_name
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "./kind_test.rs"]
+mod kind_test;
@@ -0,0 +1,48 @@
+use super::*;
+
+#[test]
+fn type_name_should_be_upper_camel_case() {
+    assert_eq!(type_name("payment_method"), "PaymentMethod");
+    assert_eq!(type_name("io"), "Io");
+    assert_eq!(type_name("r#type"), "Type");
+}
+
+#[test]
+fn kind_should_parse_from_display() {
+    for kind in ModuleKind::ALL {
+        assert_eq!(kind.to_string().parse::<ModuleKind>().unwrap(), kind);
+    }
+
+    assert!("class".parse::<ModuleKind>().is_err());
+}
+
+#[test]
+fn skeletons_should_parse() {
+    for kind in ModuleKind::ALL {
+        syn::parse_file(&kind.module("my_mod")).unwrap();
+        syn::parse_file(&kind.test("my_mod")).unwrap();
+    }
+}
+
+#[test]
+fn service_should_not_repeat_suffix() {
+    let skeleton = ModuleKind::Service.module("payment_service");
+    assert!(skeleton.contains("pub struct PaymentService {"));
+    assert!(skeleton.contains("pub struct PaymentConfig {}"));
+}
+
+#[test]
+fn skeletons_should_name_types_for_old_rust_versions() {
+    let enum_skeleton = ModuleKind::Enum.module_for("color", Some(RustVersion::new(1, 36, 0)));
+    assert!(enum_skeleton.contains("Color::First =>"));
+    assert!(ModuleKind::Enum.module_for("color", Some(RustVersion::new(1, 37, 0))).contains("Self::First =>"));
+
+    let struct_skeleton = ModuleKind::Struct.module_for("point", Some(RustVersion::new(1, 31, 0)));
+    assert!(struct_skeleton.contains("        Point {}\n"));
+
+    let service = ModuleKind::Service.module_for("payment", Some(RustVersion::new(1, 31, 0)));
+    assert!(service.contains("PaymentService { config }"));
+    for kind in ModuleKind::ALL {
+        syn::parse_file(&kind.module_for("my_mod", Some(RustVersion::new(1, 0, 0)))).unwrap();
+    }
+}
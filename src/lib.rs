@@ -1,15 +1,752 @@
-#![feature(io_error_more)]
-#![feature(file_create_new)]
-
 //! Functionality for creating new modules.
 pub mod result;
+pub mod project;
+pub mod config;
+pub mod journal;
+pub mod hooks;
+pub mod manifest;
+pub mod diff;
+pub mod prune;
+pub mod remove;
+pub mod rename;
+pub mod convert;
+pub mod kind;
+pub mod plan_file;
+pub mod snapshot;
+pub mod orphans;
+pub mod standalone;
+pub mod visibility;
+pub mod batch;
+pub mod tree;
+pub mod name;
+pub mod rustfmt;
+pub mod prompt;
+pub mod report;
+pub mod metrics;
+pub mod extract;
+pub mod test_framework;
+pub mod git;
+pub mod creator;
+pub mod rust_version;
+#[cfg(test)]
+mod fixtures;
 use std::path::{PathBuf, Path};
-use crate::result::Result;
+use crate::result::{Error, Result};
+use crate::creator::{DiskFileSystem, FileSystem, ModuleCreator};
+use crate::project::{Project, Target, TargetKind};
+use crate::config::{DeclarationFormat, InsertPosition, Templates};
+use crate::kind::ModuleKind;
+use crate::metrics::Metrics;
+use crate::rust_version::RustVersion;
+use crate::test_framework::TestFramework;
+use crate::visibility::Visibility;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
-use std::io::{self, Write, BufRead};
-use std::fs::{self, File};
+use std::io::{self, Write};
+use std::fs;
 use std::ffi::OsStr;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+/// Options for generated test scaffolding.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestOptions {
+    /// Configuration predicate guarding the test module.
+    /// e.g. `test` or `all(test, feature = "expensive-tests")`.
+    pub cfg: String,
+
+    /// Additional imports added to the test file after `use super::*;`.
+    /// e.g. `pretty_assertions::assert_eq`.
+    pub imports: Vec<String>,
+
+    /// Where the test file is placed.
+    pub location: TestLocation,
+
+    /// Source root, relative to the crate root, or `None` for `src`.
+    /// Mirrored test paths are derived from the module's path within it.
+    pub src_root: Option<PathBuf>,
+
+    /// Framework the generated tests are written for.
+    #[serde(default)]
+    pub framework: TestFramework,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            cfg: String::from("test"),
+            imports: Vec::new(),
+            location: TestLocation::Sibling,
+            src_root: None,
+            framework: TestFramework::Plain,
+        }
+    }
+}
+
+/// Location of a module's tests.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TestLocation {
+    /// In a test file next to the module file.
+    Sibling,
+
+    /// In a test file in a tree mirroring the source directory.
+    /// Contains the root of the tree, relative to the crate root. e.g. `src/tests`.
+    Mirrored(PathBuf),
+
+    /// In a `mod tests` block at the bottom of the module, without a test file.
+    Inline,
+}
+
+/// Options for the contents of a generated module.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentOptions {
+    /// Module documentation, written as `//!` comments at the top of the module.
+    pub doc: Option<String>,
+
+    /// Inner attributes added to the top of the module, after its documentation.
+    /// e.g. `#![deny(missing_docs)]`.
+    pub attributes: Vec<String>,
+
+    /// Templates of the module's body and test file.
+    #[serde(default)]
+    pub templates: Templates,
+
+    /// Starter skeleton of the module, used instead of the module's body template,
+    /// and its tests, added to the test file.
+    #[serde(default)]
+    pub kind: Option<ModuleKind>,
+}
+
+/// Options for adding a module to its super module.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SuperOptions {
+    /// Add module to main instead of lib. Only applicable if module is being created in the
+    /// crate root.
+    pub main: bool,
+
+    /// Visibility of the declaration, or `None` to follow the super's glob re-export convention
+    /// if it has one, and declare it `pub` otherwise.
+    pub vis: Option<Visibility>,
+
+    /// Modify the super file even if it has unstaged changes.
+    pub dirty_ok: bool,
+
+    /// Refuse to modify the super file if it has any uncommitted changes, staged or not.
+    /// Takes precedence over `dirty_ok`.
+    #[serde(default)]
+    pub require_clean: bool,
+
+    /// 1-based line number at which to insert the declaration,
+    /// or `None` to determine it from the super file's contents.
+    pub at: Option<usize>,
+
+    /// Comment to add after the declaration. e.g. `owned by infra team`.
+    pub comment: Option<String>,
+
+    /// Attributes to add to the declaration. e.g. `#[cfg(unix)]`.
+    pub attributes: Vec<String>,
+
+    /// Format of the declaration.
+    pub format: DeclarationFormat,
+
+    /// Source root, relative to the crate root, or `None` for `src`.
+    /// Modules directly inside it are added to the crate's root module.
+    pub src_root: Option<PathBuf>,
+
+    /// Name of the target whose root module the module is added to,
+    /// when the module is in a directory containing the roots of several targets.
+    /// Takes precedence over `main`.
+    #[serde(default)]
+    pub target: Option<String>,
+
+    /// Re-export the module's contents from the super, after its declaration.
+    /// If `None`, modules are glob re-exported if the super re-exports all of its modules.
+    #[serde(default)]
+    pub reexport: Option<Reexport>,
+
+    /// Feature gating the module. Its declaration is preceded by `#[cfg(feature = "<feature>")]`.
+    #[serde(default)]
+    pub feature: Option<String>,
+
+    /// File to declare the module in, overriding the resolution of its super.
+    /// Does not apply to missing parents created for the module.
+    #[serde(default)]
+    pub super_file: Option<PathBuf>,
+}
+
+/// Re-export of a new module's contents from its super.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reexport {
+    /// `pub use module::*;`
+    Glob,
+
+    /// `pub use module::<item>;` e.g. `Client`, or `{Client, Config}`.
+    Item(String),
+}
+
+impl Reexport {
+    /// Render the re-export statement.
+    ///
+    /// # Arguments
+    /// + `mod_name`: Name of the module.
+    pub fn render(&self, mod_name: &str) -> String {
+        match self {
+            Self::Glob => format!("pub use {mod_name}::*;"),
+            Self::Item(item) => format!("pub use {mod_name}::{};", item.trim()),
+        }
+    }
+}
+
+/// What to do when a module being created already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Exists {
+    /// Fail with [`Error::ModuleExists`].
+    #[default]
+    Error,
+
+    /// Leave the module as it is, but declare it in its super if it is not,
+    /// and declare its test file in it if the test file exists but is not declared.
+    Ok,
+
+    /// As [`Exists::Ok`], and also create the module's test file if it is missing.
+    Update,
+}
+
+impl SuperOptions {
+    /// Visibility of the declaration, `pub` if it is not set.
+    pub fn visibility(&self) -> Visibility {
+        self.vis.clone().unwrap_or(Visibility::Pub)
+    }
+}
+
+/// Position in a file, for editors to jump to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Path to the file.
+    pub path: PathBuf,
+
+    /// 1-based line.
+    pub line: usize,
+
+    /// 1-based column.
+    pub column: usize,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// Files touched while creating a module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Created {
+    /// Path to the module.
+    /// The file for a file module, or the directory for a directory module.
+    pub module_path: PathBuf,
+
+    /// Path to the module's test file, if one was created.
+    pub test_path: Option<PathBuf>,
+
+    /// Path to the super file, if the module was added to it.
+    pub super_modified: Option<PathBuf>,
+
+    /// Location of the module's declaration in the super file, if it was added to it.
+    pub declaration_location: Option<Location>,
+
+    /// 1-based lines inserted in the super file for the declaration, its attributes, and its re-export.
+    /// Empty if the module was not added to it.
+    pub declaration_lines: Vec<usize>,
+
+    /// Location in the test file where tests should be written, if one was created.
+    pub test_location: Option<Location>,
+
+    /// Whether the module already existed, and was completed instead of created.
+    pub existed: bool,
+
+    /// Path to the module's file, if the module already existed and its file was modified.
+    pub module_modified: Option<PathBuf>,
+
+    /// Missing ancestors created for the module, outermost first.
+    pub parents: Vec<Created>,
+
+    /// Time spent rendering the module's files and declaring it, without its parents.
+    pub metrics: Metrics,
+}
+
+impl Created {
+    /// Paths of all files created or modified.
+    pub fn paths(&self) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = self.parents.iter().flat_map(Created::paths).collect();
+        if !self.existed {
+            paths.push(self.module_path.as_path());
+        }
+
+        paths.extend(self.module_modified.as_deref());
+        paths.extend(self.test_path.as_deref());
+        paths.extend(self.super_modified.as_deref());
+        paths
+    }
+
+    /// Paths of the files created, without the existing files that were modified.
+    pub fn created_paths(&self) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = self.parents.iter().flat_map(Created::created_paths).collect();
+        if !self.existed {
+            paths.push(self.module_path.as_path());
+        }
+
+        paths.extend(self.test_path.as_deref());
+        paths
+    }
+
+    /// Recompute the locations from the files' current contents, e.g. after they were formatted.
+    /// The declaration is found by parsing the super file, so it is found wherever it was moved to.
+    /// If it was moved, the inserted lines are those of the declaration and its re-export in the super file.
+    pub fn relocate(&mut self) -> Result {
+        for parent in &mut self.parents {
+            parent.relocate()?;
+        }
+
+        if let Some(location) = &mut self.declaration_location {
+            let name = module_name(&self.module_path)?.to_string_lossy().into_owned();
+            let content = fs::read_to_string(&location.path)?;
+            if let Some((line, column)) = mod_position(&content, &name, location.line) {
+                if line != location.line {
+                    self.declaration_lines = declaration_lines(&content, &name, line);
+                }
+
+                location.line = line;
+                location.column = column;
+            }
+        }
+
+        if let Some(location) = &mut self.test_location {
+            let content = fs::read_to_string(&location.path)?;
+            location.line = match self.test_path {
+                // after the last line of the test file
+                Some(_) => content.lines().count() + 1,
+                // before the closing brace of the inline test module
+                None => content.lines().count(),
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the `mod` keyword of a module's declaration.
+///
+/// # Arguments
+/// + `content`: Content of the file declaring the module.
+/// + `mod_name`: Name of the module.
+/// + `near`: 1-based line the declaration is expected near, to choose between alternative declarations.
+///
+/// # Returns
+/// The 1-based line and column, or `None` if the file does not parse or does not declare the module.
+fn mod_position(content: &str, mod_name: &str, near: usize) -> Option<(usize, usize)> {
+    let file = syn::parse_file(content).ok()?;
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Mod(item) if item.ident.to_string().trim_start_matches("r#") == mod_name => {
+                let start = item.mod_token.span.start();
+                let offset = byte_offset(content, start);
+                let line_start = content[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+                Some((start.line, offset - line_start + 1))
+            },
+
+            _ => None,
+        })
+        .min_by_key(|(line, _)| line.abs_diff(near))
+}
+
+/// Finds the lines of a module's declaration, with its attributes, and of its re-export.
+///
+/// # Arguments
+/// + `content`: Content of the file declaring the module.
+/// + `mod_name`: Name of the module.
+/// + `line`: 1-based line of the declaration's `mod` keyword.
+///
+/// # Returns
+/// The 1-based lines, or none if the file does not parse.
+fn declaration_lines(content: &str, mod_name: &str, line: usize) -> Vec<usize> {
+    let Ok(file) = syn::parse_file(content) else {
+        return Vec::new();
+    };
+
+    let named = |ident: &syn::Ident| ident.to_string().trim_start_matches("r#") == mod_name;
+    let mut lines: Vec<usize> = file.items
+        .iter()
+        .find_map(|item| match item {
+            syn::Item::Mod(item) if named(&item.ident) && item.mod_token.span.start().line == line => {
+                Some((item.span().start().line..=item.span().end().line).collect())
+            },
+
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let reexport = file.items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Use(item) => match &item.tree {
+                syn::UseTree::Path(path) if named(&path.ident) => Some(item.span()),
+                _ => None,
+            },
+
+            _ => None,
+        })
+        .min_by_key(|span| span.start().line.abs_diff(line));
+
+    if let Some(span) = reexport {
+        lines.extend(span.start().line..=span.end().line);
+    }
+
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+/// Planned creation of a module.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    /// Name of the module.
+    pub name: String,
+
+    /// Absolute path to the module.
+    /// The file for a file module, or the directory for a directory module.
+    pub module_path: PathBuf,
+
+    /// If the module is a directory module.
+    pub dir: bool,
+
+    /// Absolute path to the module's test file, if one will be created.
+    pub test_path: Option<PathBuf>,
+
+    /// Absolute path to the super file, if the module will be added to it.
+    pub super_file: Option<PathBuf>,
+
+    /// Declaration that will be added to the super file, if it does not already declare the module.
+    pub declaration: Option<String>,
+
+    /// Location the declaration will have in the super file.
+    pub declaration_location: Option<Location>,
+
+    /// Absolute paths of the files and directories that will be created or modified
+    /// for the module's missing ancestors.
+    #[serde(default)]
+    pub parents: Vec<PathBuf>,
+}
+
+impl Plan {
+    /// Paths of all files that will be created or modified.
+    pub fn paths(&self) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = self.parents.iter().map(PathBuf::as_path).collect();
+        paths.push(self.module_path.as_path());
+        paths.extend(self.test_path.as_deref());
+        paths.extend(self.super_file.as_deref());
+        paths
+    }
+}
+
+/// Changes creating a module would make.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preview {
+    /// Absolute paths of the files that would be created, and their contents.
+    pub created: Vec<(PathBuf, String)>,
+
+    /// Absolute paths of the files that would be modified, and their original and new contents.
+    pub modified: Vec<(PathBuf, String, String)>,
+}
+
+impl Preview {
+    /// Render the changes as a unified diff.
+    /// Created files are shown as diffs from `/dev/null`.
+    ///
+    /// # Arguments
+    /// + `base`: Directory paths are shown relative to, if they are inside of it.
+    pub fn diff(&self, base: &Path) -> String {
+        let name = |path: &Path| path.strip_prefix(base).unwrap_or(path).display().to_string();
+        let mut diff = String::new();
+        for (path, content) in &self.created {
+            let new_name = format!("b/{}", name(path));
+            match diff::unified("", content, "/dev/null", &new_name) {
+                // empty files have no lines to show
+                created if created.is_empty() => diff.push_str(&format!("--- /dev/null\n+++ {new_name}\n")),
+                created => diff.push_str(&created),
+            }
+        }
+
+        for (path, original, new) in &self.modified {
+            let name = name(path);
+            diff.push_str(&diff::unified(original, new, &format!("a/{name}"), &format!("b/{name}")));
+        }
+
+        diff
+    }
+}
+
+/// Options for creating a module.
+///
+/// By default a file module is created with a test file, and added to its super as public.
+///
+/// ```no_run
+/// # use mkmod::CreateOptions;
+/// let created = CreateOptions::new("src/my_mod").dir(true).public(false).create()?;
+/// # Ok::<(), mkmod::result::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateOptions {
+    path: PathBuf,
+    dir: bool,
+    content: ContentOptions,
+    with_test: bool,
+    test: TestOptions,
+    add_to_super: bool,
+    super_options: SuperOptions,
+    sanitize: bool,
+    exists: Exists,
+    parents: Option<ContentOptions>,
+}
+
+impl CreateOptions {
+    /// Options for creating a module at a path.
+    ///
+    /// # Arguments
+    /// + `path`: Path of the module, without extension. e.g. `src/my_mod`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            dir: false,
+            content: ContentOptions::default(),
+            with_test: true,
+            test: TestOptions::default(),
+            add_to_super: true,
+            super_options: SuperOptions::default(),
+            sanitize: false,
+            exists: Exists::Error,
+            parents: None,
+        }
+    }
+
+    /// Create a directory module instead of a file module.
+    pub fn dir(mut self, dir: bool) -> Self {
+        self.dir = dir;
+        self
+    }
+
+    /// Set the options for the module's contents.
+    pub fn content(mut self, content: ContentOptions) -> Self {
+        self.content = content;
+        self
+    }
+
+    /// Create a test file for the module.
+    pub fn with_test(mut self, with_test: bool) -> Self {
+        self.with_test = with_test;
+        self
+    }
+
+    /// Set the options for the test file.
+    pub fn test(mut self, test: TestOptions) -> Self {
+        self.test = test;
+        self
+    }
+
+    /// Add the module to its super.
+    pub fn add_to_super(mut self, add_to_super: bool) -> Self {
+        self.add_to_super = add_to_super;
+        self
+    }
+
+    /// Set the options for adding the module to its super.
+    pub fn super_options(mut self, options: SuperOptions) -> Self {
+        self.super_options = options;
+        self
+    }
+
+    /// Add the module to `main.rs` instead of `lib.rs`, if it is in the crate root.
+    pub fn super_main(mut self, main: bool) -> Self {
+        self.super_options.main = main;
+        self
+    }
+
+    /// Set the visibility of the module's declaration.
+    pub fn vis(mut self, vis: Visibility) -> Self {
+        self.super_options.vis = Some(vis);
+        self
+    }
+
+    /// Declare the module as `pub`, or private.
+    pub fn public(self, public: bool) -> Self {
+        self.vis(Visibility::from(public))
+    }
+
+    /// Gate the module behind a feature.
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.super_options.feature = Some(feature.into());
+        self
+    }
+
+    /// Convert the module's name into a valid module name, instead of rejecting it.
+    /// See [`name::sanitize`].
+    pub fn sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Set what to do if the module already exists.
+    pub fn exists(mut self, exists: Exists) -> Self {
+        self.exists = exists;
+        self
+    }
+
+    /// Create the module's missing ancestors as directory modules, with the given contents,
+    /// instead of failing because its super does not exist. See [`create_parents`].
+    pub fn parents(mut self, content: ContentOptions) -> Self {
+        self.parents = Some(content);
+        self
+    }
+
+    /// Plan the creation of the module, without modifying anything.
+    /// Fails if the module already exists, regardless of [`Self::exists`].
+    ///
+    /// # Errors
+    /// See [`Self::create`].
+    pub fn plan(&self) -> Result<Plan> {
+        ModuleCreator::new(&mut DiskFileSystem).plan(self)
+    }
+
+    /// Preview the changes creating the module would make, without modifying anything.
+    ///
+    /// # Errors
+    /// See [`Self::create`].
+    pub fn preview(&self) -> Result<Preview> {
+        ModuleCreator::new(&mut DiskFileSystem).preview(self)
+    }
+
+    /// Create the module.
+    /// If it already exists, it is completed instead, as set by [`Self::exists`].
+    ///
+    /// # Returns
+    /// Files created or modified.
+    ///
+    /// # Errors
+    /// + If the module's name is not a valid module name, unless sanitized.
+    /// + If a module of the given name already exists, unless allowed.
+    /// + If the super file has unstaged changes, unless allowed.
+    pub fn create(&self) -> Result<Created> {
+        ModuleCreator::new(&mut DiskFileSystem).create(self)
+    }
+
+    /// Path of the module, with its name sanitized if requested.
+    ///
+    /// # Errors
+    /// + If the module's name is not a valid module name, and is not sanitized.
+    fn checked_path(&self) -> Result<PathBuf> {
+        let name = module_name(&self.path)?.to_string_lossy();
+        if !self.sanitize {
+            name::validate(&name)?;
+            return Ok(self.path.clone());
+        }
+
+        Ok(self.path.with_file_name(name::sanitize(&name)?))
+    }
+
+    /// Options for the test file, if one will be created.
+    fn test_options(&self) -> Option<&TestOptions> {
+        self.with_test.then_some(&self.test)
+    }
+
+    /// Options for adding the module to its super, if it will be added.
+    fn super_options_if_added(&self) -> Option<&SuperOptions> {
+        self.add_to_super.then_some(&self.super_options)
+    }
+}
+
+/// Plan the creation of a new module, without modifying anything.
+///
+/// # Args
+/// See [`main`].
+///
+/// # Errors
+/// See [`CreateOptions::create`].
+pub fn plan(
+    path: &Path,
+    dir: bool,
+    test: Option<&TestOptions>,
+    add_to_super: Option<&SuperOptions>,
+) -> Result<Plan> {
+    plan_in(&DiskFileSystem, path, dir, test, add_to_super)
+}
+
+/// Plan the creation of a new module in a file system. See [`plan`].
+fn plan_in(
+    fs: &dyn FileSystem,
+    path: &Path,
+    dir: bool,
+    test: Option<&TestOptions>,
+    add_to_super: Option<&SuperOptions>,
+) -> Result<Plan> {
+    let name = match module_name(path)?.to_str() {
+        Some(name) => name.to_string(),
+        None => return Err(Error::InvalidModuleName(module_name(path)?.to_string_lossy().into_owned())),
+    };
+
+    let module_path = project::absolute_path_in(fs, path)?;
+    let module_file = if dir {
+        module_path.join("mod.rs")
+    } else {
+        module_path.with_extension("rs")
+    };
+
+    if fs.exists(&module_path) || fs.exists(&module_file) {
+        let existing = match fs.exists(&module_file) {
+            true => module_file,
+            false => module_path,
+        };
+
+        return Err(Error::ModuleExists(existing));
+    }
+
+    let test_path = match test {
+        Some(test) => test_path(fs, &module_file, test)?,
+        None => None,
+    };
+
+    let (super_file, declaration, declaration_location) = match add_to_super {
+        Some(options) => {
+            let super_file = checked_super_path(fs, path, options)?;
+            let content = fs.read_to_string(&super_file)?;
+            match addition(&content, &super_file, &name, options)? {
+                Addition::Keep(location) | Addition::Widen(_, location) => (Some(super_file), None, Some(location)),
+                Addition::Insert => {
+                    let declaration = super_declaration(&name, &content, options)?;
+                    let insert = insert_line(&content, &name, options)?;
+                    render_insert(&declaration, insert, &content, &super_file)?;
+                    let location = declaration_location(&declaration, &name, insert, &content, &super_file)?;
+                    (Some(super_file), Some(declaration), Some(location))
+                },
+            }
+        },
+
+        None => (None, None, None),
+    };
+
+    Ok(Plan {
+        name,
+        module_path: if dir { module_path } else { module_file },
+        dir,
+        test_path,
+        super_file,
+        declaration,
+        declaration_location,
+        parents: Vec::new(),
+    })
+}
 
 /// Create a new module.
 ///
@@ -18,11 +755,12 @@ use std::ffi::OsStr;
 /// + `dir`: If the module is a direcotry or a file.
 /// + `add_to_super`: Automatically add the new module to it's super, if it exists. 
 /// + `super_main`: Add module to main instead of lib. Only applicable if `add_to_super` is true,
-/// and module is being created in the crate root.
+///   and module is being created in the crate root.
 /// + `public`: Add the module as public.
 ///
 /// # Errors
 /// + If a module of the given name already exists.
+#[deprecated(note = "use `CreateOptions` instead")]
 pub fn main(
     path: &Path, 
     dir: bool, 
@@ -31,216 +769,1132 @@ pub fn main(
     super_main: bool, 
     public: bool
 ) -> Result {
-    if path.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::AlreadyExists, "file already exists"
-        ).into());
-    }
-
-    let mod_path;
-    if dir {
-        mod_path = make_mod_dir(path, with_test)?;
-    } else {
-        mod_path = make_mod_file(path, with_test)?;
-    }
-
-    if add_to_super {
-        crate::add_to_super(&mod_path, super_main, public)?;
-    }
+    CreateOptions::new(path)
+        .dir(dir)
+        .with_test(with_test)
+        .add_to_super(add_to_super)
+        .super_main(super_main)
+        .public(public)
+        .create()?;
 
     Ok(())
 }
 
-/// Make a file module.
+
+/// Create a test support module, for helpers shared between unit tests.
+///
+/// If the test support tree does not exist, it is created as a private directory module
+/// declared as `#[cfg(test)] mod <tree>;` in its super.
 ///
 /// # Arguments
-/// + `path`: Path of the module. Should not include file extensions.
-/// + `with_test`: Create a test module.
+/// + `path`: Path of the module, inside the test support tree. e.g. `src/test_support/fixtures`.
+/// + `content`: Options for the module's contents.
+/// + `add_to_super`: Options for adding the module to the test support tree.
+///   The tree's declaration uses the same options, but is always private and cfg-gated.
 ///
-pub fn make_mod_file(path: &Path, with_test: bool) -> Result<PathBuf> {
-    // get module name
-    let name = match path.file_name() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-            io::ErrorKind::InvalidFilename, "module name could not be derived from path"
+/// # Returns
+/// Files touched while creating the tree, if it was created, and the module.
+///
+/// # Errors
+/// See [`CreateOptions::create`].
+pub fn test_support(
+    path: &Path,
+    content: &ContentOptions,
+    add_to_super: &SuperOptions,
+) -> Result<Vec<Created>> {
+    test_support_in(&mut DiskFileSystem, path, content, add_to_super)
+}
+
+/// Create a test support module in a file system. See [`test_support`].
+pub fn test_support_in(
+    fs: &mut dyn FileSystem,
+    path: &Path,
+    content: &ContentOptions,
+    add_to_super: &SuperOptions,
+) -> Result<Vec<Created>> {
+    let tree = match path.parent() {
+        Some(tree) if !tree.as_os_str().is_empty() => tree,
+        _ => return Err(io::Error::new(
+            io::ErrorKind::InvalidInput, "module must be inside of the test support tree"
         ).into()),
     };
-    let name = match name.to_str() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-            io::ErrorKind::InvalidFilename, "module name could not convert module name to string"
-        ).into()),
+
+    let mut attributes = vec![String::from("#[cfg(test)]")];
+    attributes.extend(add_to_super.attributes.iter().cloned());
+    let tree_options = SuperOptions {
+        vis: Some(Visibility::Private),
+        attributes,
+        super_file: None,
+        ..add_to_super.clone()
     };
 
-    // create module file
-    let mod_path = path.with_extension("rs");
-    let mut file = File::create_new(&mod_path)?;
-    if with_test {
-       // create module test
-        let path_str = match path.to_str() {
-            Some(p) => p,
-            None => return Err(io::Error::new(
-                io::ErrorKind::InvalidFilename, "path could not be converted to string"
-            ).into()),
-        };
+    // the tree is removed if the module can not be created
+    let tree_exists = fs.exists(tree);
+    ModuleCreator::new(fs).transaction(|creator| {
+        let mut created = Vec::new();
+        if !tree_exists {
+            created.push(creator.create_module(tree, true, &ContentOptions::default(), None, Some(&tree_options))?);
+        }
 
-        let test_path = format!("{}_test.rs", path_str);
-        File::create(test_path)?;
-     
-        // add test to module file
-        let content = file_template_with_test(name);
-        let content = content.into_bytes();
-        file.write(&content)?;
-    }
+        created.push(creator.create_module(path, false, content, None, Some(add_to_super))?);
+        Ok(created)
+    })
+}
 
-    Ok(mod_path)
+/// Create the missing ancestors of a module, so it can be created.
+///
+/// Each missing ancestor directory is created as a directory module and added to its super.
+/// If a file module of the same name exists, only its directory is created,
+/// so its submodules are declared in it.
+/// Ancestors are created from the top down, stopping at the first existing directory.
+///
+/// # Arguments
+/// + `path`: Path of the module whose ancestors should be created. e.g. `src/a/b/c`.
+/// + `content`: Options for the contents of created ancestors.
+/// + `add_to_super`: Options for adding created ancestors to their supers, or `None` to not add them.
+///
+/// # Returns
+/// The ancestors created, outermost first.
+pub fn create_parents(
+    path: &Path,
+    content: &ContentOptions,
+    add_to_super: Option<&SuperOptions>,
+) -> Result<Vec<Created>> {
+    ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| creator.create_parents(path, content, add_to_super))
 }
 
-/// Create a directory module.
+/// Convert a file module to a directory module.
+///
+/// `a.rs` is moved to `a/mod.rs`, and its `#[path]` attributes are updated
+/// so they still refer to the same files. The module's declaration does not change.
 ///
 /// # Arguments
-/// + `path`: Path of the module.
-/// + `with_test`: Create a test module.
-pub fn make_mod_dir(path: &Path, with_test: bool) -> Result<PathBuf> {
-    fs::create_dir(path)?; 
+/// + `file`: File of the module. e.g. `src/a.rs`.
+///
+/// # Returns
+/// The removed file and the created file.
+pub fn convert_to_dir(file: &Path) -> Result<Vec<PathBuf>> {
+    convert_to_dir_in(&mut DiskFileSystem, file)
+}
+
+/// Convert a file module to a directory module in a file system. See [`convert_to_dir`].
+pub fn convert_to_dir_in(fs: &mut dyn FileSystem, file: &Path) -> Result<Vec<PathBuf>> {
+    let dir = file.with_extension("");
+    if fs.exists(&dir) {
+        return Err(Error::ModuleExists(dir));
+    }
+
+    let old_dir = match file.parent() {
+        Some(parent) => project::absolute_path_in(fs, parent)?,
+        None => return Err(Error::InvalidPath(file.to_path_buf())),
+    };
 
-    let mod_path = path.join("mod");
-    make_mod_file(&mod_path, with_test)?;
+    let new_dir = old_dir.join(module_name(file)?);
+    let content = retarget_paths(&fs.read_to_string(file)?, &old_dir, &new_dir)?;
 
-    Ok(path.to_path_buf())
+    let mod_file = dir.join("mod.rs");
+    fs.create_dir(&dir)?;
+    fs.write(&mod_file, &content)?;
+    fs.remove_file(file)?;
+    Ok(vec![file.to_path_buf(), mod_file])
 }
 
-/// Add a module to its super module.
+/// Rewrite the `#[path]` attributes of a module moving between directories
+/// so they still refer to the same files.
 ///
-/// # Argument
-/// + `path`: Path of the module to add.
-/// + `super_main`: Add module to main instead of lib. Only applicable if adding module to crate
-/// root.
-/// + `public`: Add the module as public.
-pub fn add_to_super(path: &Path, super_main: bool, public: bool) -> Result {
-    // get super file
-    let super_file = super_path(path, super_main)?;
+/// # Arguments
+/// + `content`: Content of the module.
+/// + `old_dir`: Absolute path of the directory the module is in.
+/// + `new_dir`: Absolute path of the directory the module is moving to.
+fn retarget_paths(content: &str, old_dir: &Path, new_dir: &Path) -> Result<String> {
+    let re_path = Regex::new(r#"#\[path\s*=\s*"([^"]+)"\]"#)?;
+    let content = re_path.replace_all(content, |caps: &regex::Captures| {
+        let target = project::normalize_path(&old_dir.join(&caps[1]));
+        format!("#[path = \"{}\"]", project::relative_path(new_dir, &target))
+    });
+
+    Ok(content.into_owned())
+}
+
+/// Render the contents of a file module and its test file.
+///
+/// # Arguments
+/// + `fs`: File system the module is created in.
+/// + See [`make_mod_file`] for the others.
+///
+/// # Returns
+/// The module's content, and the path and content of its test file if one will be created.
+///
+/// # Errors
+/// + [`Error::Parse`] if the generated code does not parse.
+fn render_mod_file(
+    fs: &dyn FileSystem,
+    path: &Path,
+    content: &ContentOptions,
+    test: Option<&TestOptions>,
+) -> Result<(String, Option<(PathBuf, String)>)> {
+    // get module name
+    let name = match path.file_name() {
+        Some(p) => p,
+        None => return Err(Error::InvalidPath(path.to_path_buf())),
+    };
+    let name = match name.to_str() {
+        Some(p) => p,
+        None => return Err(Error::InvalidModuleName(name.to_string_lossy().into_owned())),
+    };
+
+    // render module and test file
+    let mod_path = path.with_extension("rs");
+
+    // directory modules are created as `<name>/mod`
+    let is_dir = name == "mod";
+    let mod_name = match is_dir {
+        true => path.parent().and_then(Path::file_name).and_then(OsStr::to_str).unwrap_or(name),
+        false => name,
+    };
+
+    let template = match is_dir {
+        true => content.templates.dir_module.as_ref().or(content.templates.module.as_ref()),
+        false => content.templates.module.as_ref(),
+    };
+
+    let templates = &content.templates;
+    let vars = if template.is_some() || templates.test.is_some() || templates.header.is_some() || templates.doc.is_some() {
+        Some(template_vars(fs, &mod_path, mod_name)?)
+    } else {
+        None
+    };
+
+    let mut mod_content = preamble(content, vars.as_ref())?;
+
+    match (content.kind, template, &vars) {
+        (Some(kind), _, _) => mod_content.push_str(&kind.module_for(mod_name, rust_version(fs, &mod_path)?)),
+        (None, Some(template), Some(vars)) => {
+            mod_content.push_str(&render_template(template, vars.clone())?);
+        },
+
+        _ => {},
+    }
+
+    let test_file = match test {
+        Some(test) => {
+            let test_content = match (&content.templates.test, &vars) {
+                (Some(template), Some(vars)) => {
+                    let imports: Vec<String> = test.imports
+                        .iter()
+                        .map(|import| import_path(import).to_string())
+                        .collect();
+
+                    render_template(template, minijinja::context! {
+                        imports,
+                        test_attribute => test.framework.attribute(),
+                        test_async => test.framework.is_async(),
+                        ..vars.clone()
+                    })?
+                },
+
+                _ => test_file_template(&test.imports),
+            };
+
+            let test_content = match test.location {
+                TestLocation::Inline => test_content,
+                _ => format!("{}{test_content}", header(content, vars.as_ref())?),
+            };
+
+            let test_content = match (content.kind, &content.templates.test) {
+                (Some(kind), _) => format!("{test_content}{}", test.framework.convert(&kind.test(mod_name))),
+                (None, None) => format!("{test_content}{}", test.framework.skeleton()),
+                (None, Some(_)) => test_content,
+            };
+
+            match test_path(fs, &mod_path, test)? {
+                Some(test_path) => {
+                    let mod_dir = match project::absolute_path_in(fs, &mod_path)?.parent() {
+                        Some(p) => p.to_path_buf(),
+                        None => return Err(Error::InvalidPath(mod_path)),
+                    };
+
+                    let rel_test_path = project::relative_path(&mod_dir, &project::absolute_path_in(fs, &test_path)?);
+                    if !mod_content.is_empty() {
+                        mod_content.push('\n');
+                    }
+
+                    mod_content.push_str(&file_template_with_test(name, &rel_test_path, &test.cfg));
+                    Some((test_path, test_content))
+                },
+
+                None => {
+                    if !mod_content.is_empty() {
+                        mod_content.push('\n');
+                    }
+
+                    mod_content.push_str(&inline_test_template(&test.cfg, &test_content, "\n"));
+                    None
+                },
+            }
+        },
+
+        None => None,
+    };
+
+    // ensure generated code is valid before writing anything
+    validate("module", &mod_content)?;
+    if let Some((_, test_content)) = &test_file {
+        validate("test file", test_content)?;
+    }
+
+    Ok((mod_content, test_file))
+}
+
+/// Make a file module.
+///
+/// # Arguments
+/// + `path`: Path of the module. Should not include file extensions.
+/// + `content`: Options for the module's contents.
+/// + `test`: Options for the test module, or `None` to not create one.
+///
+/// # Returns
+/// Files created.
+pub fn make_mod_file(
+    path: &Path,
+    content: &ContentOptions,
+    test: Option<&TestOptions>,
+) -> Result<Created> {
+    ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| creator.make_mod_file(path, content, test))
+}
+
+/// Render the content a module would be created with, e.g. to compare it with the module as it is.
+///
+/// # Arguments
+/// + `path`: Path to the module's file. e.g. `src/a.rs` or `src/a/mod.rs`.
+/// + `content`: Options for the module's contents.
+/// + `test`: Options for the module's tests, or `None` if it has none.
+///
+/// # Returns
+/// Content of the module's file.
+pub fn render_module(path: &Path, content: &ContentOptions, test: Option<&TestOptions>) -> Result<String> {
+    let (mod_content, _) = render_mod_file(&DiskFileSystem, &path.with_extension(""), content, test)?;
+    Ok(mod_content)
+}
+
+/// Find where the tests of a module are, from the test modules it declares.
+///
+/// # Arguments
+/// + `path`: Path to the module's file.
+/// + `content`: Content of the module's file.
+///
+/// # Returns
+/// [`TestLocation::Inline`] if the module has a `mod tests` block, [`TestLocation::Sibling`]
+/// if it declares a `<name>_test` module, or `None` if it has neither or does not parse.
+pub fn test_location(path: &Path, content: &str) -> Option<TestLocation> {
+    let test_mod = format!("{}_test", path.file_stem()?.to_str()?);
+    let file = syn::parse_file(content).ok()?;
+    file.items.iter().find_map(|item| match item {
+        syn::Item::Mod(item) if item.ident == "tests" && item.content.is_some() => Some(TestLocation::Inline),
+        syn::Item::Mod(item) if item.ident == test_mod.as_str() => Some(TestLocation::Sibling),
+        _ => None,
+    })
+}
+
+/// Ensure generated code parses.
+///
+/// # Arguments
+/// + `template`: Name of what was generated, used in the error. e.g. `module`.
+/// + `content`: Generated code.
+///
+/// # Errors
+/// + [`Error::Parse`] if the code does not parse.
+fn validate(template: &str, content: &str) -> Result {
+    match syn::parse_file(content) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let start = err.span().start();
+            Err(Error::Parse {
+                template: template.to_string(),
+                line: start.line,
+                column: start.column + 1,
+                message: err.to_string(),
+            })
+        },
+    }
+}
+
+/// Get the path of a module's test file.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `mod_path`: Path to the module's file.
+/// + `test`: Options for the test module.
+///
+/// # Returns
+/// Path of the test file, or `None` for inline tests.
+fn test_path(fs: &dyn FileSystem, mod_path: &Path, test: &TestOptions) -> Result<Option<PathBuf>> {
+    let test_name = match mod_path.file_stem().and_then(OsStr::to_str) {
+        Some(stem) => format!("{stem}_test.rs"),
+        None => return Err(Error::InvalidPath(mod_path.to_path_buf())),
+    };
+
+    let test_path = match &test.location {
+        TestLocation::Sibling => mod_path.with_file_name(test_name),
+        TestLocation::Mirrored(root) => {
+            let project = project(fs, mod_path, test.src_root.as_deref())?;
+            let mod_path = project::absolute_path_in(fs, mod_path)?;
+            let rel_path = match mod_path.strip_prefix(&project.src) {
+                Ok(p) => p,
+                Err(_) => return Err(Error::NotInSource { path: mod_path, src: project.src }),
+            };
+
+            project.root.join(root).join(rel_path).with_file_name(test_name)
+        },
+
+        TestLocation::Inline => return Ok(None),
+    };
+
+    Ok(Some(test_path))
+}
+
+/// Create a directory module.
+///
+/// # Arguments
+/// + `path`: Path of the module.
+/// + `content`: Options for the module's contents.
+/// + `test`: Options for the test module, or `None` to not create one.
+///
+/// # Returns
+/// Files created.
+pub fn make_mod_dir(
+    path: &Path,
+    content: &ContentOptions,
+    test: Option<&TestOptions>,
+) -> Result<Created> {
+    ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| creator.make_mod_dir(path, content, test))
+}
+
+/// Add a module to its super module.
+///
+/// # Argument
+/// + `path`: Path of the module to add.
+/// + `options`: Options for adding the module.
+///
+/// # Errors
+/// + If the super file has unstaged changes, unless allowed.
+pub fn add_to_super(path: &Path, options: &SuperOptions) -> Result {
+    // get super file
+    let super_file = checked_super_path(&DiskFileSystem, path, options)?;
+
+    // add new module to super
+    add_module_to(module_name(path)?, &super_file, options)?;
+    Ok(())
+}
+
+/// Find the project containing a path.
+///
+/// # Arguments
+/// + `fs`: File system the project is in.
+/// + `path`: Path inside the project.
+/// + `src_root`: Source root, relative to the crate root, or `None` for `src`.
+fn project(fs: &dyn FileSystem, path: &Path, src_root: Option<&Path>) -> Result<Project> {
+    let project = Project::discover_in(fs, path)?;
+    Ok(match src_root {
+        Some(src_root) => project.with_src_root(src_root),
+        None => project,
+    })
+}
+
+/// Get the name of a module from its path.
+///
+/// # Arguments
+/// + `path`: Path to the module. Should be the file path for a file module,
+///   or the directory for a directory module.
+fn module_name(path: &Path) -> Result<&OsStr> {
+    match path.file_stem() {
+        Some(p) => Ok(p),
+        None => Err(Error::InvalidPath(path.to_path_buf())),
+    }
+}
+
+/// Get the super file of the given module, ensuring it may be modified.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `path`: Path to the module.
+/// + `options`: Options for adding the module.
+///
+/// # Errors
+/// + If the super file may not be modified. See [`check_modifiable`].
+/// + If the explicit insertion line is outside of the super file.
+fn checked_super_path(fs: &dyn FileSystem, path: &Path, options: &SuperOptions) -> Result<PathBuf> {
+    let super_file = resolve_super(fs, path, options)?;
+    check_modifiable(fs, &super_file, options)?;
+
+    if let Some(line) = options.at {
+        explicit_insert_line(&fs.read_to_string(&super_file)?, line)?;
+    }
+
+    Ok(super_file)
+}
+
+/// Get the super file of a module, using the explicit super file if given.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `path`: Path to the module.
+/// + `options`: Options for adding the module.
+///
+/// # Errors
+/// + [`Error::ParentNotFound`] if the explicit super file does not exist.
+/// + If the super can not be resolved. See [`super_path`].
+fn resolve_super(fs: &dyn FileSystem, path: &Path, options: &SuperOptions) -> Result<PathBuf> {
+    match &options.super_file {
+        Some(super_file) if fs.is_file(super_file) => Ok(project::absolute_path_in(fs, super_file)?),
+        Some(super_file) => Err(Error::ParentNotFound(super_file.clone())),
+        None => super_path(fs, path, options.main, options.src_root.as_deref(), options.target.as_deref()),
+    }
+}
+
+/// Ensure an existing file may be modified, so edits are not tangled with work in progress.
+/// The file's changes are read from the file system's version control.
+///
+/// # Errors
+/// + [`Error::Uncommitted`] if the file has uncommitted changes and `options.require_clean` is set.
+/// + [`Error::Dirty`] if the file has unstaged changes and `options.dirty_ok` is not set.
+fn check_modifiable(fs: &dyn FileSystem, path: &Path, options: &SuperOptions) -> Result {
+    if options.require_clean && fs.vcs().has_uncommitted_changes(path)? {
+        return Err(Error::Uncommitted(path.to_path_buf()));
+    }
+
+    if !options.require_clean && !options.dirty_ok && fs.vcs().has_unstaged_changes(path)? {
+        return Err(Error::Dirty(path.to_path_buf()));
+    }
+
+    Ok(())
+}
+
+/// Get the super file of the given module file.
+/// 
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `path`: Path to the module. Should be the file path for a file module,
+///   or the directory for a directory module.
+///   The module does not need to exist.
+/// + `super_main`: Default to `main.rs`.
+/// + `src_root`: Source root, relative to the crate root.
+///   If `None`, a module's parent is the crate root if it is the `src` of the nearest package.
+/// + `target`: Name of the target whose root module to use,
+///   if the module is in the directory of a target's root module.
+///
+/// # Returns
+/// Path to the module's super file.
+/// For a module in the directory of a target's root module, this is the root module
+/// read from the manifest, preferring the library unless `super_main` is set.
+/// Otherwise, this is the module whose submodules are in the module's directory,
+/// found by following declarations from the crate's roots, so parents loaded with `#[path]` are found.
+/// If no single module is found, for a module in `foo/` this is `foo.rs` if it exists and `foo/mod.rs` does not,
+/// otherwise `foo/mod.rs`.
+///
+/// # Errors
+/// + If `src_root` is given and the module is not inside of it.
+/// + If `target` does not exist, or its root module is not in the module's directory.
+/// + If the module's directory contains the roots of several binaries and no library,
+///   and `target` is not given.
+fn super_path(
+    fs: &dyn FileSystem,
+    path: &Path,
+    super_main: bool,
+    src_root: Option<&Path>,
+    target: Option<&str>,
+) -> Result<PathBuf> {
+    // get parent
+    let abs_path = project::absolute_path_in(fs, path)?;
+    let parent = match abs_path.parent() {
+        Some(p) => p,
+        None => return Err(Error::InvalidPath(abs_path.clone())),
+    };
+
+    let g_parent = match parent.parent() {
+        Some(p) => p,
+        None => return Err(Error::InvalidPath(abs_path.clone())),
+    };
+
+    // parent is either the crate root or a multi-file binary in `bin/<name>/`
+    let (parent_is_root, parent_is_bin) = match src_root {
+        Some(src_root) => {
+            let project = project(fs, path, Some(src_root))?;
+            if !parent.starts_with(&project.src) {
+                return Err(Error::NotInSource { path: abs_path, src: project.src });
+            }
+
+            (parent == project.src, g_parent == project.src.join("bin"))
+        },
+
+        None => match Project::discover_in(fs, path) {
+            Ok(project) if parent.starts_with(&project.src) => {
+                (parent == project.src, g_parent == project.src.join("bin"))
+            },
+
+            // outside the package's `src`, e.g. a custom layout
+            _ => {
+                let parent_is_bin = g_parent.file_name() == Some(OsStr::new("bin"))
+                    && g_parent.parent()
+                        .and_then(Path::parent)
+                        .is_some_and(|dir| project::is_package_root_in(fs, dir));
+
+                (project::is_package_root_in(fs, g_parent), parent_is_bin)
+            },
+        },
+    };
+
+    let super_file: PathBuf;
+    if let Some(target_root) = target_root(fs, parent, super_main, target)? {
+        super_file = target_root;
+    } else if parent_is_bin {
+        super_file = parent.join("main.rs");
+    } else if parent_is_root {
+        if super_main {
+            super_file = parent.join("main.rs");
+        } else {
+            let lib_file = parent.join("lib.rs"); 
+            if fs.exists(&lib_file) {
+                super_file = lib_file;
+            } else {
+                // fall back to main.rs
+                super_file = parent.join("main.rs"); 
+            }
+        } 
+    } else if let Some(declared) = declared_super(fs, path, parent, target) {
+        super_file = declared;
+    } else {
+        // prefer `foo.rs` next to `foo/` over `foo/mod.rs`
+        let named_file = parent.with_extension("rs");
+        if fs.is_file(&named_file) && !fs.exists(&parent.join("mod.rs")) {
+            super_file = named_file;
+        } else {
+            super_file = parent.join("mod.rs");
+        }
+
+        // e.g. a file loaded with `#[path]` keeps its submodules next to it
+        if let Some(dir) = submodule_dir(fs, path, &super_file).filter(|dir| dir != parent) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("submodules of `{}` are in `{}`", super_file.display(), dir.display()),
+            ).into());
+        }
+    }
+
+    if !fs.exists(&super_file) {
+        return Err(Error::ParentNotFound(super_file));
+    }
+
+    // e.g. a file loaded with `#[path]` owns its directory, so it is the super of its siblings
+    if super_file == abs_path.with_extension("rs") || super_file == abs_path.join("mod.rs") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("module `{}` would be declared in its own file", abs_path.display()),
+        ).into());
+    }
+
+    Ok(super_file)
+}
+
+/// Get the module whose submodules are in a directory, following declarations from the crate's roots.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `path`: Path to the module being added.
+/// + `dir`: Directory of the module being added.
+/// + `target`: Name of the target whose modules to consider, or `None` for all targets.
+///
+/// # Returns
+/// `None` if the module tree can not be read, or no single module owns the directory.
+fn declared_super(fs: &dyn FileSystem, path: &Path, dir: &Path, target: Option<&str>) -> Option<PathBuf> {
+    let project = Project::discover_in(fs, path).ok()?;
+    let tree = tree::Tree::read_in(fs, &project).ok()?;
+    tree.owner(dir, target).map(|module| module.file.clone())
+}
+
+/// Get the directory a module file's submodules are in, following declarations from the crate's roots.
+///
+/// # Arguments
+/// + `fs`: File system the crate is in.
+/// + `path`: Path inside of the crate.
+/// + `file`: File of the module.
+///
+/// # Returns
+/// `None` if the module tree can not be read, or the file is not part of it.
+fn submodule_dir(fs: &dyn FileSystem, path: &Path, file: &Path) -> Option<PathBuf> {
+    let project = Project::discover_in(fs, path).ok()?;
+    let tree = tree::Tree::read_in(fs, &project).ok()?;
+    let mut stack: Vec<&tree::Module> = tree.roots.iter().collect();
+    while let Some(module) = stack.pop() {
+        if !module.inline && module.file == file {
+            return Some(module.dir.clone());
+        }
+
+        stack.extend(module.children.iter());
+    }
+
+    None
+}
+
+/// Get the root module of a target in a directory.
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `dir`: Directory of the module being added.
+/// + `super_main`: Prefer a binary over the library.
+/// + `target`: Name of the target to use.
+///
+/// # Returns
+/// `None` if no target's root module is in `dir`.
+fn target_root(fs: &dyn FileSystem, dir: &Path, super_main: bool, target: Option<&str>) -> Result<Option<PathBuf>> {
+    let targets = match Project::discover_in(fs, dir).and_then(|project| project.targets_in(fs)) {
+        Ok(targets) => targets,
+        Err(err) if target.is_some() => return Err(err),
+        Err(_) => return Ok(None),
+    };
+
+    let candidates: Vec<&Target> = targets
+        .iter()
+        .filter(|candidate| candidate.root.parent() == Some(dir))
+        .collect();
+
+    if let Some(name) = target {
+        let Some(target) = targets.iter().find(|candidate| candidate.name == name) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound, format!("target `{name}` does not exist")
+            ).into());
+        };
+
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        if !candidates.contains(&target) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("root module of target `{name}` is not in the module's directory")
+            ).into());
+        }
+
+        return Ok(Some(target.root.clone()));
+    }
+
+    let lib = candidates.iter().find(|candidate| candidate.kind == TargetKind::Lib);
+    let bins: Vec<&&Target> = candidates.iter().filter(|candidate| candidate.kind == TargetKind::Bin).collect();
+    let bin = match bins.as_slice() {
+        [] => None,
+        [bin] => Some(bin),
+        _ if lib.is_some() && !super_main => None,
+        _ => return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "directory contains the root modules of several binaries, use --target"
+        ).into()),
+    };
+
+    let root = match (lib, bin) {
+        (None, Some(bin)) => Some(&bin.root),
+        (_, Some(bin)) if super_main => Some(&bin.root),
+        (Some(lib), _) if !super_main => Some(&lib.root),
+        _ => None,
+    };
+
+    Ok(root.cloned())
+}
+
+/// Adds a submodule.
+///
+/// # Arguments
+/// + `mod_name`: Name of the module to be added.
+/// + `path`: Path of the file to which the module should be added.
+/// + `options`: Options for adding the module.
+///
+/// # Returns
+/// Location of the declaration, and the 1-based lines inserted.
+fn add_module_to(mod_name: &OsStr, path: &Path, options: &SuperOptions) -> Result<(Location, Vec<usize>)> {
+    // get module name
+    let mod_name = match mod_name.to_str() {
+        Some(p) => p,
+        None => return Err(Error::InvalidModuleName(mod_name.to_string_lossy().into_owned())),
+    };
+
+    ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| creator.declare(mod_name, path, options))
+}
+
+/// Renders the content of a super file with a module declared in it.
+/// An existing declaration is kept or widened instead of declaring the module twice. See [`addition`].
+///
+/// # Arguments
+/// + `content`: Content of the super file.
+/// + `path`: Path to the super file, for locations and errors.
+/// + `mod_name`: Name of the module.
+/// + `options`: Options for adding the module.
+///
+/// # Returns
+/// The new content, the location of the declaration, and the 1-based lines inserted or changed.
+fn declare(content: &str, path: &Path, mod_name: &str, options: &SuperOptions) -> Result<(String, Location, Vec<usize>)> {
+    match addition(content, path, mod_name, options)? {
+        Addition::Insert => {},
+        Addition::Keep(location) => return Ok((content.to_string(), location, Vec::new())),
+        Addition::Widen(new, location) => {
+            let line = location.line;
+            return Ok((new, location, vec![line]));
+        },
+    }
+
+    let insert = insert_line(content, mod_name, options)?;
+    let declaration = super_declaration(mod_name, content, options)?;
+    let location = declaration_location(&declaration, mod_name, insert, content, path)?;
+    let start = insert.unwrap_or_else(|| content.lines().count());
+    let new = render_insert(&declaration, insert, content, path)?;
+    Ok((new, location, (start + 1..=start + declaration.lines().count()).collect()))
+}
+
+/// How a module's declaration is added to a super file that may already declare it.
+#[derive(Clone, Debug, PartialEq)]
+enum Addition {
+    /// Insert the declaration.
+    Insert,
+
+    /// Keep the existing declaration, at the given location.
+    Keep(Location),
+
+    /// Widen the visibility of the existing declaration.
+    /// Contains the super file's new content, and the location of the declaration in it.
+    Widen(String, Location),
+}
+
+/// Decides how to add a module's declaration to a super file, so the module is not declared twice.
+///
+/// An existing declaration is kept if it is at least as visible as requested, and widened otherwise.
+/// Declarations whose `cfg` attributes differ from the requested ones,
+/// e.g. `#[cfg(unix)]` and `#[cfg(windows)]`, are alternatives rather than duplicates.
+/// Files that do not parse are not checked.
+///
+/// # Arguments
+/// + `content`: Content of the super file.
+/// + `path`: Path to the super file.
+/// + `mod_name`: Name of the module.
+/// + `options`: Options for adding the module.
+///
+/// # Errors
+/// + [`Error::Declared`] if the module is declared inline,
+///   or with a visibility that can not be widened to the requested one.
+fn addition(content: &str, path: &Path, mod_name: &str, options: &SuperOptions) -> Result<Addition> {
+    let Ok(file) = syn::parse_file(content) else {
+        return Ok(Addition::Insert);
+    };
+
+    let declaration = declaration(mod_name, options);
+    let requested = match syn::parse_file(&declaration) {
+        Ok(parsed) => match parsed.items.first() {
+            Some(syn::Item::Mod(item)) => cfgs(&declaration, &item.attrs),
+            _ => Vec::new(),
+        },
+
+        Err(_) => Vec::new(),
+    };
+
+    for item in &file.items {
+        let syn::Item::Mod(item) = item else {
+            continue;
+        };
+
+        if item.ident.to_string().trim_start_matches("r#") != mod_name {
+            continue;
+        }
+
+        let existing = cfgs(content, &item.attrs);
+        if !requested.is_empty() && !existing.is_empty() && requested != existing {
+            continue;
+        }
+
+        let start = item.mod_token.span.start();
+        let mod_offset = byte_offset(content, start);
+        let line_start = content[..mod_offset].rfind('\n').map_or(0, |newline| newline + 1);
+        let mut location = Location { path: path.to_path_buf(), line: start.line, column: mod_offset - line_start + 1 };
+        let declared = |reason: String| Error::Declared { path: path.to_path_buf(), line: start.line, reason };
+        if item.content.is_some() {
+            return Err(declared(format!("module `{mod_name}` is already declared inline")));
+        }
+
+        let vis = Visibility::from(&item.vis);
+        let requested = options.visibility();
+        if vis.contains(&requested) {
+            return Ok(Addition::Keep(location));
+        }
+
+        if !requested.contains(&vis) {
+            return Err(declared(format!(
+                "module `{mod_name}` is already declared as `{vis}`, which can not be widened to `{requested}`",
+            )));
+        }
+
+        let (range, keyword) = match &item.vis {
+            syn::Visibility::Inherited => (mod_offset..mod_offset, format!("{} ", requested.keyword())),
+            vis => {
+                let span = vis.span();
+                (byte_offset(content, span.start())..byte_offset(content, span.end()), requested.keyword())
+            },
+        };
+
+        if range.start >= line_start {
+            location.column = location.column + keyword.len() - range.len();
+        }
+
+        let mut new = content.to_string();
+        new.replace_range(range, &keyword);
+        return Ok(Addition::Widen(new, location));
+    }
+
+    Ok(Addition::Insert)
+}
+
+/// Gets the `cfg` attributes of an item, without whitespace, sorted.
+///
+/// # Arguments
+/// + `content`: Source the attributes were parsed from.
+/// + `attrs`: Attributes of the item.
+fn cfgs(content: &str, attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut cfgs: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .map(|attr| {
+            let span = attr.span();
+            let text = &content[byte_offset(content, span.start())..byte_offset(content, span.end())];
+            text.split_whitespace().collect()
+        })
+        .collect();
+
+    cfgs.sort();
+    cfgs
+}
+
+/// Gets the byte offset of a position in a file's content.
+/// Positions are as reported by `syn`, with 1-based lines and 0-based character columns.
+fn byte_offset(content: &str, at: proc_macro2::LineColumn) -> usize {
+    let line_start: usize = content.split_inclusive('\n').take(at.line.saturating_sub(1)).map(str::len).sum();
+    let line = &content[line_start..];
+    line_start + line.char_indices().nth(at.column).map_or(line.len(), |(offset, _)| offset)
+}
+
+/// Finds where a module's statement will be once its declaration is inserted.
+///
+/// # Arguments
+/// + `declaration`: Declaration to be inserted. May span multiple lines.
+/// + `mod_name`: Name of the module.
+/// + `insert`: Line at which the declaration will be inserted, or `None` if it will be appended.
+/// + `content`: Content of the file the declaration will be inserted in.
+/// + `path`: Path to the file.
+fn declaration_location(
+    declaration: &str,
+    mod_name: &str,
+    insert: Option<usize>,
+    content: &str,
+    path: &Path,
+) -> Result<Location> {
+    let start = insert.unwrap_or_else(|| content.lines().count());
+
+    // point at the `mod` keyword, past any preceding attributes
+    let re_mod = Regex::new(&format!(r"\bmod\s+{}\b", regex::escape(&name::ident(mod_name))))?;
+    let (offset, column) = declaration
+        .lines()
+        .enumerate()
+        .find_map(|(offset, line)| re_mod.find(line).map(|found| (offset, found.start())))
+        .unwrap_or((0, 0));
+
+    Ok(Location {
+        path: path.to_path_buf(),
+        line: start + offset + 1,
+        column: column + 1,
+    })
+}
+
+/// Finds the line at which a new module should be inserted.
+///
+/// # Arguments
+/// + `content`: Content of the file to which the module should be added.
+/// + `mod_name`: Name of the module.
+/// + `options`: Options for adding the module.
+///
+/// # Returns
+/// The line at which to insert the module, or `None` to append it.
+fn insert_line(content: &str, mod_name: &str, options: &SuperOptions) -> Result<Option<usize>> {
+    match (options.at, options.format.insert) {
+        (Some(line), _) => explicit_insert_line(content, line),
+        (None, InsertPosition::End) => preamble_insert_line(content),
+        (None, InsertPosition::Sorted) => sorted_insert_line(content, mod_name),
+    }
+}
+
+/// Finds the line at which a new module should be inserted to keep module declarations
+/// in alphabetical order. The module is inserted before the first declaration, including its
+/// attributes, whose name sorts after it, or after the last declaration if there is none.
+///
+/// Files without module declarations, or that do not parse,
+/// fall back to [`preamble_insert_line`].
+///
+/// # Arguments
+/// + `content`: Content of the file to which the module should be added.
+/// + `mod_name`: Name of the module.
+///
+/// # Returns
+/// The line at which to insert the module, or `None` to append it.
+fn sorted_insert_line(content: &str, mod_name: &str) -> Result<Option<usize>> {
+    let file = match syn::parse_file(content) {
+        Ok(file) => file,
+        Err(_) => return preamble_insert_line(content),
+    };
 
-    // add new module to super
-    let mod_name = match path.file_stem() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-            io::ErrorKind::InvalidFilename, "could not derive module name from path"
-        ).into()),
+    let unraw = |name: &str| name.strip_prefix("r#").unwrap_or(name).to_string();
+    let mod_name = unraw(mod_name);
+    let declarations: Vec<&syn::Item> = file.items
+        .iter()
+        .filter(|item| matches!(item, syn::Item::Mod(_)) && is_preamble_item(item))
+        .collect();
+
+    let next = declarations.iter().find(|item| match item {
+        syn::Item::Mod(module) => unraw(&module.ident.to_string()) > mod_name,
+        _ => false,
+    });
+
+    let line = match (next, declarations.last()) {
+        (Some(next), _) => next.span().start().line - 1,
+        // spans are 1-based, so the end line is the index of the following line
+        (None, Some(last)) => last.span().end().line,
+        (None, None) => return preamble_insert_line(content),
     };
 
-    add_module_to(&mod_name, &super_file, public)
+    if line >= content.lines().count() {
+        Ok(None)
+    } else {
+        Ok(Some(line))
+    }
 }
 
-/// Get the super file of the given module file.
-/// 
+/// Finds the line at which a new module should be inserted, based on the file's contents.
+///
+/// The module is inserted after the leading `use`, `extern crate`, and `mod` declarations,
+/// or before the first item if there are none.
+/// Files that do not parse fall back to matching lines.
+///
 /// # Arguments
-/// + `path`: Path to the module. Should be the file path for a file module,
-///     or the directory for a directory module.
-/// + `super_main`: Default to `main.rs`.
+/// + `content`: Content of the file to which the module should be added.
 ///
 /// # Returns
-/// Path to the module's super file.
-fn super_path(path: &Path, super_main: bool) -> Result<PathBuf> {
-    // get parent
-    let abs_path = path.canonicalize()?;
-    let parent = match abs_path.parent() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-            io::ErrorKind::InvalidFilename, "parent could not be found from path"
-        ).into()),
+/// The line at which to insert the module, or `None` to append it.
+fn preamble_insert_line(content: &str) -> Result<Option<usize>> {
+    let file = match syn::parse_file(content) {
+        Ok(file) => file,
+        Err(_) => return line_preamble_insert_line(content),
     };
 
-    let g_parent = match parent.parent() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-            io::ErrorKind::InvalidFilename, "grandparent could not be found from path"
-        ).into()),
+    let preamble_len = file.items.iter().take_while(|item| is_preamble_item(item)).count();
+    let line = if preamble_len > 0 {
+        // spans are 1-based, so the end line is the index of the following line
+        file.items[preamble_len - 1].span().end().line
+    } else if let Some(item) = file.items.first() {
+        item.span().start().line - 1
+    } else {
+        content.lines().count()
     };
 
-    let cargo_file = g_parent.join("Cargo.toml");
-    let parent_is_root = cargo_file.exists();
-
-    let super_file: PathBuf;
-    if parent_is_root{
-        if super_main {
-            super_file = parent.join("main.rs");
-        } else {
-            let lib_file = parent.join("lib.rs"); 
-            if lib_file.exists() {
-                super_file = lib_file;
-            } else {
-                // fall back to main.rs
-                super_file = parent.join("main.rs"); 
-            }
-        } 
+    if line >= content.lines().count() && !content.is_empty() {
+        Ok(None)
     } else {
-        super_file = parent.join("mod.rs");
+        Ok(Some(line))
     }
+}
 
-    if !super_file.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput, "parent module does not exist"
-        ).into());
-    }
+/// Check if an item belongs to a file's preamble.
+/// i.e. `use` and `extern crate` items, and module declarations other than test modules.
+fn is_preamble_item(item: &syn::Item) -> bool {
+    match item {
+        syn::Item::Use(_) | syn::Item::ExternCrate(_) => true,
+        syn::Item::Mod(module) => {
+            let name = module.ident.to_string();
+            let is_test = name == "tests" || name.ends_with("_test") || module.attrs.iter().any(|attr| {
+                attr.path().is_ident("cfg") && attr
+                    .meta
+                    .require_list()
+                    .is_ok_and(|list| list.tokens.clone().into_iter().any(is_test_token))
+            });
 
-    Ok(super_file)
+            module.content.is_none() && !is_test
+        },
+
+        _ => false,
+    }
 }
 
+/// Check if a token of a `cfg` predicate is, or contains, the `test` predicate.
+fn is_test_token(token: proc_macro2::TokenTree) -> bool {
+    match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == "test",
+        proc_macro2::TokenTree::Group(group) => group.stream().into_iter().any(is_test_token),
+        _ => false,
+    }
+}
 
-/// Adds a submodule.
+/// Finds the line at which a new module should be inserted by matching lines.
+/// Used for files that do not parse.
 ///
 /// # Arguments
-/// + `mod_name`: Name of the module to be added.
-/// + `path`: Path of the file to which the module should be added.
-/// + `public`: Insert the module as public.
-fn add_module_to(mod_name: &OsStr, path: &Path, public: bool) -> Result {
-    // get module name
-    let mod_name = match mod_name.to_str() {
-        Some(p) => p,
-        None => return Err(io::Error::new(
-                io::ErrorKind::InvalidFilename, "invalid module name"
-        ).into()),
-    };
-
+/// + `content`: Content of the file to which the module should be added.
+///
+/// # Returns
+/// The line at which to insert the module, or `None` to append it.
+fn line_preamble_insert_line(content: &str) -> Result<Option<usize>> {
     // get file info
     let (
         preamble_exists,
         preamble_end, 
         header_comment_exists, 
         header_comment_end
-    ) = file_info(path)?;
+    ) = file_info(content)?;
 
     // calculate insert line
     let insert;
     if preamble_exists {
         // add new module to end of preamble
-        if preamble_end.is_none() {
-            // preamble ends file
-            // append new module
-            insert = None;
-        } else {
-            // preamble exists
-            // add new module to end
-            insert = Some(preamble_end.unwrap() + 1);
-        }
+        // if preamble ends file append new module,
+        // otherwise add new module to end of preamble
+        insert = preamble_end.map(|end| end + 1);
     } else if header_comment_exists {
         // add new module after header comment
-        if header_comment_end.is_none() {
-            // header comment ends file
-            // append new module
-            insert = None;
-        } else {
-            insert = Some(header_comment_end.unwrap() + 1);
-        }
-        
+        // if header comment ends file append new module
+        insert = header_comment_end.map(|end| end + 1);
     } else {
         // add new module to top of file
         insert = Some(0);
     }
-    
-    // insert module
-    insert_mod_at_line(&mod_name, insert, path, public)
+
+    Ok(insert)
+}
+
+/// Converts a user provided line number into an insert line.
+///
+/// # Arguments
+/// + `content`: Content of the file to which the module should be added.
+/// + `line`: 1-based line number the module should occupy.
+///
+/// # Returns
+/// The line at which to insert the module, or `None` to append it.
+///
+/// # Errors
+/// + If the line is not in the file, or directly after its last line.
+fn explicit_insert_line(content: &str, line: usize) -> Result<Option<usize>> {
+    let n_lines = content.lines().count();
+    if line == 0 || line > n_lines + 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput, "insertion line is outside of the super file"
+        ).into());
+    }
+
+    if line == n_lines + 1 {
+        Ok(None)
+    } else {
+        Ok(Some(line - 1))
+    }
 }
 
 /// Gets info on the given file.
@@ -250,19 +1904,18 @@ fn add_module_to(mod_name: &OsStr, path: &Path, public: bool) -> Result {
 /// where
 /// + `preamble_exists`: Whether `use` and `mod` statements exist in the file.
 /// + `preamble_end`: An Option of None if the file ended with or before the preamble ended,
-///     or Some(num) for the ending line of the preamble.
+///   or Some(num) for the ending line of the preamble.
 /// + `header_comment_exists`: Whether the file starts with a comment.
-/// + `preamble_end`: An Option of None if the file ended with or before the header comment ended,
-///     or Some(num) for the ending line of the header comment. 
-fn file_info(path: &Path) -> Result<(bool, Option<usize>, bool, Option<usize>)> {
-    let file = File::open(path)?;
+/// + `header_comment_end`: An Option of None if the file ended with or before the header comment
+///   ended, or Some(num) for the ending line of the header comment.
+fn file_info(content: &str) -> Result<(bool, Option<usize>, bool, Option<usize>)> {
 
     // find end of preamble
-    let re_use = Regex::new(r"^\s*use\s+")?;
+    let re_use = Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+")?;
     let re_mod = Regex::new(r"^\s*(?:pub)?\s*mod")?;
     let re_comment = Regex::new(r"^\s*//")?; // @todo: Include C++-style comments
 
-    let lines = io::BufReader::new(file).lines();
+    let lines = content.lines();
     let mut preamble_exists = false;
     let mut header_comment_exists = false;
     let mut content_start = false;
@@ -270,12 +1923,6 @@ fn file_info(path: &Path) -> Result<(bool, Option<usize>, bool, Option<usize>)>
     let mut preamble_end = None;
     let mut header_comment_end = None;
     for (l_num, line) in lines.enumerate() {
-        if let Err(err) = line {
-            return Err(err.into());
-        }     
-
-        let line = line.unwrap();
-        
         if !content_start && line.trim().is_empty() {
             // ignore leading blank lines
             continue;
@@ -284,7 +1931,7 @@ fn file_info(path: &Path) -> Result<(bool, Option<usize>, bool, Option<usize>)>
 
         if !body_start {
             // check for leading comment
-            let comment_line = re_comment.is_match(&line);
+            let comment_line = re_comment.is_match(line);
             if comment_line {
                 if !header_comment_exists {
                     header_comment_exists = true;
@@ -301,7 +1948,7 @@ fn file_info(path: &Path) -> Result<(bool, Option<usize>, bool, Option<usize>)>
         }
 
         // check for preamble lines
-        let preamble_line = re_use.is_match(&line) || re_mod.is_match(&line);
+        let preamble_line = re_use.is_match(line) || re_mod.is_match(line);
         match (preamble_line, preamble_exists) {
             (true, false) => preamble_exists = true,
             (false, true) => {
@@ -315,63 +1962,534 @@ fn file_info(path: &Path) -> Result<(bool, Option<usize>, bool, Option<usize>)>
     Ok((preamble_exists, preamble_end, header_comment_exists, header_comment_end))
 }
 
-/// Inserts the given module name as a public module in the given file.
+/// Formats the declaration of a module.
+///
+/// # Arguments
+/// + `mod_name`: Name of the module.
+/// + `options`: Options for adding the module.
+fn declaration(mod_name: &str, options: &SuperOptions) -> String {
+    let ident = name::ident(mod_name);
+    let mut attributes = Vec::new();
+    if let Some(feature) = &options.feature {
+        attributes.push(format!("#[cfg(feature = {feature:?})]"));
+    }
+
+    attributes.extend(options.attributes.iter().cloned());
+    options.format.render(&options.visibility().keyword(), &ident, &attributes, options.comment.as_deref())
+}
+
+/// Formats the declaration of a module for a specific super file, followed by its re-export.
+/// If no re-export is requested, the super's glob re-export convention is followed if it has one,
+/// declaring the module like its siblings and re-exporting it like them.
+///
+/// # Arguments
+/// + `mod_name`: Name of the module.
+/// + `content`: Content of the super file.
+/// + `options`: Options for adding the module.
+fn super_declaration(mod_name: &str, content: &str, options: &SuperOptions) -> Result<String> {
+    if let Some(reexport) = &options.reexport {
+        return Ok(format!("{}\n{}", declaration(mod_name, options), reexport.render(mod_name)));
+    }
+
+    Ok(match glob_reexport_convention(content) {
+        Some((vis, reexport_vis)) => {
+            let options = SuperOptions { vis: options.vis.clone().or(Some(vis)), ..options.clone() };
+            format!("{}\n{} use {mod_name}::*;", declaration(mod_name, &options), reexport_vis.keyword())
+        },
+
+        None => declaration(mod_name, options),
+    })
+}
+
+/// Detects if every module declared in a file is glob re-exported.
+/// e.g. `mod a; pub use a::*;`.
+/// Test modules, i.e. `tests`, modules ending with `_test`, and `cfg(test)` modules, are ignored.
+///
+/// # Arguments
+/// + `content`: Content of the file.
+///
+/// # Returns
+/// The visibility of the modules' declarations and of their re-exports,
+/// or `None` if the file does not parse or does not follow the convention.
+/// Declarations of differing visibilities take the first's.
+fn glob_reexport_convention(content: &str) -> Option<(Visibility, Visibility)> {
+    let file = syn::parse_file(content).ok()?;
+    let modules: Vec<&syn::ItemMod> = file.items
+        .iter()
+        .filter(|item| is_preamble_item(item))
+        .filter_map(|item| match item {
+            syn::Item::Mod(module) => Some(module),
+            _ => None,
+        })
+        .collect();
+
+    let reexports: Vec<(String, Visibility)> = file.items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Use(item) if !matches!(item.vis, syn::Visibility::Inherited) => {
+                glob_reexported(&item.tree).map(|name| (name, Visibility::from(&item.vis)))
+            },
+
+            _ => None,
+        })
+        .collect();
+
+    let first = modules.first()?;
+    let all_reexported = modules
+        .iter()
+        .all(|module| reexports.iter().any(|(name, _)| module.ident == name));
+
+    if !all_reexported {
+        return None;
+    }
+
+    reexports.into_iter().next().map(|(_, reexport_vis)| (Visibility::from(&first.vis), reexport_vis))
+}
+
+/// Gets the module a `use` tree glob imports, if it is of the form `module::*` or `self::module::*`.
+fn glob_reexported(tree: &syn::UseTree) -> Option<String> {
+    let syn::UseTree::Path(path) = tree else {
+        return None;
+    };
+
+    match &*path.tree {
+        syn::UseTree::Glob(_) => Some(path.ident.to_string()),
+        tree if path.ident == "self" => glob_reexported(tree),
+        _ => None,
+    }
+}
+
+/// Inserts the given module declaration in the given file.
 ///
 /// # Arguments
-/// + `mod_name`: Name of the module to insert.
+/// + `mod_str`: Module declaration to insert.
 /// + `insert`: Line at which to insert the module, or None to append at end.
 /// + `path`: Path to the file in which to add the module.
-/// + `public`: Whether to make the module public.
-fn insert_mod_at_line(mod_name: &str, insert: Option<usize>, path: &Path, public: bool) -> Result {
-    // format mod line
-    let mod_str = match public {
-        true => format!("pub mod {mod_name};"),
-        false => format!("mod {mod_name};"),
-    };
-
-    // copy original file content to temp file
-    // inserting new mod line
-    let mut tmp = NamedTempFile::new()?;
-    let file = File::open(path)?;
-    let md = file.metadata()?; // used to check if file size is 0
-                               // if so iteration over lines does not occur
-
-    let lines = io::BufReader::new(file).lines();
-    for (l_num, line) in lines.enumerate() {
-        if let Err(err) = line {
-            return Err(err.into());
-        }     
+fn insert_mod_at_line(mod_str: &str, insert: Option<usize>, path: &Path) -> Result {
+    write_atomic(path, &render_insert(mod_str, insert, &fs::read_to_string(path)?, path)?)?;
+    Ok(())
+}
+
+/// Replaces the content of a file.
+fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    // mv temp file to path, keeping the original's permissions
+    // the temp file is next to the path so the rename stays on one filesystem, and is atomic
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut tmp = NamedTempFile::new_in(dir)?;
+    tmp.write_all(content.as_bytes())?;
+    tmp.as_file().set_permissions(fs::metadata(path)?.permissions())?;
+    tmp.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Renders the content of a file with the given module declaration inserted.
+///
+/// # Arguments
+/// + `mod_str`: Module declaration to insert.
+/// + `insert`: Line at which to insert the module, or None to append at end.
+/// + `original`: Content of the file.
+/// + `path`: Path to the file, for errors.
+///
+/// The file's line endings are kept, and a missing trailing newline stays missing.
+///
+/// # Errors
+/// + If the file parsed before the insertion, but not after it.
+fn render_insert(mod_str: &str, insert: Option<usize>, original: &str, path: &Path) -> Result<String> {
+    // copy original file content inserting new mod line
+    let newline = newline(original);
 
+    let mod_str = mod_str.lines().collect::<Vec<_>>().join(newline);
+    let mut content = String::new();
+    for (l_num, line) in original.lines().enumerate() {
         if insert == Some(l_num) {
             // add mod line
-            writeln!(tmp, "{}", &mod_str)?;
+            content.push_str(&mod_str);
+            content.push_str(newline);
         }
 
         // copy line
-        let line = line.unwrap();
-        writeln!(tmp, "{}", &line)?; 
+        content.push_str(line);
+        content.push_str(newline);
     }
 
-    if insert == None || md.len() == 0 {
+    if insert.is_none() || original.is_empty() {
         // append mod line
-        writeln!(tmp, "{}", &mod_str)?;
+        content.push_str(&mod_str);
+        content.push_str(newline);
     }
 
-    // mv temp file to path
-    fs::rename(tmp.path(), path)?;
-    Ok(())
+    if !original.is_empty() && !original.ends_with('\n') {
+        content.truncate(content.len() - newline.len());
+    }
+
+    // only reject the edit if it breaks a file that parsed
+    if syn::parse_file(original).is_ok() {
+        if let Err(err) = syn::parse_file(&content) {
+            return Err(Error::SuperParseFailed {
+                path: path.to_path_buf(),
+                line: err.span().start().line,
+                message: err.to_string(),
+            });
+        }
+    }
+
+    Ok(content)
+}
+
+/// Line ending used by a file's content, `\r\n` if it uses any, otherwise `\n`.
+fn newline(content: &str) -> &'static str {
+    match content.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+/// Move a module's companion test file into an inline test module.
+///
+/// The test file's contents are wrapped in a `mod tests` block at the bottom of the module,
+/// guarded by the declaration's `cfg`, or `#[cfg(test)]` if it has none.
+/// The `#[path]` declaration pointing to the test file is removed, and the test file is deleted.
+///
+/// # Arguments
+/// + `path`: Path to the module. Should be the file path for a file module,
+///   or the directory for a directory module.
+/// + `options`: Options for modifying the module file. Only `dirty_ok` and `require_clean` are used.
+///
+/// # Returns
+/// Paths of the modified module file and the removed test file.
+///
+/// # Errors
+/// + If the module does not declare a companion test file.
+/// + If the module file may not be modified. See [`check_modifiable`].
+pub fn inline_tests(path: &Path, options: &SuperOptions) -> Result<Vec<PathBuf>> {
+    inline_tests_in(&mut DiskFileSystem, path, options)
+}
+
+/// Move a module's companion test file into an inline test module in a file system. See [`inline_tests`].
+pub fn inline_tests_in(fs: &mut dyn FileSystem, path: &Path, options: &SuperOptions) -> Result<Vec<PathBuf>> {
+    let mod_file = module_file_in(fs, path);
+    check_modifiable(fs, &mod_file, options)?;
+    let content = fs.read_to_string(&mod_file)?;
+    let newline = newline(&content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    // find test module declaration
+    let re_cfg = Regex::new(r"^\s*#\[cfg\((.*)\)\]\s*$")?;
+    let re_path = Regex::new(r#"^\s*#\[path\s*=\s*"([^"]+)"\]\s*$"#)?;
+    let re_mod = Regex::new(r"^\s*mod\s+\w+_test\s*;\s*$")?;
+    let decl = lines.windows(2).enumerate().find_map(|(l_num, w)| match re_mod.is_match(w[1]) {
+        true => re_path.captures(w[0]).map(|caps| (l_num, caps)),
+        false => None,
+    });
+
+    let (decl, caps) = match decl {
+        Some(found) => found,
+        None => return Err(io::Error::new(
+            io::ErrorKind::NotFound, "test module declaration not found"
+        ).into()),
+    };
+
+    let test_path = &caps[1];
+    let test_path = match mod_file.parent() {
+        Some(parent) => parent.join(test_path),
+        None => PathBuf::from(test_path),
+    };
+
+    // include cfg guard in declaration
+    let mut decl_start = decl;
+    let mut cfg = String::from("test");
+    if decl > 0 {
+        if let Some(caps) = re_cfg.captures(lines[decl - 1]) {
+            cfg = caps[1].to_string();
+            decl_start -= 1;
+        }
+    }
+
+    // remove declaration
+    let mut body: Vec<&str> = lines[..decl_start].to_vec();
+    body.extend_from_slice(&lines[decl + 2..]);
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+
+    let test_content = fs.read_to_string(&test_path)?;
+    let mut new_content = String::new();
+    for line in body {
+        new_content.push_str(line);
+        new_content.push_str(newline);
+    }
+
+    if !new_content.is_empty() {
+        new_content.push_str(newline);
+    }
+
+    new_content.push_str(&inline_test_template(&cfg, &test_content, newline));
+    validate("inline test module", &new_content)?;
+
+    fs.write(&mod_file, &new_content)?;
+    fs.remove_file(&test_path)?;
+    Ok(vec![mod_file, test_path])
+}
+
+/// Get the path of a module from the file containing its contents.
+///
+/// # Arguments
+/// + `file`: File of the module. i.e. `a.rs` for a file module or `a/mod.rs` for a directory module.
+///
+/// # Returns
+/// The module's path without extension, or its directory for a directory module.
+fn module_path(file: &Path) -> PathBuf {
+    match file.parent() {
+        Some(dir) if file.file_name().is_some_and(|name| name == "mod.rs") => dir.to_path_buf(),
+        _ => file.with_extension(""),
+    }
+}
+
+/// Get the file containing a module's contents.
+///
+/// # Arguments
+/// + `path`: Path to the module. May be the module's file, with or without extension,
+///   or the directory for a directory module.
+fn module_file(path: &Path) -> PathBuf {
+    module_file_in(&DiskFileSystem, path)
+}
+
+/// Get the file containing a module's contents in a file system. See [`module_file`].
+fn module_file_in(fs: &dyn FileSystem, path: &Path) -> PathBuf {
+    if fs.is_dir(path) {
+        path.join("mod.rs")
+    } else if path.extension().is_none() {
+        path.with_extension("rs")
+    } else {
+        path.to_path_buf()
+    }
 }
 
 /// Template for file module contents.
 ///
 /// # Arguments
 /// + `name`: Name of the module.
-fn file_template_with_test(name: &str) -> String {
-    format!(r#"
-#[cfg(test)]
-#[path = "./{}_test.rs"]
+/// + `test_path`: Path to the test file, relative to the module file's directory.
+/// + `cfg`: Configuration predicate guarding the test module.
+fn file_template_with_test(name: &str, test_path: &str, cfg: &str) -> String {
+    format!(r#"#[cfg({})]
+#[path = "{}"]
 mod {}_test;
-"#, name, name)
+"#, cfg, test_path, name)
+}
+
+/// Render the header of a generated file.
+///
+/// # Arguments
+/// + `content`: Options for the file's contents.
+/// + `vars`: Variables available to templates, or `None` if no templates are used.
+fn header(content: &ContentOptions, vars: Option<&minijinja::Value>) -> Result<String> {
+    let (template, vars) = match (&content.templates.header, vars) {
+        (Some(template), Some(vars)) => (template, vars),
+        _ => return Ok(String::new()),
+    };
+
+    let mut header = render_template(template, vars.clone())?;
+    if !header.is_empty() && !header.ends_with('\n') {
+        header.push('\n');
+    }
+
+    Ok(header)
+}
+
+/// Render the start of a generated file: its header, documentation, and inner attributes.
+///
+/// # Arguments
+/// + `content`: Options for the file's contents. Its documentation is used if set,
+///   otherwise the documentation template.
+/// + `vars`: Variables available to templates, or `None` if no templates are used.
+fn preamble(content: &ContentOptions, vars: Option<&minijinja::Value>) -> Result<String> {
+    let mut preamble = header(content, vars)?;
+    let doc = match (&content.doc, &content.templates.doc, vars) {
+        (Some(doc), _, _) => Some(doc.clone()),
+        (None, Some(template), Some(vars)) => Some(render_template(template, vars.clone())?),
+        _ => None,
+    };
+
+    if let Some(doc) = doc {
+        preamble.push_str(&doc_template(&doc));
+    }
+
+    for attribute in &content.attributes {
+        preamble.push_str(&format!("{}\n", attribute.trim()));
+    }
+
+    Ok(preamble)
+}
+
+/// Template for module documentation.
+///
+/// # Arguments
+/// + `doc`: Documentation text. Each line becomes a `//!` comment.
+fn doc_template(doc: &str) -> String {
+    let mut template = String::new();
+    for line in doc.trim_end().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            template.push_str("//!\n");
+        } else {
+            template.push_str(&format!("//! {line}\n"));
+        }
+    }
+
+    template
+}
+
+/// Template for test file contents.
+///
+/// # Arguments
+/// + `imports`: Additional imports to include after `use super::*;`.
+///   May be given as a path or as a full `use` statement.
+fn test_file_template(imports: &[String]) -> String {
+    let mut template = String::from("use super::*;\n");
+    for import in imports {
+        template.push_str(&format!("use {};\n", import_path(import)));
+    }
+
+    template
+}
+
+/// Get the path of an import given as a path or as a full `use` statement.
+fn import_path(import: &str) -> &str {
+    let import = import.trim();
+    let import = import.strip_prefix("use ").unwrap_or(import);
+    let import = import.strip_suffix(';').unwrap_or(import);
+    import.trim()
+}
+
+/// Variables available to templates. See [`Templates`].
+///
+/// # Arguments
+/// + `fs`: File system the module is in.
+/// + `mod_path`: Path to the module's file.
+/// + `mod_name`: Name of the module.
+fn template_vars(fs: &dyn FileSystem, mod_path: &Path, mod_name: &str) -> Result<minijinja::Value> {
+    let (crate_name, author, rust_version) = match Project::discover_in(fs, mod_path) {
+        Ok(project) => (project.crate_name_in(fs)?, project.author_in(fs)?, project.rust_version_in(fs)?),
+        Err(_) => (None, None, None),
+    };
+
+    let author = author.or_else(|| {
+        let dir = mod_path.parent().filter(|dir| fs.is_dir(dir)).unwrap_or(Path::new("."));
+        fs.vcs().user_name(dir)
+    });
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let date = journal::format_date(now);
+    Ok(minijinja::context! {
+        mod_name,
+        crate_name,
+        year => date[..4].to_string(),
+        date,
+        author => author.unwrap_or_default(),
+        rust_version,
+    })
+}
+
+/// Get the minimum supported Rust version of the project a module is in.
+///
+/// # Returns
+/// `None` if the module is not in a project, or the project declares no `rust-version`.
+fn rust_version(fs: &dyn FileSystem, mod_path: &Path) -> Result<Option<RustVersion>> {
+    let version = match Project::discover_in(fs, mod_path) {
+        Ok(project) => project.rust_version_in(fs)?,
+        Err(_) => None,
+    };
+
+    Ok(version.map(|version| version.parse()).transpose()?)
+}
+
+/// Render a user template.
+///
+/// # Arguments
+/// + `template`: Template source.
+/// + `vars`: Variables available to the template.
+fn render_template(template: &str, vars: minijinja::Value) -> Result<String> {
+    let rust_version = match vars.get_attr("rust_version")?.as_str() {
+        Some(version) => Some(version.parse::<RustVersion>()?),
+        None => None,
+    };
+
+    let mut env = minijinja::Environment::new();
+    env.add_function("rust_supports", move |version: &str| {
+        let version = version.parse::<RustVersion>().map_err(|err| {
+            minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, err.to_string())
+        })?;
+
+        Ok(rust_version.is_none_or(|msrv| msrv.supports(version)))
+    });
+
+    let syntax = minijinja::syntax::SyntaxConfig::builder()
+        .keep_trailing_newline(true)
+        .build()?;
+
+    env.set_syntax(syntax);
+    Ok(env.render_str(template, vars)?)
+}
+
+/// Template for an inline test module.
+/// Lines of the contents are indented, except those continuing multi-line literals,
+/// so the values of string literals are not changed.
+///
+/// # Arguments
+/// + `cfg`: Configuration predicate guarding the test module.
+/// + `content`: Contents of the test module.
+/// + `newline`: Line ending to use.
+fn inline_test_template(cfg: &str, content: &str, newline: &str) -> String {
+    let continued = literal_continuation_lines(content);
+    let mut template = format!("#[cfg({cfg})]{newline}mod tests {{{newline}");
+    for (index, line) in content.lines().enumerate() {
+        if !line.trim().is_empty() && !continued.contains(&(index + 1)) {
+            template.push_str("    ");
+        }
+
+        template.push_str(line);
+        template.push_str(newline);
+    }
+
+    template.push('}');
+    template.push_str(newline);
+    template
+}
+
+/// Finds the lines that start inside of a multi-line literal, e.g. a raw string.
+///
+/// # Returns
+/// 1-based line numbers, or none if the content can not be tokenized.
+fn literal_continuation_lines(content: &str) -> HashSet<usize> {
+    fn walk(tokens: proc_macro2::TokenStream, lines: &mut HashSet<usize>) {
+        for token in tokens {
+            match token {
+                proc_macro2::TokenTree::Group(group) => walk(group.stream(), lines),
+                proc_macro2::TokenTree::Literal(literal) => {
+                    let span = literal.span();
+                    lines.extend(span.start().line + 1..=span.end().line);
+                },
+
+                _ => {},
+            }
+        }
+    }
+
+    let mut lines = HashSet::new();
+    if let Ok(tokens) = content.parse() {
+        walk(tokens, &mut lines);
+    }
+
+    lines
 }
 
 
@@ -1,12 +1,1144 @@
 use super::*;
+use crate::creator::MemoryFileSystem;
+use crate::fixtures::{create, project};
+use crate::git::MemoryVcs;
 
 #[test]
 fn creating_module_should_work() {
-       
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), None).unwrap();
+
+    assert!(dir.path().join("my_mod.rs").exists());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("my_mod_test.rs")).unwrap(),
+        "use super::*;\n"
+    );
+}
+
+#[test]
+fn create_options_should_create_module() {
+    let (_dir, root) = project(&[]);
+
+    let created = CreateOptions::new(root.join("src/my_mod"))
+        .dir(true)
+        .public(false)
+        .with_test(false)
+        .create()
+        .unwrap();
+
+    assert_eq!(created.module_path, root.join("src/my_mod"));
+    assert_eq!(created.test_path, None);
+    assert_eq!(created.super_modified, Some(root.join("src/lib.rs")));
+    assert!(root.join("src/my_mod/mod.rs").exists());
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "mod my_mod;\n");
+}
+
+#[test]
+fn create_options_should_declare_visibility() {
+    let (_dir, root) = project(&[]);
+
+    for (name, vis) in [("a", Visibility::Crate), ("b", Visibility::In(String::from("crate::a")))] {
+        CreateOptions::new(root.join("src").join(name))
+            .vis(vis)
+            .with_test(false)
+            .create()
+            .unwrap();
+    }
+
+    assert_eq!(
+        fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub(crate) mod a;\npub(in crate::a) mod b;\n"
+    );
+}
+
+#[test]
+fn create_options_plan_should_not_modify_anything() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let plan = CreateOptions::new(&path).add_to_super(false).plan().unwrap();
+
+    assert_eq!(plan.test_path, Some(dir.path().canonicalize().unwrap().join("my_mod_test.rs")));
+    assert_eq!(plan.super_file, None);
+    assert!(!dir.path().join("my_mod.rs").exists());
+}
+
+#[test]
+fn preview_should_show_changes_without_modifying_anything() {
+    let (_dir, root) = project(&[("src/lib.rs", "use std::fs;\n")]);
+
+    let preview = CreateOptions::new(root.join("src/my_mod")).preview().unwrap();
+    assert_eq!(preview.created.len(), 2);
+    assert_eq!(preview.created[1], (root.join("src/my_mod_test.rs"), String::from("use super::*;\n")));
+    assert_eq!(
+        preview.modified,
+        vec![(root.join("src/lib.rs"), String::from("use std::fs;\n"), String::from("use std::fs;\npub mod my_mod;\n"))]
+    );
+
+    let diff = preview.diff(&root);
+    assert!(diff.contains("--- /dev/null\n+++ b/src/my_mod.rs\n"));
+    assert!(diff.contains("--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,2 @@\n use std::fs;\n+pub mod my_mod;\n"));
+    assert!(!root.join("src/my_mod.rs").exists());
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "use std::fs;\n");
+}
+
+#[test]
+fn preview_should_include_missing_parents() {
+    let (_dir, root) = project(&[]);
+
+    let options = CreateOptions::new(root.join("src/a/b/c")).with_test(false);
+    assert!(matches!(options.preview(), Err(Error::ParentNotFound(_))));
+
+    let preview = options.parents(ContentOptions::default()).preview().unwrap();
+    let created: Vec<&Path> = preview.created.iter().map(|(path, _)| path.as_path()).collect();
+    assert_eq!(created, [root.join("src/a/mod.rs"), root.join("src/a/b/mod.rs"), root.join("src/a/b/c.rs")]);
+    assert_eq!(preview.modified, vec![(root.join("src/lib.rs"), String::new(), String::from("pub mod a;\n"))]);
+    assert!(!root.join("src/a").exists());
+}
+
+#[test]
+fn failed_creation_should_be_rolled_back() {
+    let (_dir, root) = project(&[]);
+
+    // the test file can not be created inside of a file
+    fs::write(root.join("tests"), "").unwrap();
+    let test = TestOptions {
+        location: TestLocation::Mirrored(PathBuf::from("tests")),
+        ..TestOptions::default()
+    };
+
+    let res = CreateOptions::new(root.join("src/my_mod")).dir(true).test(test).create();
+    assert!(res.is_err());
+    assert!(!root.join("src/my_mod").exists());
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "");
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_main_should_create_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    main(&path, false, true, false, false, true).unwrap();
+    assert!(dir.path().join("my_mod.rs").exists());
+    assert!(dir.path().join("my_mod_test.rs").exists());
 }
 
 #[test]
 #[should_panic(expected = "")]
 fn creating_module_that_already_exists_should_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), None).unwrap();
+    create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), None).unwrap();
+}
+
+#[test]
+fn inline_tests_should_fold_test_file_into_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), None).unwrap();
+
+    let test_file = dir.path().join("my_mod_test.rs");
+    fs::write(&test_file, "use super::*;\n\n#[test]\nfn it_works() {}\n").unwrap();
+    inline_tests(&path, &SuperOptions::default()).unwrap();
+
+    let content = fs::read_to_string(dir.path().join("my_mod.rs")).unwrap();
+    assert_eq!(
+        content,
+        "#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn it_works() {}\n}\n"
+    );
+    assert!(!test_file.exists());
+}
+
+#[test]
+fn inline_tests_should_guard_tests_and_keep_literals_and_line_endings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    fs::write(
+        dir.path().join("my_mod.rs"),
+        "fn f() {}\r\n\r\n#[path = \"my_mod_test.rs\"]\r\nmod my_mod_test;\r\n",
+    ).unwrap();
+    fs::write(dir.path().join("my_mod_test.rs"), "#[test]\nfn a() {\n    let s = r\"x\n  y\";\n}\n").unwrap();
+
+    inline_tests(&path, &SuperOptions::default()).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("my_mod.rs")).unwrap(),
+        "fn f() {}\r\n\r\n#[cfg(test)]\r\nmod tests {\r\n    #[test]\r\n    fn a() {\r\n        let s = r\"x\r\n  y\";\r\n    }\r\n}\r\n"
+    );
+}
+
+#[test]
+fn inline_tests_should_not_modify_dirty_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), None).unwrap();
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=mkmod", "-c", "user.email=mkmod@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+    };
+
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    let module = dir.path().join("my_mod.rs");
+    let content = format!("fn wip() {{}}\n{}", fs::read_to_string(&module).unwrap());
+    fs::write(&module, &content).unwrap();
+
+    let res = inline_tests(&path, &SuperOptions::default());
+    assert!(matches!(res, Err(Error::Dirty(_))));
+    assert_eq!(fs::read_to_string(&module).unwrap(), content);
+
+    inline_tests(&path, &SuperOptions { dirty_ok: true, ..SuperOptions::default() }).unwrap();
+    assert!(!dir.path().join("my_mod_test.rs").exists());
+}
+
+#[test]
+fn inline_tests_without_test_declaration_should_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    create(&path, false, &ContentOptions::default(), None, None).unwrap();
+
+    let res = inline_tests(&path, &SuperOptions::default());
+    assert!(matches!(res, Err(result::Error::Io(err)) if err.kind() == io::ErrorKind::NotFound));
+}
+
+#[test]
+fn custom_test_cfg_should_guard_test_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let test = TestOptions {
+        cfg: String::from(r#"all(test, feature = "expensive-tests")"#),
+        ..TestOptions::default()
+    };
+
+    create(&path, false, &ContentOptions::default(), Some(&test), None).unwrap();
+    let content = fs::read_to_string(dir.path().join("my_mod.rs")).unwrap();
+    assert!(content.contains("#[cfg(all(test, feature = \"expensive-tests\"))]\n#[path"));
+
+    inline_tests(&path, &SuperOptions::default()).unwrap();
+    let content = fs::read_to_string(dir.path().join("my_mod.rs")).unwrap();
+    assert!(content.starts_with("#[cfg(all(test, feature = \"expensive-tests\"))]\nmod tests {"));
+}
+
+#[test]
+fn test_imports_should_be_added_to_test_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let test = TestOptions {
+        imports: vec![
+            String::from("pretty_assertions::assert_eq"),
+            String::from("use std::fs;"),
+        ],
+        ..TestOptions::default()
+    };
+
+    create(&path, false, &ContentOptions::default(), Some(&test), None).unwrap();
+    let content = fs::read_to_string(dir.path().join("my_mod_test.rs")).unwrap();
+    assert_eq!(content, "use super::*;\nuse pretty_assertions::assert_eq;\nuse std::fs;\n");
+}
+
+#[test]
+fn mirrored_test_should_be_created_in_test_tree() {
+    let (_dir, root) = project(&[]);
+    fs::create_dir(root.join("src/a")).unwrap();
+
+    let path = root.join("src/a/my_mod");
+    let test = TestOptions {
+        location: TestLocation::Mirrored(PathBuf::from("src/tests")),
+        ..TestOptions::default()
+    };
+
+    create(&path, false, &ContentOptions::default(), Some(&test), None).unwrap();
+    assert!(root.join("src/tests/a/my_mod_test.rs").exists());
+
+    let content = fs::read_to_string(root.join("src/a/my_mod.rs")).unwrap();
+    assert!(content.contains("#[path = \"../tests/a/my_mod_test.rs\"]"));
+
+    inline_tests(&path, &SuperOptions::default()).unwrap();
+    assert!(!root.join("src/tests/a/my_mod_test.rs").exists());
+}
+
+#[test]
+fn adding_to_super_should_insert_declaration() {
+    let (_dir, root) = project(&[("src/lib.rs", "//! Crate.\nuse std::fs;\n\nfn f() {}\n")]);
+
+    let path = root.join("src/my_mod");
+    create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+
+    let content = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    assert_eq!(content, "//! Crate.\nuse std::fs;\npub mod my_mod;\n\nfn f() {}\n");
+}
+
+#[test]
+fn created_module_should_report_locations() {
+    let (_dir, root) = project(&[("src/lib.rs", "use std::fs;\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        attributes: vec![String::from("#[cfg(unix)]")],
+        ..SuperOptions::default()
+    };
+
+    let path = root.join("src/my_mod");
+    let created = create(&path, false, &ContentOptions::default(), Some(&TestOptions::default()), Some(&options)).unwrap();
+    assert_eq!(
+        created.declaration_location,
+        Some(Location { path: lib.clone(), line: 3, column: 5 })
+    );
+    assert_eq!(
+        created.test_location,
+        Some(Location { path: root.join("src/my_mod_test.rs"), line: 2, column: 1 })
+    );
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "use std::fs;\n#[cfg(unix)]\npub mod my_mod;\n");
+}
+
+#[test]
+fn adding_to_dirty_super_should_error_before_creating_module() {
+    let (_dir, root) = project(&[]);
+    let lib = root.join("src/lib.rs");
+
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=mkmod", "-c", "user.email=mkmod@example.com"])
+            .args(args)
+            .current_dir(&root)
+            .output()
+            .unwrap();
+    };
+
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    fs::write(&lib, "fn wip() {}\n").unwrap();
+
+    let path = root.join("src/my_mod");
+    let res = create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default()));
+    assert!(matches!(res, Err(Error::Dirty(_))));
+    assert!(!root.join("src/my_mod.rs").exists());
+
+    let options = SuperOptions {
+        dirty_ok: true,
+        ..SuperOptions::default()
+    };
+
+    create(&path, false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "pub mod my_mod;\nfn wip() {}\n");
+}
+
+#[test]
+fn explicit_insertion_line_should_be_used() {
+    let (_dir, root) = project(&[("src/lib.rs", "use std::fs;\n\nfn f() {}\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        at: Some(3),
+        ..SuperOptions::default()
+    };
+
+    create(&root.join("src/a"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "use std::fs;\n\npub mod a;\nfn f() {}\n");
+
+    let options = SuperOptions {
+        at: Some(5),
+        ..SuperOptions::default()
+    };
+
+    create(&root.join("src/b"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "use std::fs;\n\npub mod a;\nfn f() {}\npub mod b;\n"
+    );
+
+    let options = SuperOptions {
+        at: Some(7),
+        ..SuperOptions::default()
+    };
+
+    assert!(create(&root.join("src/c"), false, &ContentOptions::default(), None, Some(&options)).is_err());
+    assert!(!root.join("src/c.rs").exists());
+}
+
+#[test]
+fn declaration_comment_should_follow_declaration() {
+    let options = SuperOptions {
+        comment: Some(String::from("owned by infra team")),
+        ..SuperOptions::default()
+    };
+
+    assert_eq!(declaration("telemetry", &options), "pub mod telemetry; // owned by infra team");
+}
+
+#[test]
+fn plan_should_not_modify_anything() {
+    let (_dir, root) = project(&[]);
+
+    let plan = plan(
+        &root.join("src/my_mod"),
+        true,
+        Some(&TestOptions::default()),
+        Some(&SuperOptions::default()),
+    ).unwrap();
+
+    assert_eq!(plan.name, "my_mod");
+    assert_eq!(plan.module_path, root.join("src/my_mod"));
+    assert_eq!(plan.test_path, Some(root.join("src/my_mod/mod_test.rs")));
+    assert_eq!(plan.super_file, Some(root.join("src/lib.rs")));
+    assert_eq!(plan.declaration.as_deref(), Some("pub mod my_mod;"));
+    assert_eq!(
+        plan.declaration_location,
+        Some(Location { path: root.join("src/lib.rs"), line: 1, column: 5 })
+    );
+    assert!(!root.join("src/my_mod").exists());
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "");
+}
+
+#[test]
+fn doc_should_be_written_as_inner_doc_comments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let content = ContentOptions {
+        doc: Some(String::from("# My module\n\nDoes things.  \n\n")),
+        ..ContentOptions::default()
+    };
+
+    create(&path, false, &content, Some(&TestOptions::default()), None).unwrap();
+    let content = fs::read_to_string(dir.path().join("my_mod.rs")).unwrap();
+    assert!(content.starts_with("//! # My module\n//!\n//! Does things.\n\n#[cfg(test)]"));
+}
+
+#[test]
+fn module_attributes_should_follow_doc() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let content = ContentOptions {
+        doc: Some(String::from("My module.")),
+        attributes: vec![
+            String::from("#![deny(missing_docs)]"),
+            String::from("#![allow(clippy::module_name_repetitions)]"),
+        ],
+        ..ContentOptions::default()
+    };
+
+    create(&path, false, &content, None, None).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("my_mod.rs")).unwrap(),
+        "//! My module.\n#![deny(missing_docs)]\n#![allow(clippy::module_name_repetitions)]\n"
+    );
+}
+
+#[test]
+fn templates_should_render_module_and_test_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"my-crate\"\n").unwrap();
+    let content = ContentOptions {
+        templates: Templates {
+            module: Some(String::from("// {{ mod_name }} in {{ crate_name }}\n")),
+            dir_module: Some(String::from("// directory {{ mod_name }}\n")),
+            test: Some(String::from("{% for import in imports %}use {{ import }};\n{% endfor %}")),
+            ..Templates::default()
+        },
+        ..ContentOptions::default()
+    };
+
+    let test = TestOptions {
+        imports: vec![String::from("use std::fs;")],
+        ..TestOptions::default()
+    };
+
+    create(&dir.path().join("a"), false, &content, Some(&test), None).unwrap();
+    create(&dir.path().join("b"), true, &content, None, None).unwrap();
+
+    let module = fs::read_to_string(dir.path().join("a.rs")).unwrap();
+    assert!(module.starts_with("// a in my_crate\n\n#[cfg(test)]\n#[path = \"./a_test.rs\"]"));
+    assert_eq!(fs::read_to_string(dir.path().join("a_test.rs")).unwrap(), "use std::fs;\n");
+    assert_eq!(fs::read_to_string(dir.path().join("b/mod.rs")).unwrap(), "// directory b\n");
+}
+
+#[test]
+fn templates_and_skeletons_should_follow_rust_version() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\nrust-version = \"1.36\"\n").unwrap();
+    let template = "{% if rust_supports(\"1.65\") %}// let-else{% else %}// match{% endif %} on {{ rust_version }}\n";
+    let content = ContentOptions {
+        templates: Templates { module: Some(String::from(template)), ..Templates::default() },
+        ..ContentOptions::default()
+    };
+
+    create(&dir.path().join("a"), false, &content, None, None).unwrap();
+    assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), "// match on 1.36\n");
+
+    let content = ContentOptions { kind: Some(ModuleKind::Enum), ..ContentOptions::default() };
+    create(&dir.path().join("color"), false, &content, None, None).unwrap();
+    assert!(fs::read_to_string(dir.path().join("color.rs")).unwrap().contains("Color::First =>"));
+
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    create(&dir.path().join("b"), false, &content, None, None).unwrap();
+    assert!(fs::read_to_string(dir.path().join("b.rs")).unwrap().contains("Self::First =>"));
+}
+
+#[test]
+fn rendered_module_should_match_created_module() {
+    let (_dir, root) = project(&[]);
+    let content = ContentOptions {
+        templates: Templates { module: Some(String::from("pub fn {{ mod_name }}() {}\n")), ..Templates::default() },
+        ..ContentOptions::default()
+    };
+
+    let test = TestOptions::default();
+    create(&root.join("src/a"), false, &content, Some(&test), None).unwrap();
+    let created = fs::read_to_string(root.join("src/a.rs")).unwrap();
+    assert_eq!(render_module(&root.join("src/a.rs"), &content, Some(&test)).unwrap(), created);
+
+    create(&root.join("src/b"), true, &content, None, None).unwrap();
+    let created = fs::read_to_string(root.join("src/b/mod.rs")).unwrap();
+    assert_eq!(render_module(&root.join("src/b/mod.rs"), &content, None).unwrap(), created);
+}
+
+#[test]
+fn test_location_should_follow_declared_test_modules() {
+    let path = Path::new("src/a.rs");
+    assert_eq!(test_location(path, "#[cfg(test)]\n#[path = \"./a_test.rs\"]\nmod a_test;\n"), Some(TestLocation::Sibling));
+    assert_eq!(test_location(path, "#[cfg(test)]\nmod tests {\n    use super::*;\n}\n"), Some(TestLocation::Inline));
+    assert_eq!(test_location(path, "mod tests;\nmod b_test;\n"), None);
+    assert_eq!(test_location(path, "mod a_test"), None);
+}
+
+#[test]
+fn invalid_template_output_should_not_be_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let content = ContentOptions {
+        templates: Templates {
+            module: Some(String::from("fn {{ mod_name }}(")),
+            ..Templates::default()
+        },
+        ..ContentOptions::default()
+    };
+
+    let res = create(&dir.path().join("a"), false, &content, None, None);
+    assert!(matches!(res, Err(Error::Parse { .. })));
+    assert!(!dir.path().join("a.rs").exists());
+}
+
+#[test]
+fn src_root_should_be_used_for_super_and_mirrored_tests() {
+    let (_dir, root) = project(&[("rust/src/lib.rs", "")]);
+
+    let src_root = Some(PathBuf::from("rust/src"));
+    let test = TestOptions {
+        location: TestLocation::Mirrored(PathBuf::from("rust/tests")),
+        src_root: src_root.clone(),
+        ..TestOptions::default()
+    };
+
+    let options = SuperOptions {
+        src_root: src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let path = root.join("rust/src/my_mod");
+    create(&path, false, &ContentOptions::default(), Some(&test), Some(&options)).unwrap();
+    assert_eq!(fs::read_to_string(root.join("rust/src/lib.rs")).unwrap(), "pub mod my_mod;\n");
+    assert!(root.join("rust/tests/my_mod_test.rs").exists());
+
+    fs::create_dir_all(root.join("other")).unwrap();
+    let path = root.join("other/my_mod");
+    let res = create(&path, false, &ContentOptions::default(), None, Some(&options));
+    assert!(matches!(res, Err(Error::NotInSource { .. })));
+    assert!(!root.join("other/my_mod.rs").exists());
+}
+
+#[test]
+fn modules_of_multi_file_binary_should_be_added_to_its_main() {
+    let (_dir, root) = project(&[("src/bin/server/main.rs", "fn create() {}\n")]);
+
+    let path = root.join("src/bin/server/routes");
+    create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("src/bin/server/main.rs")).unwrap(),
+        "pub mod routes;\nfn create() {}\n"
+    );
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "");
+}
+
+#[test]
+fn modules_of_multi_file_binary_should_respect_src_root() {
+    let (_dir, root) = project(&[("rust/src/bin/server/main.rs", "")]);
+
+    let options = SuperOptions {
+        src_root: Some(PathBuf::from("rust/src")),
+        ..SuperOptions::default()
+    };
+
+    let path = root.join("rust/src/bin/server/routes");
+    create(&path, false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("rust/src/bin/server/main.rs")).unwrap(),
+        "pub mod routes;\n"
+    );
+}
+
+#[test]
+fn glob_reexport_convention_should_be_followed() {
+    let (_dir, root) = project(&[("src/lib.rs", "mod a;\npub use a::*;\nmod b;\npub use b::*;\n\n#[cfg(test)]\nmod lib_test;\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let path = root.join("src/c");
+    create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "mod a;\npub use a::*;\nmod b;\npub use b::*;\nmod c;\npub use c::*;\n\n#[cfg(test)]\nmod lib_test;\n"
+    );
+}
+
+#[test]
+fn requested_reexport_should_follow_declaration() {
+    let (_dir, root) = project(&[("src/lib.rs", "mod a;\npub use a::*;\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        vis: Some(Visibility::Private),
+        reexport: Some(Reexport::Item(String::from("{Client, Config}"))),
+        ..SuperOptions::default()
+    };
+
+    let created = create(&root.join("src/b"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(created.declaration_location.unwrap().line, 3);
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "mod a;\npub use a::*;\nmod b;\npub use b::{Client, Config};\n"
+    );
+}
+
+#[test]
+fn glob_reexport_convention_should_require_every_module_reexported() {
+    assert_eq!(
+        glob_reexport_convention("mod a;\npub(crate) use a::*;\n"),
+        Some((Visibility::Private, Visibility::Crate))
+    );
+    assert_eq!(
+        glob_reexport_convention("pub(super) mod a;\npub use self::a::*;\n"),
+        Some((Visibility::Super, Visibility::Pub))
+    );
+    assert_eq!(glob_reexport_convention("mod a;\npub use a::*;\nmod b;\n"), None);
+    assert_eq!(glob_reexport_convention("pub use std::fs::*;\n"), None);
+}
+
+#[test]
+fn glob_reexport_convention_should_ignore_comments_and_strings() {
+    let content = "mod a;\npub use a::*;\n// mod b;\nconst C: &str = \"mod c;\";\n";
+    assert_eq!(glob_reexport_convention(content), Some((Visibility::Private, Visibility::Pub)));
+}
+
+#[test]
+fn requested_visibility_should_override_glob_reexport_convention() {
+    let (_dir, root) = project(&[("src/lib.rs", "mod a;\npub use a::*;\nmod b;\npub use b::*;\n")]);
+    let options = SuperOptions { vis: Some(Visibility::Crate), ..SuperOptions::default() };
+    create(&root.join("src/d"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "mod a;\npub use a::*;\nmod b;\npub use b::*;\npub(crate) mod d;\npub use d::*;\n"
+    );
+}
+
+#[test]
+fn test_support_should_create_cfg_gated_tree() {
+    let (_dir, root) = project(&[("src/lib.rs", "pub mod a;\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let tree = root.join("src/test_support");
+    let created = test_support(&tree.join("fixtures"), &ContentOptions::default(), &SuperOptions::default()).unwrap();
+    assert_eq!(created.len(), 2);
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "pub mod a;\n#[cfg(test)]\nmod test_support;\n");
+    assert_eq!(fs::read_to_string(tree.join("mod.rs")).unwrap(), "pub mod fixtures;\n");
+    assert!(tree.join("fixtures.rs").exists());
+    assert!(!tree.join("fixtures_test.rs").exists());
+
+    let created = test_support(&tree.join("mocks"), &ContentOptions::default(), &SuperOptions::default()).unwrap();
+    assert_eq!(created.len(), 1);
+    assert_eq!(fs::read_to_string(tree.join("mod.rs")).unwrap(), "pub mod fixtures;\npub mod mocks;\n");
+}
+
+#[test]
+fn unparsable_module_should_not_be_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let content = ContentOptions {
+        attributes: vec![String::from("#![deny(missing_docs)")],
+        ..ContentOptions::default()
+    };
+
+    let res = create(&path, false, &content, Some(&TestOptions::default()), None);
+    assert!(matches!(res, Err(Error::Parse { template, line: 1, .. }) if template == "module"));
+    assert!(!dir.path().join("my_mod.rs").exists());
+    assert!(!dir.path().join("my_mod_test.rs").exists());
+}
+
+#[test]
+fn unparsable_declaration_should_error_before_creating_module() {
+    let (_dir, root) = project(&[("src/lib.rs", "use std::fs;\n")]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        attributes: vec![String::from("#[cfg(unix")],
+        ..SuperOptions::default()
+    };
+
+    let path = root.join("src/my_mod");
+    let res = create(&path, false, &ContentOptions::default(), None, Some(&options));
+    assert!(matches!(res, Err(Error::SuperParseFailed { path, line: 2, .. }) if path == lib));
+    assert!(!root.join("src/my_mod.rs").exists());
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "use std::fs;\n");
+
+    // files that did not parse before are not rejected
+    fs::write(&lib, "fn wip( {\n").unwrap();
+    create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+}
+
+#[test]
+fn create_parents_should_create_ancestors_and_keep_named_files() {
+    let (_dir, root) = project(&[]);
+    let src = root.join("src");
+    create(&src.join("a"), false, &ContentOptions::default(), Some(&TestOptions::default()), Some(&SuperOptions::default())).unwrap();
+
+    let path = src.join("a/b/c");
+    create_parents(&path, &ContentOptions::default(), Some(&SuperOptions::default())).unwrap();
+    create(&path, false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+
+    assert!(!src.join("a/mod.rs").exists());
+    assert_eq!(
+        fs::read_to_string(src.join("a.rs")).unwrap(),
+        "pub mod b;\n#[cfg(test)]\n#[path = \"./a_test.rs\"]\nmod a_test;\n"
+    );
+    assert_eq!(fs::read_to_string(src.join("a/b/mod.rs")).unwrap(), "pub mod c;\n");
+    assert!(src.join("a/b/c.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\n");
+}
+
+#[test]
+fn named_file_parent_should_be_super() {
+    let (_dir, root) = project(&[("src/lib.rs", "pub mod foo;\n"), ("src/foo.rs", "use std::fs;\n")]);
+    let src = root.join("src");
+    fs::create_dir_all(root.join("src/foo")).unwrap();
+
+    create(&src.join("foo/bar"), false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    assert_eq!(fs::read_to_string(src.join("foo.rs")).unwrap(), "use std::fs;\npub mod bar;\n");
+    assert!(!src.join("foo/mod.rs").exists());
+}
+
+#[test]
+fn super_path_should_use_nearest_package() {
+    let (_dir, root) = project(&[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/a\"]\n"),
+        ("crates/a/Cargo.toml", "[package]\nname = \"a\"\n"),
+        ("crates/a/src/lib.rs", ""),
+        ("crates/a/src/bin/tool/main.rs", ""),
+    ]);
+    let member = root.join("crates/a");
+
+    assert_eq!(super_path(&DiskFileSystem, &member.join("src/b"), false, None, None).unwrap(), member.join("src/lib.rs"));
+    assert_eq!(
+        super_path(&DiskFileSystem, &member.join("src/bin/tool/b"), false, None, None).unwrap(),
+        member.join("src/bin/tool/main.rs")
+    );
+
+    // virtual manifest is not a crate root
+    assert!(super_path(&DiskFileSystem, &root.join("src/b"), false, None, None).is_err());
+}
+
+#[test]
+fn super_path_should_use_manifest_targets() {
+    let (_dir, root) = project(&[
+        ("Cargo.toml", "[package]\nname = \"a\"\n\n[lib]\npath = \"rust/core.rs\"\n"),
+        ("rust/core.rs", ""),
+        ("src/bin/one.rs", ""),
+        ("src/bin/two.rs", ""),
+    ]);
+
+    assert_eq!(super_path(&DiskFileSystem, &root.join("rust/b"), false, None, None).unwrap(), root.join("rust/core.rs"));
+    assert!(super_path(&DiskFileSystem, &root.join("src/bin/b"), false, None, None).is_err());
+    assert_eq!(
+        super_path(&DiskFileSystem, &root.join("src/bin/b"), false, None, Some("two")).unwrap(),
+        root.join("src/bin/two.rs")
+    );
+
+    assert!(super_path(&DiskFileSystem, &root.join("src/bin/b"), false, None, Some("a")).is_err());
+    assert!(super_path(&DiskFileSystem, &root.join("src/bin/b"), false, None, Some("three")).is_err());
+}
+
+#[test]
+fn super_path_should_follow_path_attributes() {
+    let (_dir, root) = project(&[
+        ("Cargo.toml", "[package]\nname = \"a\"\n"),
+        ("src/lib.rs", "#[path = \"some/dir/parent.rs\"]\nmod parent;\n"),
+        ("src/some/dir/parent.rs", ""),
+    ]);
+
+    let child = root.join("src/some/dir/child");
+    assert_eq!(super_path(&DiskFileSystem, &child, false, None, None).unwrap(), root.join("src/some/dir/parent.rs"));
+    assert!(super_path(&DiskFileSystem, &root.join("src/other/child"), false, None, None).is_err());
+
+    let options = SuperOptions { super_file: Some(root.join("src/lib.rs")), ..SuperOptions::default() };
+    assert_eq!(resolve_super(&DiskFileSystem, &child, &options).unwrap(), root.join("src/lib.rs"));
+
+    let options = SuperOptions { super_file: Some(root.join("src/missing.rs")), ..SuperOptions::default() };
+    assert!(matches!(resolve_super(&DiskFileSystem, &child, &options), Err(Error::ParentNotFound(_))));
+}
+
+#[test]
+fn sorted_insert_should_keep_declarations_in_order() {
+    let insert_line = |content: &str, mod_name: &str| sorted_insert_line(content, mod_name).unwrap();
+
+    let content = "use std::fs;\npub mod alpha;\n/// Docs.\n#[cfg(unix)]\nmod gamma;\n\nfn f() {}\n";
+    assert_eq!(insert_line(content, "beta"), Some(2));
+    assert_eq!(insert_line(content, "aardvark"), Some(1));
+    assert_eq!(insert_line(content, "r#zeta"), Some(5));
+    assert_eq!(insert_line("mod a;\nmod c;\n", "d"), None);
+    assert_eq!(insert_line("mod b;\n#[cfg(test)]\nmod a_test;\n", "c"), Some(1));
+
+    // files without declarations fall back to the preamble
+    assert_eq!(insert_line("use std::fs;\n\nfn f() {}\n", "a"), Some(1));
+}
+
+#[test]
+fn preamble_should_be_found_by_parsing() {
+    let insert_line = |content: &str| preamble_insert_line(content).unwrap();
+
+    assert_eq!(insert_line("/* header\nmod fake;\n*/\n#![allow(dead_code)]\nuse std::fs;\n\nfn f() {}\n"), Some(5));
+    assert_eq!(insert_line("//! Crate.\n#![deny(missing_docs)]\n\n/// Docs.\nfn f() {}\n"), Some(3));
+    assert_eq!(insert_line("mod a;\n#[cfg(test)]\nmod tests {\n    use super::*;\n}\n"), Some(1));
+    assert_eq!(insert_line("const S: &str = r#\"\nmod fake;\n\"#;\n"), Some(0));
+    assert_eq!(insert_line("use std::fs;\nmod a;\n"), None);
+    assert_eq!(insert_line("//! Crate.\n"), None);
+    assert_eq!(insert_line(""), Some(0));
+
+    // files that do not parse fall back to matching lines
+    assert_eq!(insert_line("use std::fs;\nfn wip( {\n"), Some(1));
+}
+
+#[test]
+fn missing_parent_should_error_with_its_path() {
+    let (_dir, root) = project(&[]);
+    fs::create_dir_all(root.join("src/a")).unwrap();
+
+    let res = create(&root.join("src/a/b"), false, &ContentOptions::default(), None, Some(&SuperOptions::default()));
+    let err = res.unwrap_err();
+    assert!(matches!(&err, Error::ParentNotFound(path) if *path == root.join("src/a/mod.rs")));
+    assert_eq!(
+        err.to_string(),
+        format!("parent module {} does not exist, create it first", root.join("src/a/mod.rs").display())
+    );
+}
+
+#[test]
+fn invalid_names_should_be_rejected_unless_sanitized() {
+    let (_dir, root) = project(&[]);
+
+    let res = CreateOptions::new(root.join("src/my-mod")).create();
+    assert!(matches!(res, Err(Error::InvalidModuleName(name)) if name == "my-mod"));
+    assert!(CreateOptions::new(root.join("src/type")).create().is_err());
+    assert!(!root.join("src/type.rs").exists());
+
+    let created = CreateOptions::new(root.join("src/My Mod")).sanitize(true).create().unwrap();
+    assert_eq!(created.module_path, root.join("src/my_mod.rs"));
+
+    let created = CreateOptions::new(root.join("src/type")).sanitize(true).with_test(false).create().unwrap();
+    assert_eq!(created.module_path, root.join("src/type.rs"));
+    assert_eq!(created.declaration_location.unwrap().column, 5);
+    assert_eq!(
+        fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub mod my_mod;\npub mod r#type;\n"
+    );
+}
+
+#[test]
+fn inline_test_location_should_write_tests_in_module() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let test = TestOptions {
+        location: TestLocation::Inline,
+        ..TestOptions::default()
+    };
+
+    let content = ContentOptions {
+        kind: Some(ModuleKind::Struct),
+        ..ContentOptions::default()
+    };
+
+    let created = CreateOptions::new(&path).content(content).test(test).add_to_super(false).create().unwrap();
+    assert_eq!(created.test_path, None);
+    assert!(!dir.path().join("my_mod_test.rs").exists());
+
+    let module = fs::read_to_string(dir.path().join("my_mod.rs")).unwrap();
+    assert!(module.contains("\n#[cfg(test)]\nmod tests {\n    use super::*;\n"));
+    assert!(module.ends_with("}\n}\n"));
+    assert!(!module.contains("#[path"));
+
+    let location = created.test_location.unwrap();
+    assert_eq!(location.path, dir.path().join("my_mod.rs"));
+    assert_eq!(location.line, module.lines().count());
+}
+
+#[test]
+fn insertion_should_keep_line_endings_and_missing_trailing_newline() {
+    let (_dir, root) = project(&[("src/lib.rs", "use std::fs;\r\n\r\nfn a() {}")]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        attributes: vec![String::from("#[cfg(unix)]")],
+        ..SuperOptions::default()
+    };
+
+    create(&root.join("src/b"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "use std::fs;\r\n#[cfg(unix)]\r\npub mod b;\r\n\r\nfn a() {}"
+    );
+
+    fs::write(&lib, "fn a() {}").unwrap();
+    create(&root.join("src/c"), false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "pub mod c;\nfn a() {}");
+}
+
+#[cfg(unix)]
+#[test]
+fn insertion_should_keep_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (_dir, root) = project(&[]);
+    let lib = root.join("src/lib.rs");
+    fs::set_permissions(&lib, fs::Permissions::from_mode(0o754)).unwrap();
+
+    create(&root.join("src/a"), false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), "pub mod a;\n");
+    assert_eq!(fs::metadata(&lib).unwrap().permissions().mode() & 0o777, 0o754);
+}
+
+#[test]
+fn insertion_should_replace_super_without_leaving_temp_files() {
+    let (_dir, root) = project(&[]);
+    let src = root.join("src");
+
+    create(&src.join("a"), false, &ContentOptions::default(), None, Some(&SuperOptions::default())).unwrap();
+    let mut files: Vec<_> = fs::read_dir(&src).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    files.sort();
+    assert_eq!(files, ["a.rs", "lib.rs"]);
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\n");
+}
+
+#[test]
+fn header_and_doc_templates_should_start_generated_files() {
+    let (_dir, root) = project(&[("Cargo.toml", "[package]\nname = \"a\"\nauthors = [\"Jane Doe <jane@example.com>\"]\n")]);
+
+    let content = ContentOptions {
+        templates: Templates {
+            header: Some(String::from("// Copyright {{ author }}")),
+            doc: Some(String::from("TODO: document {{ mod_name }}.")),
+            ..Templates::default()
+        },
+        ..ContentOptions::default()
+    };
+
+    let created = CreateOptions::new(root.join("src/my_mod")).content(content.clone()).create().unwrap();
+    let module = fs::read_to_string(&created.module_path).unwrap();
+    assert!(module.starts_with("// Copyright Jane Doe\n//! TODO: document my_mod.\n"));
+    assert_eq!(
+        fs::read_to_string(created.test_path.unwrap()).unwrap(),
+        "// Copyright Jane Doe\nuse super::*;\n"
+    );
+
+    let content = ContentOptions { doc: Some(String::from("Explicit.")), ..content };
+    let created = CreateOptions::new(root.join("src/other")).content(content).with_test(false).create().unwrap();
+    assert_eq!(
+        fs::read_to_string(&created.module_path).unwrap(),
+        "// Copyright Jane Doe\n//! Explicit.\n"
+    );
+}
+
+#[test]
+fn feature_should_gate_declaration() {
+    let (_dir, root) = project(&[]);
+    let lib = root.join("src/lib.rs");
+
+    let options = SuperOptions {
+        feature: Some(String::from("fancy")),
+        attributes: vec![String::from("#[doc(hidden)]")],
+        ..SuperOptions::default()
+    };
+
+    create(&root.join("src/my_mod"), false, &ContentOptions::default(), None, Some(&options)).unwrap();
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "#[cfg(feature = \"fancy\")]\n#[doc(hidden)]\npub mod my_mod;\n"
+    );
+}
+
+#[test]
+fn existing_module_should_be_completed_if_allowed() {
+    let (_dir, root) = project(&[("src/my_mod.rs", "pub fn a() {}\n")]);
+
+    let options = CreateOptions::new(root.join("src/my_mod"));
+    assert!(matches!(options.create(), Err(Error::ModuleExists(_))));
+
+    // without a test file, `ok` only declares the module
+    let created = options.clone().exists(Exists::Ok).create().unwrap();
+    assert!(created.existed);
+    assert_eq!(created.paths(), vec![root.join("src/lib.rs")]);
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub mod my_mod;\n");
+    assert_eq!(fs::read_to_string(root.join("src/my_mod.rs")).unwrap(), "pub fn a() {}\n");
+
+    let created = options.clone().exists(Exists::Update).create().unwrap();
+    assert_eq!(created.super_modified, None);
+    assert_eq!(created.test_path, Some(root.join("src/my_mod_test.rs")));
+    assert_eq!(created.module_modified, Some(root.join("src/my_mod.rs")));
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub mod my_mod;\n");
+    assert_eq!(
+        fs::read_to_string(root.join("src/my_mod.rs")).unwrap(),
+        "pub fn a() {}\n\n#[cfg(test)]\n#[path = \"./my_mod_test.rs\"]\nmod my_mod_test;\n"
+    );
+
+    // complete modules are left as they are
+    let created = options.exists(Exists::Update).create().unwrap();
+    assert!(created.paths().is_empty());
+}
+
+#[test]
+fn completing_declared_module_should_widen_or_reject_declaration() {
+    let (_dir, root) = project(&[
+        ("src/lib.rs", "mod my_mod;\nmod other {}\n"),
+        ("src/my_mod.rs", ""),
+        ("src/other.rs", ""),
+    ]);
+
+    let created = CreateOptions::new(root.join("src/my_mod")).exists(Exists::Ok).create().unwrap();
+    assert_eq!(created.super_modified, Some(root.join("src/lib.rs")));
+    assert_eq!(created.declaration_lines, vec![1]);
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub mod my_mod;\nmod other {}\n");
+
+    let err = CreateOptions::new(root.join("src/other")).exists(Exists::Ok).create().unwrap_err();
+    assert!(matches!(err, Error::Declared { line: 2, .. }));
+}
+
+#[test]
+fn adding_declared_module_should_keep_or_widen_declaration() {
+    let dir = tempfile::tempdir().unwrap();
+    let lib = dir.path().join("lib.rs");
+    fs::write(&lib, "mod a;\n#[path = \"b_impl.rs\"]\npub(crate) mod b;\n#[cfg(unix)]\nmod c;\nmod d {}\n").unwrap();
+
+    let private = SuperOptions { vis: Some(Visibility::Private), ..SuperOptions::default() };
+    let (location, lines) = add_module_to(OsStr::new("b"), &lib, &private).unwrap();
+    assert_eq!((location.line, location.column), (3, 12));
+    assert!(lines.is_empty());
+
+    let (location, lines) = add_module_to(OsStr::new("a"), &lib, &SuperOptions::default()).unwrap();
+    assert_eq!((location.line, location.column), (1, 5));
+    assert_eq!(lines, vec![1]);
+
+    // alternatives under other `cfg`s are not duplicates
+    let windows = SuperOptions { attributes: vec![String::from("#[cfg(windows)]")], ..private.clone() };
+    add_module_to(OsStr::new("c"), &lib, &windows).unwrap();
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "pub mod a;\n#[path = \"b_impl.rs\"]\npub(crate) mod b;\n#[cfg(unix)]\nmod c;\n#[cfg(windows)]\nmod c;\nmod d {}\n"
+    );
+
+    let err = add_module_to(OsStr::new("d"), &lib, &private).unwrap_err();
+    assert!(matches!(err, Error::Declared { line: 8, .. }));
+}
+
+#[test]
+fn async_framework_should_write_async_tests() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_mod");
+    let test = TestOptions { framework: TestFramework::Tokio, ..TestOptions::default() };
+    create(&path, false, &ContentOptions::default(), Some(&test), None).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("my_mod_test.rs")).unwrap(),
+        "use super::*;\n\n#[tokio::test]\nasync fn it_works() {}\n"
+    );
+
+    let template = "{{ test_attribute }}\n{% if test_async %}async {% endif %}fn a() {}\n";
+    let content = ContentOptions {
+        templates: Templates { test: Some(String::from(template)), ..Templates::default() },
+        ..ContentOptions::default()
+    };
+
+    let test = TestOptions { framework: TestFramework::AsyncStd, ..test };
+    create(&dir.path().join("other"), false, &content, Some(&test), None).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("other_test.rs")).unwrap(),
+        "#[async_std::test]\nasync fn a() {}\n"
+    );
+}
+
+#[test]
+fn relocate_should_follow_moved_declarations() {
+    let (_dir, root) = project(&[("Cargo.toml", "[package]\nname = \"a\"\n"), ("src/lib.rs", "mod zeta;\n")]);
+
+    let mut created = CreateOptions::new(root.join("src/alpha")).create().unwrap();
+    assert_eq!(created.declaration_location.as_ref().unwrap().line, 2);
+    assert_eq!(created.declaration_lines, vec![2]);
+
+    // e.g. reordered by `rustfmt`
+    fs::write(root.join("src/lib.rs"), "pub mod alpha;\nmod zeta;\n").unwrap();
+    fs::write(root.join("src/alpha_test.rs"), "use super::*;\n\n#[test]\nfn a() {}\n").unwrap();
+    created.relocate().unwrap();
+
+    let location = created.declaration_location.unwrap();
+    assert_eq!((location.line, location.column), (1, 5));
+    assert_eq!(created.declaration_lines, vec![1]);
+    assert_eq!(created.test_location.unwrap().line, 5);
+}
+
+#[test]
+fn relocate_should_find_lines_of_moved_declaration_and_reexport() {
+    let (_dir, root) = project(&[("src/lib.rs", "mod zeta;\n")]);
+    let options = SuperOptions { reexport: Some(Reexport::Glob), ..SuperOptions::default() };
+    let mut created = CreateOptions::new(root.join("src/alpha"))
+        .with_test(false)
+        .super_options(options)
+        .create()
+        .unwrap();
+
+    assert_eq!(created.declaration_lines, vec![2, 3]);
+
+    // e.g. reordered by `rustfmt`
+    fs::write(root.join("src/lib.rs"), "pub mod alpha;\nmod zeta;\n\npub use alpha::*;\n").unwrap();
+    created.relocate().unwrap();
+    assert_eq!(created.declaration_location.unwrap().line, 1);
+    assert_eq!(created.declaration_lines, vec![1, 4]);
+}
+
+#[test]
+fn module_edits_should_be_made_in_the_given_file_system() {
+    let mut fs = MemoryFileSystem::new()
+        .with_file("Cargo.toml", "[package]\nname = \"a\"\n")
+        .with_file("src/lib.rs", "pub mod a;\n")
+        .with_file("src/a.rs", "#[cfg(test)]\n#[path = \"a_test.rs\"]\nmod a_test;\n")
+        .with_file("src/a_test.rs", "#[test]\nfn t() {}\n");
+
+    inline_tests_in(&mut fs, Path::new("src/a"), &SuperOptions::default()).unwrap();
+    assert_eq!(fs.files()[Path::new("/src/a.rs")], "#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {}\n}\n");
+    assert!(!fs.is_file(Path::new("src/a_test.rs")));
+
+    convert_to_dir_in(&mut fs, Path::new("src/a.rs")).unwrap();
+    assert!(fs.is_file(Path::new("src/a/mod.rs")));
+    assert!(!fs.is_file(Path::new("src/a.rs")));
+
+    let created = test_support_in(
+        &mut fs, Path::new("src/test_support/fixtures"), &ContentOptions::default(), &SuperOptions::default(),
+    ).unwrap();
+
+    assert_eq!(created.len(), 2);
+    assert_eq!(fs.files()[Path::new("/src/lib.rs")], "pub mod a;\n#[cfg(test)]\nmod test_support;\n");
+    assert!(!Path::new("src/test_support").exists());
 
+    let vcs = MemoryVcs::new().with_unstaged("src/a/mod.rs");
+    let mut fs = fs.with_vcs(vcs);
+    let res = inline_tests_in(&mut fs, Path::new("src/a"), &SuperOptions::default());
+    assert!(matches!(res, Err(Error::Dirty(_))));
 }
@@ -1,70 +1,2131 @@
 //! CLI for adding modules to a rust project.
-use mkmod::result::Error;
-use std::path::PathBuf;
-use std::io;
-use clap::{command, Arg, ArgAction, value_parser};
+use mkmod::{ContentOptions, CreateOptions, Created, Exists, Reexport, TestOptions, TestLocation, SuperOptions};
+use mkmod::batch::{self, Spec};
+use mkmod::config::{self, Config, InsertPosition, Templates, TestStyle};
+use mkmod::kind::ModuleKind;
+use mkmod::manifest::Manifest;
+use mkmod::metrics::Metrics;
+use mkmod::plan_file::PlanFile;
+use mkmod::standalone::{self, TargetDir};
+use mkmod::{convert, diff, extract, git, hooks, journal, orphans, prune, remove, rename, rustfmt};
+use mkmod::project::{self, Project, Target, TargetKind};
+use mkmod::test_framework::{Dependency, TestFramework};
+use mkmod::tree::{Module, Tree};
+use mkmod::visibility::{self, Visibility};
+use mkmod::prompt::Prompt;
+use mkmod::report::{Modified, Report};
+use mkmod::name;
+use mkmod::result::{Error, Result};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+use std::time::Instant;
+use std::{env, fs, io, iter};
+use clap::{command, Arg, ArgAction, ArgMatches, Command, value_parser};
+use clap_complete::Shell;
+
+/// When mkmod started, for the total time of an operation.
+static STARTED: OnceLock<Instant> = OnceLock::new();
 
 fn main() {
-    let matches = command!()
-        .arg(
-            Arg::new("path")
-                .value_parser(value_parser!(PathBuf))
-                .help("Path to the module")
-        )
-        .arg(
-            Arg::new("dir")
-                .long("dir")
-                .action(ArgAction::SetTrue)
-                .help("Create module as a directory")
-        )
-        .arg(
-            Arg::new("with_test")
-                .long("no-test")
-                .action(ArgAction::SetFalse)
-                .help("Do not add a test file")
-        )
-        .arg(
-            Arg::new("add_to_super")
-                .long("no-add")
-                .action(ArgAction::SetFalse)
-                .help("Do not add module to super")
-        )
-        .arg(
-            Arg::new("super_main")
-                .long("main")
-                .action(ArgAction::SetTrue)
-                .help("Add module to main instead of lib (only applies when adding to super for the crate root)")
-        )
-        .arg(
-            Arg::new("public")
-                .long("private")
-                .action(ArgAction::SetFalse)
-                .help("Add module to super as private (only applies when adding to super)")
-        )
-        .get_matches();
+    STARTED.get_or_init(Instant::now);
+    let matches = cli().get_matches();
+
+    let res = match matches.subcommand() {
+        Some(("apply", matches)) => apply(matches),
+        Some(("inline-tests", matches)) => inline_tests(matches),
+        Some(("history", matches)) => history(matches),
+        Some(("env", matches)) => print_env(matches),
+        Some(("test-support", matches)) => test_support(matches),
+        Some(("check", matches)) => check(matches),
+        Some(("diff", matches)) => diff(matches),
+        Some(("template", matches)) => match matches.subcommand() {
+            Some(("upgrade", matches)) => template_upgrade(matches),
+            _ => unreachable!("`template` requires a subcommand"),
+        },
+        Some(("sync", matches)) => sync(matches),
+        Some(("prune", matches)) => prune(matches),
+        Some(("adopt", matches)) => adopt(matches),
+        Some(("rm", matches)) => rm(matches),
+        Some(("mv", matches)) => mv(matches),
+        Some(("extract", matches)) => extract(matches),
+        Some(("convert", matches)) => convert(matches),
+        Some(("batch", matches)) => batch(matches),
+        Some(("ls", _)) => ls(),
+        Some(("completions", matches)) => completions(matches),
+        Some(("man", _)) => man(),
+        _ if matches.get_flag("interactive") => interactive(),
+        _ => create(&matches),
+    };
+
+    if let Err(err) = res {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+/// Command line interface.
+fn cli() -> Command<'static> {
+    command!()
+    .args_conflicts_with_subcommands(true)
+    .subcommand_negates_reqs(true)
+    .arg(
+        Arg::new("path")
+            .value_parser(value_parser!(PathBuf))
+            .required_unless_present("interactive")
+            .help("Path to the module, or its path from the crate root, e.g. 'a::b::c'. Missing parent modules are created")
+    )
+    .arg(
+        Arg::new("interactive")
+            .short('i')
+            .long("interactive")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(&[
+                "path", "package", "dir", "kind", "template", "in", "with_test", "test_style", "super_main", "target", "super",
+                "vis", "private", "plan_out", "output",
+            ])
+            .help("Choose the module's name, location, and options by answering questions")
+    )
+    .arg(
+        Arg::new("output")
+            .long("output")
+            .value_name("FORMAT")
+            .value_parser(["human", "json"])
+            .default_value("human")
+            .help("Report created and modified files as text, or as JSON for editors and scripts")
+    )
+    .arg(
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("output")
+            .help("Only report errors")
+    )
+    .arg(
+        Arg::new("no_fmt")
+            .long("no-fmt")
+            .global(true)
+            .action(ArgAction::SetTrue)
+            .help("Do not format the touched files with rustfmt")
+    )
+    .arg(
+        Arg::new("package")
+            .short('p')
+            .long("package")
+            .value_name("NAME")
+            .help("Workspace package to create the module in, with the path relative to its source directory")
+    )
+    .arg(
+        Arg::new("src_root")
+            .long("src-root")
+            .value_name("DIR")
+            .value_parser(value_parser!(PathBuf))
+            .help("Source root of the crate, relative to the crate root [default: src]")
+    )
+    .arg(
+        Arg::new("dir")
+            .long("dir")
+            .action(ArgAction::SetTrue)
+            .help("Create module as a directory")
+    )
+    .arg(
+        Arg::new("sanitize")
+            .long("sanitize")
+            .action(ArgAction::SetTrue)
+            .help("Convert the module name to snake_case, declaring keywords as raw identifiers, instead of rejecting invalid names")
+    )
+    .arg(
+        Arg::new("exists")
+            .long("exists")
+            .value_name("MODE")
+            .value_parser(["error", "ok", "update"])
+            .default_value("error")
+            .conflicts_with_all(&["plan_out", "dry_run"])
+            .help("If the module exists: 'error', 'ok' to declare it and its existing test file if they are not, or 'update' to also create a missing test file")
+    )
+    .arg(
+        Arg::new("doc_file")
+            .long("doc-file")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("File containing documentation for the module, added as `//!` comments")
+    )
+    .arg(
+        Arg::new("doc")
+            .long("doc")
+            .value_name("TEXT")
+            .conflicts_with("doc_file")
+            .help("Documentation for the module, added as `//!` comments")
+    )
+    .arg(
+        Arg::new("kind")
+            .long("kind")
+            .value_name("KIND")
+            .value_parser(["struct", "trait", "enum", "service"])
+            .help("Create the module with a starter skeleton of the given kind, and matching tests")
+    )
+    .arg(
+        Arg::new("in")
+            .long("in")
+            .value_name("DIR")
+            .value_parser(["tests", "benches", "examples", "src/bin"])
+            .conflicts_with_all(&["dir", "kind", "src_root", "plan_out", "dry_run"])
+            .help("Create an integration test, bench, example, or binary named by the path instead of a module")
+    )
+    .arg(
+        Arg::new("template")
+            .long("template")
+            .value_name("NAME")
+            .conflicts_with_all(&["kind", "module_template"])
+            .help("Create the module from a template configured in [templates.named], e.g. 'handler'")
+    )
+    .arg(
+        Arg::new("module_template")
+            .long("module-template")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("File containing the template of the module's body, overriding the configuration")
+    )
+    .arg(
+        Arg::new("test_template")
+            .long("test-template")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("File containing the template of the test file, overriding the configuration")
+    )
+    .arg(
+        Arg::new("with_test")
+            .long("no-test")
+            .action(ArgAction::SetFalse)
+            .help("Do not add a test file")
+    )
+    .arg(
+        Arg::new("test_style")
+            .long("test-style")
+            .value_name("STYLE")
+            .value_parser(["inline", "file", "none"])
+            .conflicts_with("with_test")
+            .help("Write tests in an inline `mod tests` block, a separate test file, or not at all [default: configured, or file]")
+    )
+    .arg(
+        Arg::new("test_cfg")
+            .long("test-cfg")
+            .value_name("PREDICATE")
+            .default_value("test")
+            .help("Configuration predicate guarding the test module, e.g. 'all(test, feature = \"expensive-tests\")'")
+    )
+    .arg(
+        Arg::new("test_framework")
+            .long("test-framework")
+            .value_name("FRAMEWORK")
+            .value_parser(["plain", "tokio", "async-std"])
+            .help("Framework the generated tests are written for, e.g. 'tokio' for `#[tokio::test] async fn` tests [default: plain]")
+    )
+    .arg(
+        Arg::new("async")
+            .long("async")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("test_framework")
+            .help("Write async tests with tokio, short for --test-framework tokio")
+    )
+    .arg(
+        Arg::new("add_dev_dependency")
+            .long("add-dev-dependency")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("plan_out")
+            .help("Add the test framework's crate to the [dev-dependencies] of Cargo.toml if it is missing")
+    )
+    .arg(
+        Arg::new("test_imports")
+            .long("test-import")
+            .value_name("PATH")
+            .action(ArgAction::Append)
+            .help("Additional import for the test file, e.g. 'pretty_assertions::assert_eq'")
+    )
+    .arg(
+        Arg::new("test_location")
+            .long("test-location")
+            .value_parser(["sibling", "mirrored"])
+            .default_value("sibling")
+            .help("Place the test file next to the module, or in a tree mirroring the source directory")
+    )
+    .arg(
+        Arg::new("test_root")
+            .long("test-root")
+            .value_name("DIR")
+            .value_parser(value_parser!(PathBuf))
+            .default_value("src/tests")
+            .help("Root of the mirrored test tree, relative to the crate root (only applies to mirrored tests)")
+    )
+    .arg(
+        Arg::new("add_to_super")
+            .long("no-add")
+            .action(ArgAction::SetFalse)
+            .help("Do not add module to super")
+    )
+    .arg(
+        Arg::new("super_main")
+            .long("main")
+            .action(ArgAction::SetTrue)
+            .help("Add module to main instead of lib (only applies when adding to super for the crate root)")
+    )
+    .arg(
+        Arg::new("target")
+            .long("target")
+            .value_name("NAME")
+            .conflicts_with("super_main")
+            .help("Target whose root module the module is added to, when its directory contains several target roots")
+    )
+    .arg(
+        Arg::new("super")
+            .long("super")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with_all(&["super_main", "target", "add_to_super"])
+            .help("File to declare the module in, instead of resolving its super from the module tree")
+    )
+    .arg(
+        Arg::new("vis")
+            .long("vis")
+            .value_name("VIS")
+            .help("Visibility of the declaration: 'pub', 'pub(crate)', 'pub(super)', 'pub(in <path>)', or 'private' (only applies when adding to super) [default: pub, or that of the super's glob re-exported modules]")
+    )
+    .arg(
+        Arg::new("private")
+            .long("private")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("vis")
+            .help("Add module to super as private, same as --vis private")
+    )
+    .arg(
+        Arg::new("reexport")
+            .long("reexport")
+            .value_name("ITEM")
+            .min_values(0)
+            .require_equals(true)
+            .default_missing_value("glob")
+            .help("Re-export the module's contents after its declaration: 'glob', or an item, e.g. '--reexport=Client' [default: glob]")
+    )
+    .arg(
+        Arg::new("dirty_ok")
+            .long("dirty-ok")
+            .action(ArgAction::SetTrue)
+            .help("Add module to super even if it has unstaged changes")
+    )
+    .arg(
+        Arg::new("require_clean")
+            .long("require-clean")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("dirty_ok")
+            .help("Refuse to modify the super file if it has any uncommitted changes, staged or not, or is untracked")
+    )
+    .arg(
+        Arg::new("git")
+            .long("git")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(&["plan_out", "dry_run"])
+            .help("Stage the created and modified files with git")
+    )
+    .arg(
+        Arg::new("at")
+            .long("at")
+            .value_name("LINE")
+            .value_parser(value_parser!(usize))
+            .help("Line number at which to insert the module declaration in the super file")
+    )
+    .arg(
+        Arg::new("insert")
+            .long("insert")
+            .value_name("POSITION")
+            .value_parser(["end", "sorted"])
+            .conflicts_with("at")
+            .help("Insert the declaration after the super file's declarations, or in alphabetical order among them [default: configured, or end]")
+    )
+    .arg(
+        Arg::new("mod_comment")
+            .long("mod-comment")
+            .value_name("COMMENT")
+            .help("Comment to add after the module declaration in the super file")
+    )
+    .arg(
+        Arg::new("mod_attrs")
+            .long("mod-attr")
+            .value_name("ATTRIBUTE")
+            .action(ArgAction::Append)
+            .help("Attribute to add to the module declaration in the super file, e.g. '#[cfg(unix)]'")
+    )
+    .arg(
+        Arg::new("feature")
+            .long("feature")
+            .value_name("FEATURE")
+            .help("Gate the module behind a feature, declaring it with '#[cfg(feature = \"FEATURE\")]', or with --in, require it")
+    )
+    .arg(
+        Arg::new("add_feature")
+            .long("add-feature")
+            .action(ArgAction::SetTrue)
+            .requires("feature")
+            .conflicts_with("plan_out")
+            .help("Add the feature to the [features] table of Cargo.toml if it is missing")
+    )
+    .arg(
+        Arg::new("plan_out")
+            .long("plan-out")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("Write the plan to a file instead of creating the module, to be applied with `mkmod apply`")
+    )
+    .arg(
+        Arg::new("dry_run")
+            .long("dry-run")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(&["plan_out", "output", "quiet"])
+            .help("Show the changes as a diff without modifying anything")
+    )
+    .subcommand(
+        Command::new("apply")
+            .about("Apply a plan written with --plan-out, if the files it touches have not changed")
+            .arg(
+                Arg::new("plan")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+                    .help("Path to the plan file")
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FORMAT")
+                    .value_parser(["human", "json"])
+                    .default_value("human")
+                    .help("Report created and modified files as text, or as JSON for editors and scripts")
+            )
+            .arg(
+                Arg::new("quiet")
+                    .short('q')
+                    .long("quiet")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("output")
+                    .help("Only report errors")
+            )
+    )
+    .subcommand(
+        Command::new("inline-tests")
+            .about("Move a module's test file into an inline test module")
+            .arg(
+                Arg::new("path")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+                    .help("Path to the module")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify the module file even if it has unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("env")
+            .about("Show the resolved project layout and configuration")
+            .arg(
+                Arg::new("path")
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Path inside the project [default: current directory]")
+            )
+    )
+    .subcommand(
+        Command::new("test-support")
+            .about("Create a module for helpers shared between unit tests")
+            .arg(
+                Arg::new("name")
+                    .required(true)
+                    .help("Name of the module")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify super files even if they have unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("check")
+            .about("List modules that are not declared in their super, failing if there are any")
+            .arg(
+                Arg::new("changed_since")
+                    .long("changed-since")
+                    .value_name("REF")
+                    .help("Only check files added since the git commit")
+            )
+    )
+    .subcommand(
+        Command::new("diff")
+            .about("Show how a module differs from the template it would be created with, failing if it does")
+            .arg(
+                Arg::new("path")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Module file, e.g. 'src/handlers/users.rs'")
+            )
+            .arg(
+                Arg::new("template")
+                    .long("template")
+                    .value_name("NAME")
+                    .help("Template configured in [templates.named] to compare with [default: the module template]")
+            )
+    )
+    .subcommand(
+        Command::new("template")
+            .about("Manage modules generated from templates")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("upgrade")
+                    .about("Re-render modules with the current version of their templates, merging in the edits made since they were generated")
+                    .arg(
+                        Arg::new("paths")
+                            .action(ArgAction::Append)
+                            .value_parser(value_parser!(PathBuf))
+                            .help("Module files to upgrade [default: all modules generated from templates]")
+                    )
+                    .arg(
+                        Arg::new("dry_run")
+                            .long("dry-run")
+                            .action(ArgAction::SetTrue)
+                            .help("Print the changes as a diff instead of making them")
+                    )
+            )
+    )
+    .subcommand(
+        Command::new("sync")
+            .about("Declare modules that are not declared in their super")
+            .arg(
+                Arg::new("changed_since")
+                    .long("changed-since")
+                    .value_name("REF")
+                    .help("Only declare files added since the git commit")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify super files even if they have unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("prune")
+            .about("Remove modules that contain no items, along with their declarations")
+            .arg(
+                Arg::new("path")
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Path inside the project [default: current directory]")
+            )
+            .arg(
+                Arg::new("yes")
+                    .short('y')
+                    .long("yes")
+                    .action(ArgAction::SetTrue)
+                    .help("Remove the modules without asking for confirmation")
+            )
+    )
+    .subcommand(
+        Command::new("adopt")
+            .about("Declare an existing module file in its super, without modifying the file")
+            .arg(
+                Arg::new("path")
+                    .required_unless_present("all")
+                    .conflicts_with("all")
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Module file, or directory of a directory module")
+            )
+            .arg(
+                Arg::new("all")
+                    .long("all")
+                    .action(ArgAction::SetTrue)
+                    .help("Declare every module that is not declared in its super")
+            )
+            .arg(
+                Arg::new("vis")
+                    .long("vis")
+                    .value_name("VIS")
+                    .default_value("pub")
+                    .help("Visibility of the declarations: 'pub', 'pub(crate)', 'pub(super)', 'pub(in <path>)', or 'private'")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify super files even if they have unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("rm")
+            .about("Remove a module, its test file, and its declaration")
+            .arg(
+                Arg::new("path")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Path to the module")
+            )
+            .arg(
+                Arg::new("force")
+                    .short('f')
+                    .long("force")
+                    .action(ArgAction::SetTrue)
+                    .help("Remove the module even if other files refer to it")
+            )
+    )
+    .subcommand(
+        Command::new("mv")
+            .about("Rename or move a module, its test file, and its declaration")
+            .arg(
+                Arg::new("from")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Path to the module")
+            )
+            .arg(
+                Arg::new("to")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("New path of the module")
+            )
+            .arg(
+                Arg::new("no_rewrite")
+                    .long("no-rewrite")
+                    .action(ArgAction::SetTrue)
+                    .help("Do not rewrite `crate::` paths to the module")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify super files even if they have unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("extract")
+            .about("Move items into a new module, re-exporting them so callers keep compiling")
+            .arg(
+                Arg::new("file")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Module file containing the items")
+            )
+            .arg(
+                Arg::new("item")
+                    .long("item")
+                    .value_name("NAME")
+                    .required(true)
+                    .action(ArgAction::Append)
+                    .help("Struct, enum, function, trait, type, const, or static to move, with its impls. May be repeated")
+            )
+            .arg(
+                Arg::new("into")
+                    .long("into")
+                    .value_name("NAME")
+                    .required(true)
+                    .help("Name of the new module")
+            )
+            .arg(
+                Arg::new("vis")
+                    .long("vis")
+                    .value_name("VIS")
+                    .default_value("private")
+                    .help("Visibility of the new module: 'pub', 'pub(crate)', 'pub(super)', 'pub(in <path>)', or 'private'")
+            )
+            .arg(
+                Arg::new("dirty_ok")
+                    .long("dirty-ok")
+                    .action(ArgAction::SetTrue)
+                    .help("Modify the file even if it has unstaged changes")
+            )
+    )
+    .subcommand(
+        Command::new("batch")
+            .about("Create the modules listed in a spec, undoing everything if any can not be created")
+            .arg(
+                Arg::new("spec")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("TOML spec with `[[module]]` tables, or a file listing module paths, or '-' to read from stdin")
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FORMAT")
+                    .value_parser(["human", "json"])
+                    .default_value("human")
+                    .help("Report created and modified files as text, or as JSON for editors and scripts")
+            )
+            .arg(
+                Arg::new("quiet")
+                    .short('q')
+                    .long("quiet")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("output")
+                    .help("Only report errors")
+            )
+    )
+    .subcommand(
+        Command::new("convert")
+            .about("Convert a file module into a directory module, or a directory module into a file module")
+            .arg(
+                Arg::new("path")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf))
+                    .help("Path to the module, e.g. `src/a.rs` or `src/a`")
+            )
+    )
+    .subcommand(
+        Command::new("ls")
+            .about("Print the crate's module tree, flagging missing and undeclared module files")
+    )
+    .subcommand(
+        Command::new("completions")
+            .about("Print the completion script of a shell, e.g. `mkmod completions bash > /etc/bash_completion.d/mkmod`")
+            .arg(
+                Arg::new("shell")
+                    .required(true)
+                    .value_parser(value_parser!(Shell))
+                    .help("Shell to complete commands in")
+            )
+    )
+    .subcommand(
+        Command::new("man")
+            .about("Print the man page, e.g. `mkmod man > mkmod.1`")
+    )
+    .subcommand(
+        Command::new("history")
+            .about("Show the journal of operations performed on the project")
+            .arg(
+                Arg::new("limit")
+                    .short('n')
+                    .long("limit")
+                    .value_name("N")
+                    .value_parser(value_parser!(usize))
+                    .help("Only show the most recent operations")
+            )
+    )
+}
+
+/// Get the path of a module from its path from the crate root, e.g. `a::b::c`,
+/// following declarations from the root module it is added to.
+///
+/// # Arguments
+/// + `project`: Project the module is in.
+/// + `module_path`: Path of the module from the crate root. e.g. `a/b/c`.
+/// + `matches`: Arguments selecting the root module.
+///
+/// # Returns
+/// The path in the source root if no target's root module is in it.
+fn resolve_module_path(project: &Project, module_path: &Path, matches: &ArgMatches) -> Result<PathBuf> {
+    let tree = Tree::read(project)?;
+    let targets = project.targets()?;
+    let roots: Vec<(&Target, &Module)> = targets
+        .iter()
+        .zip(&tree.roots)
+        .filter(|(target, _)| target.root.parent() == Some(project.src.as_path()))
+        .collect();
+
+    let root = match matches.get_one::<String>("target") {
+        Some(name) => roots.iter().find(|(target, _)| target.name == *name),
+        None if matches.get_flag("super_main") => roots.iter().find(|(target, _)| target.kind == TargetKind::Bin),
+        None => roots
+            .iter()
+            .find(|(target, _)| target.kind == TargetKind::Lib)
+            .or_else(|| roots.first()),
+    };
+
+    let segments: Vec<&str> = module_path.iter().filter_map(OsStr::to_str).collect();
+    match root {
+        Some((_, root)) => root.resolve(&segments),
+        None => Ok(project.src.join(module_path)),
+    }
+}
+
+/// Create a new module.
+fn create(matches: &ArgMatches) -> Result {
+    if matches.get_one::<PathBuf>("path").is_some_and(|path| path == Path::new("-")) {
+        return create_batch(&read_spec(Path::new("-"))?, !matches.get_flag("no_fmt"), output(matches));
+    }
+
+    if let Some(dir) = matches.get_one::<String>("in") {
+        return create_standalone(matches, dir.parse()?);
+    }
 
     let name = matches.get_one::<PathBuf>("path").expect("`path` must be provided");
+    let package = matches.get_one::<String>("package").map(String::as_str);
+    let src_root = matches.get_one::<PathBuf>("src_root");
+    // module paths, e.g. `a::b::c`, are relative to the source root
+    let module_path = name
+        .to_str()
+        .filter(|name| name.contains("::"))
+        .map(|name| name.trim_start_matches("crate::").split("::").collect::<PathBuf>());
+
+    // file paths inside of a package are used as given, unless a package is selected
+    let mut metrics = Metrics::default();
+    let discovery = Instant::now();
+    let in_package = package.is_none() && module_path.is_none() && Project::discover(name).is_ok();
+    let name = &if name.is_relative() && !in_package {
+        let project = match Project::select_package(&env::current_dir()?, package)? {
+            Some(project) => Some(project),
+            None if module_path.is_some() => Some(Project::discover(&env::current_dir()?)?),
+            None => None,
+        };
+
+        match project {
+            Some(project) => {
+                let config = Config::load(&project.root)?;
+                let project = match src_root.or(config.project.src_root.as_ref()) {
+                    Some(src_root) => project.with_src_root(src_root),
+                    None => project,
+                };
+
+                match &module_path {
+                    Some(module_path) => resolve_module_path(&project, module_path, matches)?,
+                    None => project.src.join(name),
+                }
+            },
+
+            None => name.clone(),
+        }
+    } else {
+        name.clone()
+    };
+
+    metrics.root_discovery.record(discovery.elapsed());
     let dir = matches.get_flag("dir");
-    let with_test = matches.get_flag("with_test");
+    let doc = match matches.get_one::<PathBuf>("doc_file") {
+        Some(doc_file) => Some(fs::read_to_string(doc_file)?),
+        None => matches.get_one::<String>("doc").cloned(),
+    };
+
+    let test_cfg = matches.get_one::<String>("test_cfg").expect("`test_cfg` has a default");
+    let test_imports = matches.get_many::<String>("test_imports")
+        .map(|imports| imports.cloned().collect())
+        .unwrap_or_default();
+
+    let file_location = match matches.get_one::<String>("test_location").map(String::as_str) {
+        Some("mirrored") => {
+            let test_root = matches.get_one::<PathBuf>("test_root").expect("`test_root` has a default");
+            TestLocation::Mirrored(test_root.clone())
+        },
+
+        _ => TestLocation::Sibling,
+    };
+
     let add_to_super = matches.get_flag("add_to_super");
     let super_main = matches.get_flag("super_main");
-    let public = matches.get_flag("public");
+    let vis = match matches.get_flag("private") {
+        true => Some(Visibility::Private),
+        false => matches.get_one::<String>("vis").map(|vis| vis.parse()).transpose()?,
+    };
+    let dirty_ok = matches.get_flag("dirty_ok");
+    let require_clean = matches.get_flag("require_clean");
+    let at = matches.get_one::<usize>("at").copied();
+    let comment = matches.get_one::<String>("mod_comment").cloned();
+    let attributes = matches.get_many::<String>("mod_attrs")
+        .map(|attrs| attrs.cloned().collect())
+        .unwrap_or_default();
+
+    let feature = matches.get_one::<String>("feature");
+    let add_feature = feature.filter(|_| matches.get_flag("add_feature"));
+
+    let config = load_config(name)?;
+    let src_root = src_root.or(config.project.src_root.as_ref()).cloned();
+    let test_style = match matches.get_one::<String>("test_style").map(String::as_str) {
+        _ if !matches.get_flag("with_test") => TestStyle::None,
+        Some("inline") => TestStyle::Inline,
+        Some("file") => TestStyle::File,
+        Some(_) => TestStyle::None,
+        None => config.module.test_style,
+    };
+
+    let with_test = test_style != TestStyle::None;
+    let test_location = match test_style {
+        TestStyle::Inline => TestLocation::Inline,
+        _ => file_location,
+    };
+
+    let mut templates = match matches.get_one::<String>("template") {
+        Some(name) => config.templates.clone().select(name)?,
+        None => config.templates.clone(),
+    };
+
+    if let Some(template) = matches.get_one::<PathBuf>("module_template") {
+        let template = fs::read_to_string(template)?;
+        templates.module = Some(template.clone());
+        templates.dir_module = Some(template);
+    }
+
+    if let Some(template) = matches.get_one::<PathBuf>("test_template") {
+        templates.test = Some(fs::read_to_string(template)?);
+    }
+
+    let framework = match matches.get_one::<String>("test_framework") {
+        _ if matches.get_flag("async") => TestFramework::Tokio,
+        Some(framework) => framework.parse()?,
+        None => config.module.test_framework,
+    };
+
+    let test = TestOptions {
+        cfg: test_cfg.clone(),
+        imports: test_imports,
+        location: test_location,
+        src_root: src_root.clone(),
+        framework,
+    };
+
+    let mut format = config.declaration.clone();
+    match matches.get_one::<String>("insert").map(String::as_str) {
+        Some("sorted") => format.insert = InsertPosition::Sorted,
+        Some("end") => format.insert = InsertPosition::End,
+        _ => {},
+    }
+
+    let super_options = SuperOptions {
+        main: super_main,
+        vis,
+        dirty_ok,
+        require_clean,
+        at,
+        comment,
+        attributes,
+        format,
+        src_root,
+        target: matches.get_one::<String>("target").cloned(),
+        reexport: matches.get_one::<String>("reexport").map(|item| match item.as_str() {
+            "glob" | "*" => Reexport::Glob,
+            item => Reexport::Item(item.to_string()),
+        }),
+        feature: feature.cloned(),
+        super_file: matches.get_one::<PathBuf>("super").cloned(),
+    };
+
+    let exists = match matches.get_one::<String>("exists").map(String::as_str) {
+        Some("ok") => Exists::Ok,
+        Some("update") => Exists::Update,
+        _ => Exists::Error,
+    };
+
+    let kind = match matches.get_one::<String>("kind") {
+        Some(kind) => Some(kind.parse::<ModuleKind>()?),
+        None => None,
+    };
+
+    let content = ContentOptions {
+        doc,
+        attributes: config.module.attributes.clone(),
+        templates,
+        kind,
+    };
+
+    let parent_content = ContentOptions {
+        attributes: config.module.attributes.clone(),
+        templates: config.templates.clone(),
+        ..ContentOptions::default()
+    };
+
+    let options = CreateOptions::new(name)
+        .dir(dir)
+        .content(content.clone())
+        .with_test(with_test)
+        .test(test.clone())
+        .add_to_super(add_to_super)
+        .super_options(super_options.clone())
+        .sanitize(matches.get_flag("sanitize"))
+        .exists(exists)
+        .parents(parent_content.clone());
+
+    let test = if with_test { Some(test) } else { None };
+    let super_options = if add_to_super { Some(super_options) } else { None };
+    if let Some(plan_out) = matches.get_one::<PathBuf>("plan_out") {
+        let plan_file = PlanFile::new(options.plan()?, content, test, super_options)?.parents(parent_content);
+        return plan_file.save(plan_out);
+    }
+
+    if matches.get_flag("dry_run") {
+        let mut preview = options.preview()?;
+        let dependency = framework.dependency().filter(|_| with_test && matches.get_flag("add_dev_dependency"));
+        if add_feature.is_some() || dependency.is_some() {
+            let manifest_path = Project::discover(name)?.manifest_path();
+            let mut manifest = Manifest::load(&manifest_path)?;
+            if let Some(feature) = add_feature {
+                manifest.ensure_feature(feature)?;
+            }
+
+            if let Some(dependency) = dependency {
+                manifest.ensure_dev_dependency(dependency.name, dependency.version, dependency.features)?;
+            }
+
+            if manifest.is_modified() {
+                preview.modified.push((manifest_path, fs::read_to_string(manifest.path())?, manifest.contents()));
+            }
+        }
+
+        print!("{}", preview.diff(&env::current_dir()?.canonicalize()?));
+        return Ok(());
+    }
+
+    let mut created = match config.hooks.pre_create.is_empty() {
+        true => options.create(),
+        false => match options.plan() {
+            // pre-create hooks only run for modules that are created
+            Err(Error::ModuleExists(_)) if exists != Exists::Error => options.create(),
+            plan => plan
+                .and_then(|plan| pre_create(&config, &plan))
+                .and_then(|_| options.create()),
+        },
+    }?;
+
+    let mut warnings = Vec::new();
+    if !matches.get_flag("no_fmt") {
+        warnings.extend(format_created(name, std::slice::from_mut(&mut created), &mut metrics)?);
+    }
+
+    let output = output(matches);
+    if output == Output::Human {
+        print_created(&created)?;
+    }
+
+    let mut manifest_edits = Vec::new();
+    if let Some(feature) = add_feature {
+        manifest_edits.extend(add_to_features(name, feature)?);
+    }
+
+    if let Some(dependency) = framework.dependency().filter(|_| with_test) {
+        match matches.get_flag("add_dev_dependency") {
+            true => manifest_edits.extend(add_to_dev_dependencies(name, dependency)?),
+            false => if let Ok(project) = Project::discover(name) {
+                let mut manifest = Manifest::load(&project.manifest_path())?;
+                if manifest.ensure_dev_dependency(dependency.name, dependency.version, dependency.features)? {
+                    warnings.push(format!(
+                        "add `{}` to [dev-dependencies] to run the tests, or pass --add-dev-dependency",
+                        dependency.name,
+                    ));
+                }
+            },
+        }
+    }
+
+    let mut report = Report { metrics, ..Report::default() };
+    report.add_module(&created)?;
+    for edit in &manifest_edits {
+        report.add_modified(&edit.path, &edit.inserted_lines)?;
+    }
+
+    let manifest_path = manifest_edits.first().map(|edit| edit.path.as_path());
+    if let Some(manifest_path) = manifest_path.filter(|_| output == Output::Human) {
+        let cwd = env::current_dir()?;
+        println!("added to   {}", manifest_path.strip_prefix(&cwd).unwrap_or(manifest_path).display());
+    }
+
+    // modules from skeletons or template files can not be re-rendered
+    let from_template = kind.is_none() && !matches.contains_id("module_template");
+    let template = matches.get_one::<String>("template").map(String::as_str);
+    let generated = match from_template {
+        true => generated(&created, template)?.into_iter().collect(),
+        false => Vec::new(),
+    };
+
+    let mut touched = created.paths();
+    touched.extend(manifest_path);
+    report.warnings = warnings;
+    report.warnings.extend(post_create(&config, name, &touched, false, generated)?);
+    if matches.get_flag("git") {
+        report.warnings.extend(stage(name, &touched)?);
+    }
+
+    finish(output, report)
+}
+
+/// Stage touched files with git.
+///
+/// # Arguments
+/// + `path`: Path in the repository.
+/// + `touched`: Files touched by the operation.
+///
+/// # Returns
+/// A warning if the files could not be staged.
+fn stage(path: &Path, touched: &[&Path]) -> Result<Option<String>> {
+    let dir = match Project::discover(path) {
+        Ok(project) => project.root,
+        Err(_) => env::current_dir()?,
+    };
+
+    let paths = touched
+        .iter()
+        .map(|path| project::absolute_path(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    match git::stage(&dir, &paths) {
+        Ok(()) => Ok(None),
+        Err(err) => Ok(Some(format!("files were not staged: {err}"))),
+    }
+}
+
+/// Add a feature to the `[features]` table of a module's package, if it is missing.
+///
+/// # Returns
+/// The manifest and the lines inserted in it, if it was modified.
+fn add_to_features(path: &Path, feature: &str) -> Result<Option<Modified>> {
+    let mut manifest = Manifest::load(&Project::discover(path)?.manifest_path())?;
+    if !manifest.ensure_feature(feature)? {
+        return Ok(None);
+    }
+
+    let inserted_lines = manifest.save()?;
+    Ok(Some(Modified { path: manifest.path().to_path_buf(), inserted_lines }))
+}
+
+/// Add a test framework's crate to the `[dev-dependencies]` of a module's package,
+/// if it is not a dependency already.
+///
+/// # Returns
+/// The manifest and the lines inserted in it, if it was modified.
+fn add_to_dev_dependencies(path: &Path, dependency: Dependency) -> Result<Option<Modified>> {
+    let mut manifest = Manifest::load(&Project::discover(path)?.manifest_path())?;
+    if !manifest.ensure_dev_dependency(dependency.name, dependency.version, dependency.features)? {
+        return Ok(None);
+    }
+
+    let inserted_lines = manifest.save()?;
+    Ok(Some(Modified { path: manifest.path().to_path_buf(), inserted_lines }))
+}
+
+/// Create an integration test, bench, example, or binary in the selected package.
+fn create_standalone(matches: &ArgMatches, dir: TargetDir) -> Result {
+    let name = matches.get_one::<PathBuf>("path").expect("`path` must be provided");
+    let package = matches.get_one::<String>("package").map(String::as_str);
+    let cwd = env::current_dir()?;
+    let mut metrics = Metrics::default();
+    let project = metrics.root_discovery.time(|| match Project::select_package(&cwd, package)? {
+        Some(project) => Ok(project),
+        None => Project::discover(&cwd),
+    })?;
+
+    let config = Config::load(&project.root)?;
+    let doc = match matches.get_one::<PathBuf>("doc_file") {
+        Some(doc_file) => Some(fs::read_to_string(doc_file)?),
+        None => matches.get_one::<String>("doc").cloned(),
+    };
+
+    let content = ContentOptions {
+        doc,
+        templates: config.templates.clone(),
+        ..ContentOptions::default()
+    };
+
+    let output = output(matches);
+    let feature = matches.get_one::<String>("feature").map(String::as_str);
+    let (file, manifest_edit) = standalone::create(&project, dir, &name.to_string_lossy(), &content, feature)?;
+    let mut manifest_edits: Vec<Modified> = manifest_edit.into_iter().collect();
+    if let Some(feature) = feature.filter(|_| matches.get_flag("add_feature")) {
+        manifest_edits.extend(add_to_features(&project.root, feature)?);
+    }
+
+    let mut report = Report { metrics, ..Report::default() };
+    report.add_touched(&file, false)?;
+    for edit in &manifest_edits {
+        report.add_modified(&edit.path, &edit.inserted_lines)?;
+    }
+
+    let mut touched = vec![file.as_path()];
+    touched.extend(manifest_edits.first().map(|edit| edit.path.as_path()));
+    if output == Output::Human {
+        for (index, path) in touched.iter().enumerate() {
+            let path = path.strip_prefix(&cwd).unwrap_or(path);
+            match index {
+                0 => println!("created    {}", path.display()),
+                _ => println!("added to   {}", path.display()),
+            }
+        }
+    }
+
+    let has_criterion = project
+        .manifest()?
+        .get("dev-dependencies")
+        .is_some_and(|deps| deps.get("criterion").is_some());
+
+    if dir == TargetDir::Benches && !has_criterion {
+        report.warnings.push(String::from("add `criterion` to [dev-dependencies] to build the bench"));
+    }
+
+    report.warnings.extend(post_create(&config, &file, &touched, !matches.get_flag("no_fmt"), Vec::new())?);
+    finish(output, report)
+}
+
+/// Create a module, asking for its name, location, and options.
+/// Other options given on the command line are kept, and the equivalent command is printed.
+fn interactive() -> Result {
+    let cwd = env::current_dir()?;
+    let (project, config) = load_project(&cwd)?;
+    let tree = Tree::read(&project)?;
+    let mut prompt = Prompt::stdio();
+
+    // modules new modules can be added to, with the directory of their submodules
+    let mut parents: Vec<(&Module, PathBuf, bool)> = Vec::new();
+    let mut stack: Vec<(&Module, bool)> = tree.roots.iter().rev().map(|root| (root, true)).collect();
+    while let Some((module, is_root)) = stack.pop() {
+        if !module.inline && !module.missing && !module.unparsed {
+            let owns_dir = is_root || module.file.file_name().is_some_and(|name| name == "mod.rs");
+            let dir = match owns_dir {
+                true => module.file.parent().unwrap_or(Path::new("")).to_path_buf(),
+                false => module.file.with_extension(""),
+            };
+
+            parents.push((module, dir, is_root));
+        }
+
+        stack.extend(module.children.iter().rev().map(|child| (child, false)));
+    }
+
+    if parents.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no module to add a module to was found").into());
+    }
+
+    let relative = |path: &Path| path.strip_prefix(&cwd).unwrap_or(path).to_path_buf();
+    let labels: Vec<String> = parents
+        .iter()
+        .map(|(module, _, _)| format!("{} ({})", module.name, relative(&module.file).display()))
+        .collect();
+
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let default = parents.iter().position(|(_, dir, _)| *dir == cwd).unwrap_or(0);
+    let (parent, dir, is_root) = &parents[prompt.select("Parent module", &labels, default)?];
+
+    let name = prompt.text("Module name", None, |name| {
+        if let Err(err) = name::validate(name) {
+            return match name::sanitize(name) {
+                Ok(sanitized) => Err(format!("{err}, try `{sanitized}`")),
+                Err(_) => Err(err.to_string()),
+            };
+        }
+
+        match dir.join(format!("{name}.rs")).exists() || dir.join(name).exists() {
+            true => Err(format!("`{name}` already exists")),
+            false => Ok(()),
+        }
+    })?;
+
+    let mut args: Vec<String> = vec![relative(&dir.join(&name)).to_string_lossy().into_owned()];
+    if prompt.select("Create the module as", &["file", "directory"], 0)? == 1 {
+        args.push(String::from("--dir"));
+    }
+
+    let mut visibilities = vec![Visibility::Pub, Visibility::Crate, Visibility::Super, Visibility::Private];
+    let common = visibility::most_common(&fs::read_to_string(&parent.file)?);
+    if let Some(vis) = common.as_ref().filter(|vis| !visibilities.contains(vis)) {
+        visibilities.push(vis.clone());
+    }
+
+    let labels: Vec<String> = visibilities.iter().map(Visibility::to_string).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let default = common.and_then(|vis| visibilities.iter().position(|v| *v == vis)).unwrap_or(0);
+    let vis = &visibilities[prompt.select("Visibility", &labels, default)?];
+    args.extend([String::from("--vis"), vis.to_string()]);
+
+    let styles = ["file", "inline", "none"];
+    let default = match config.module.test_style {
+        TestStyle::File => 0,
+        TestStyle::Inline => 1,
+        TestStyle::None => 2,
+    };
+
+    let style = styles[prompt.select("Tests", &styles, default)?];
+    args.extend([String::from("--test-style"), style.to_string()]);
+
+    let kinds = ["none", "struct", "trait", "enum", "service"];
+    let kind = kinds[prompt.select("Skeleton", &kinds, 0)?];
+    if kind != "none" {
+        args.extend([String::from("--kind"), kind.to_string()]);
+    }
+
+    // roots sharing a directory are told apart by their target
+    let shared_dir = parents.iter().filter(|(_, other, other_root)| *other_root && other == dir).count() > 1;
+    if *is_root && shared_dir {
+        args.extend([String::from("--target"), parent.name.clone()]);
+    }
+
+    let given = env::args().skip(1).filter(|arg| arg != "-i" && arg != "--interactive");
+    let args: Vec<String> = given.chain(args).collect();
+    let command = iter::once(String::from("mkmod"))
+        .chain(args.iter().map(|arg| shell_quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    println!("{command}");
+    if !prompt.confirm("Create the module?", true)? {
+        return Ok(());
+    }
+
+    create(&cli().get_matches_from(iter::once(String::from("mkmod")).chain(args)))
+}
+
+/// Quote an argument for a POSIX shell, if needed.
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "_-./:=@".contains(c);
+    match !arg.is_empty() && arg.chars().all(plain) {
+        true => arg.to_string(),
+        false => format!("'{}'", arg.replace('\'', "'\\''")),
+    }
+}
+
+/// Apply a saved plan.
+fn apply(matches: &ArgMatches) -> Result {
+    let path = matches.get_one::<PathBuf>("plan").expect("`plan` must be provided");
+    let plan_file = PlanFile::load(path)?;
+    let mut metrics = Metrics::default();
+    let config = metrics.root_discovery.time(|| load_config(&plan_file.plan.module_path))?;
+    if !config.hooks.pre_create.is_empty() {
+        plan_file.verify()?;
+        pre_create(&config, &plan_file.plan)?;
+    }
+
+    let mut created = plan_file.apply()?;
+    let path = &plan_file.plan.module_path;
+
+    let mut warnings = Vec::new();
+    if !matches.get_flag("no_fmt") {
+        warnings.extend(format_created(path, std::slice::from_mut(&mut created), &mut metrics)?);
+    }
+
+    let output = output(matches);
+    if output == Output::Human {
+        print_created(&created)?;
+    }
+
+    let mut report = Report { metrics, ..Report::default() };
+    report.add_module(&created)?;
+    report.warnings = warnings;
+    report.warnings.extend(post_create(&config, path, &created.paths(), false, Vec::new())?);
+    finish(output, report)
+}
+
+/// Print where a created module was declared and where its tests go.
+/// Paths are relative to the current directory when inside of it.
+fn print_created(created: &Created) -> Result {
+    let cwd = env::current_dir()?;
+    let relative = |location: &mkmod::Location| -> Result<mkmod::Location> {
+        let path = project::absolute_path(&location.path)?;
+        let path = path.strip_prefix(&cwd).map(Path::to_path_buf).unwrap_or(path);
+        Ok(mkmod::Location { path, ..location.clone() })
+    };
+
+    if created.existed {
+        let path = project::absolute_path(&created.module_path)?;
+        println!("exists      {}", path.strip_prefix(&cwd).unwrap_or(&path).display());
+    }
+
+    if let Some(location) = &created.declaration_location {
+        println!("declared at {}", relative(location)?);
+    }
+
+    if let Some(location) = &created.test_location {
+        println!("tests at    {}", relative(location)?);
+    }
+
+    Ok(())
+}
+
+/// Run the pre-create hooks for a planned module.
+fn pre_create(config: &Config, plan: &mkmod::Plan) -> Result {
+    let project = Project::discover(&plan.module_path)?;
+    let input = serde_json::to_string(plan)?;
+    hooks::run(&project.root, &config.hooks.pre_create, &plan.paths(), Some(&input))
+}
+
+/// Format and record a created module, and run the post-create hooks.
+///
+/// # Arguments
+/// + `config`: Configuration of the project.
+/// + `path`: Path of the module.
+/// + `touched`: Files touched while creating the module.
+/// + `fmt`: Format the touched files with `rustfmt`.
+/// + `generated`: Modules created from templates.
+///
+/// # Returns
+/// Warnings about problems that did not stop the module from being created.
+fn post_create(
+    config: &Config,
+    path: &Path,
+    touched: &[&Path],
+    fmt: bool,
+    generated: Vec<journal::Generated>,
+) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    if fmt {
+        warnings.extend(format(path, touched)?);
+    }
+
+    record_generated(path, touched, generated)?;
+
+    if let Ok(project) = Project::discover(path) {
+        let paths = touched
+            .iter()
+            .map(|path| project::absolute_path(path))
+            .collect::<Result<Vec<_>>>()?;
+
+        let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        hooks::run(&project.root, &config.hooks.post_create, &paths, None)?;
+    }
+
+    Ok(warnings)
+}
+
+/// Format touched files with `rustfmt`.
+/// Files outside of a project are not formatted.
+///
+/// # Arguments
+/// + `path`: Path in the project.
+/// + `touched`: Files touched by the operation.
+///
+/// # Returns
+/// A warning if the files could not be formatted.
+fn format(path: &Path, touched: &[&Path]) -> Result<Option<String>> {
+    let project = match Project::discover(path) {
+        Ok(project) => project,
+        Err(_) => return Ok(None),
+    };
+
+    let paths = touched
+        .iter()
+        .map(|path| project::absolute_path(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    match rustfmt::format(&project, &paths)? {
+        true => Ok(None),
+        false => Ok(Some(String::from("rustfmt could not format the touched files, they were left unformatted"))),
+    }
+}
+
+/// Format the files created for modules with `rustfmt`, leaving the existing files they were declared in as they are.
+/// Modules outside of a project are not formatted.
+///
+/// # Arguments
+/// + `path`: Path in the project.
+/// + `created`: Created modules, whose locations are updated.
+/// + `metrics`: Metrics the time spent formatting is added to.
+///
+/// # Returns
+/// A warning if the files could not be formatted.
+fn format_created(path: &Path, created: &mut [Created], metrics: &mut Metrics) -> Result<Option<String>> {
+    let project = match Project::discover(path) {
+        Ok(project) => project,
+        Err(_) => return Ok(None),
+    };
+
+    match metrics.format.time(|| rustfmt::format_created(&project, created))? {
+        true => Ok(None),
+        false => Ok(Some(String::from("rustfmt could not format the created files, they were left unformatted"))),
+    }
+}
+
+/// How the results of an operation are reported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    /// Text, with warnings as notes.
+    Human,
+
+    /// A [`Report`] as JSON.
+    Json,
 
-    let res = mkmod::main(&name, dir, with_test, add_to_super, super_main, public);
-    if res.is_ok() {
-        return;
+    /// Nothing.
+    Quiet,
+}
+
+/// Get how results should be reported.
+fn output(matches: &ArgMatches) -> Output {
+    if matches.get_flag("quiet") {
+        return Output::Quiet;
     }
 
-    // output error message
-    let err = res.unwrap_err();
-    let err_msg;
-    match err {
-        Error::Io(err) if err.kind() == io::ErrorKind::AlreadyExists => {
-            err_msg = String::from("a file of that name already exists");
+    match matches.get_one::<String>("output").map(String::as_str) {
+        Some("json") => Output::Json,
+        _ => Output::Human,
+    }
+}
+
+/// Report the results of an operation.
+/// The total time of the operation is added to its metrics.
+fn finish(output: Output, mut report: Report) -> Result {
+    if let Some(started) = STARTED.get() {
+        report.metrics.total_us = u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX);
+    }
+
+    match output {
+        Output::Human => {
+            for warning in &report.warnings {
+                println!("note: {warning}");
+            }
         },
 
-        _ => panic!("An unhandled error ocurred: {:?}", err),
+        Output::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        Output::Quiet => {},
+    }
+
+    Ok(())
+}
+
+/// Move a module's test file into an inline test module.
+fn inline_tests(matches: &ArgMatches) -> Result {
+    let path = matches.get_one::<PathBuf>("path").expect("`path` must be provided");
+    let options = SuperOptions { dirty_ok: matches.get_flag("dirty_ok"), ..SuperOptions::default() };
+    let touched = mkmod::inline_tests(path, &options)?;
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record(path, &touched)
+}
+
+/// Print the resolved environment.
+fn print_env(matches: &ArgMatches) -> Result {
+    let path = match matches.get_one::<PathBuf>("path") {
+        Some(path) => path.clone(),
+        None => env::current_dir()?,
+    };
+
+    let (project, config) = load_project(&path)?;
+
+    let edition = project.edition()?.unwrap_or_else(|| String::from("inherited from workspace"));
+    let rust_version = project.rust_version()?.unwrap_or_else(|| String::from("none"));
+    let root_module = match project.root_module() {
+        Some(module) => module.display().to_string(),
+        None => String::from("none"),
+    };
+
+    let config_files: Vec<String> = [Some(config::path(&project.root)), config::user_path()]
+        .into_iter()
+        .flatten()
+        .filter(|file| file.exists())
+        .map(|file| file.display().to_string())
+        .collect();
+
+    let config_files = match config_files.is_empty() {
+        true => String::from("none"),
+        false => config_files.join(", "),
+    };
+
+    let templates = match config.templates == Templates::default() {
+        true => "built-in",
+        false => "configured",
+    };
+
+    println!("crate root:   {}", project.root.display());
+    println!("source root:  {}", project.src.display());
+    println!("root module:  {root_module}");
+    println!("edition:      {edition}");
+    println!("rust-version: {rust_version}");
+    println!("config files: {config_files}");
+    println!("templates:    {templates}");
+    println!();
+    print!("{}", config.to_toml()?);
+    Ok(())
+}
+
+/// Create a test support module.
+fn test_support(matches: &ArgMatches) -> Result {
+    let name = matches.get_one::<String>("name").expect("`name` must be provided");
+    let (project, config) = load_project(&env::current_dir()?)?;
+    let tree = config.project.test_support.clone().unwrap_or_else(|| PathBuf::from("test_support"));
+    let path = project.src.join(tree).join(name);
+
+    let options = SuperOptions {
+        dirty_ok: matches.get_flag("dirty_ok"),
+        format: config.declaration.clone(),
+        src_root: config.project.src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let content = ContentOptions {
+        attributes: config.module.attributes.clone(),
+        templates: config.templates.clone(),
+        ..ContentOptions::default()
+    };
+
+    let created = mkmod::test_support(&path, &content, &options)?;
+    let touched: Vec<&Path> = created.iter().flat_map(|created| created.paths()).collect();
+    record(&project.root, &touched)
+}
+
+/// List modules missing from their super.
+/// Exits with an error code if any are found.
+fn check(matches: &ArgMatches) -> Result {
+    let (project, config) = load_project(&env::current_dir()?)?;
+    let changed_since = matches.get_one::<String>("changed_since").map(String::as_str);
+    let orphans = orphans::find(&project.src, config.project.src_root.as_deref(), changed_since)?;
+    if orphans.is_empty() {
+        return Ok(());
+    }
+
+    println!("Modules not declared in their super:");
+    for file in &orphans {
+        println!("    {}", file.display());
+    }
+
+    std::process::exit(1);
+}
+
+/// Show how a module differs from its template.
+fn diff(matches: &ArgMatches) -> Result {
+    let path = env::current_dir()?.join(matches.get_one::<PathBuf>("path").expect("`path` is required"));
+    if !path.is_file() {
+        return Err(Error::ModuleNotFound(path));
+    }
+
+    let (project, config) = load_project(&path)?;
+    let template = matches.get_one::<String>("template").map(String::as_str);
+    let current = fs::read_to_string(&path)?;
+    let expected = render_from_template(&project, &config, &path, template, &current, !matches.get_flag("no_fmt"))?;
+    let name = path.strip_prefix(&project.root).unwrap_or(&path).display().to_string();
+    let diff = diff::unified(&expected, &current, &format!("a/{name}"), &format!("b/{name}"));
+    if diff.is_empty() {
+        return Ok(());
+    }
+
+    print!("{diff}");
+    std::process::exit(1);
+}
+
+/// Render a module from its template, as it would be created.
+///
+/// # Arguments
+/// + `project`: Project the module is in.
+/// + `config`: Configuration of the project.
+/// + `path`: Module file.
+/// + `template`: Name of the named template, or `None` for the module template.
+/// + `declared`: Content of the module declaring its tests. Tests are rendered the way it declares them.
+/// + `fmt`: Format the rendered module with `rustfmt`.
+fn render_from_template(
+    project: &Project,
+    config: &Config,
+    path: &Path,
+    template: Option<&str>,
+    declared: &str,
+    fmt: bool,
+) -> Result<String> {
+    let templates = match template {
+        Some(name) => config.templates.clone().select(name)?,
+        None => config.templates.clone(),
+    };
+
+    let content = ContentOptions {
+        attributes: config.module.attributes.clone(),
+        templates,
+        ..ContentOptions::default()
+    };
+
+    let test = mkmod::test_location(path, declared).map(|location| TestOptions {
+        location,
+        src_root: config.project.src_root.clone(),
+        framework: config.module.test_framework,
+        ..TestOptions::default()
+    });
+
+    let rendered = mkmod::render_module(path, &content, test.as_ref())?;
+    match fmt {
+        true => rustfmt::format_str(project, &rendered, path.parent().unwrap_or(&project.root)),
+        false => Ok(rendered),
+    }
+}
+
+/// Re-render modules generated from templates with the current templates,
+/// merging in the edits made since they were generated.
+fn template_upgrade(matches: &ArgMatches) -> Result {
+    let cwd = env::current_dir()?;
+    let (project, config) = load_project(&cwd)?;
+    let mut modules = journal::generated_modules(&project.root)?;
+    if let Some(paths) = matches.get_many::<PathBuf>("paths") {
+        let mut selected = Vec::new();
+        for path in paths {
+            let path = project::absolute_path(&cwd.join(path))?;
+            match modules.iter().position(|module| project.root.join(&module.file) == path) {
+                Some(index) => selected.push(modules.swap_remove(index)),
+                None => return Err(Error::NotGenerated(path)),
+            }
+        }
+
+        modules = selected;
+    }
+
+    let dry_run = matches.get_flag("dry_run");
+    let mut conflicts = 0;
+    let mut touched = Vec::new();
+    let mut upgraded = Vec::new();
+    for module in modules {
+        let path = project.root.join(&module.file);
+        if !path.is_file() {
+            continue;
+        }
+
+        let template = module.template.as_deref();
+        let rendered = render_from_template(&project, &config, &path, template, &module.content, !matches.get_flag("no_fmt"))?;
+        if rendered == module.content {
+            continue;
+        }
+
+        let current = fs::read_to_string(&path)?;
+        let merge = diff::merge(&module.content, &current, &rendered, "current", "template");
+        let name = module.file.display().to_string();
+        if dry_run {
+            print!("{}", diff::unified(&current, &merge.content, &format!("a/{name}"), &format!("b/{name}")));
+            continue;
+        }
+
+        fs::write(&path, &merge.content)?;
+        match merge.conflicts {
+            0 => println!("upgraded   {name}"),
+            1 => println!("upgraded   {name}, with a conflict to resolve"),
+            n => println!("upgraded   {name}, with {n} conflicts to resolve"),
+        }
+
+        conflicts += merge.conflicts;
+        upgraded.push(journal::Generated::new(&project.root, &path, module.template, rendered)?);
+        touched.push(path);
+    }
+
+    if touched.is_empty() {
+        return Ok(());
+    }
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record_generated(&project.root, &touched, upgraded)?;
+    if conflicts > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Declare modules missing from their super.
+fn sync(matches: &ArgMatches) -> Result {
+    let (project, config) = load_project(&env::current_dir()?)?;
+    let changed_since = matches.get_one::<String>("changed_since").map(String::as_str);
+    let orphans = orphans::find(&project.src, config.project.src_root.as_deref(), changed_since)?;
+
+    let options = SuperOptions {
+        dirty_ok: matches.get_flag("dirty_ok"),
+        format: config.declaration.clone(),
+        src_root: config.project.src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let mut touched = Vec::new();
+    for file in &orphans {
+        let super_file = orphans::register(file, &options)?;
+        println!("declared {} in {}", file.display(), super_file.display());
+        touched.push(super_file);
+    }
+
+    if touched.is_empty() {
+        return Ok(());
+    }
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    if !matches.get_flag("no_fmt") {
+        if let Some(warning) = format(&project.root, &touched)? {
+            println!("note: {warning}");
+        }
+    }
+
+    record(&project.root, &touched)
+}
+
+/// Declare existing module files in their super.
+fn adopt(matches: &ArgMatches) -> Result {
+    let cwd = env::current_dir()?;
+    let path = matches.get_one::<PathBuf>("path").map(|path| cwd.join(path));
+    let (project, config) = load_project(path.as_deref().unwrap_or(&cwd))?;
+    let options = SuperOptions {
+        vis: Some(matches.get_one::<String>("vis").expect("`vis` has a default").parse()?),
+        dirty_ok: matches.get_flag("dirty_ok"),
+        format: config.declaration.clone(),
+        src_root: config.project.src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let files = match path {
+        Some(path) => vec![path],
+        None => orphans::find(&project.src, config.project.src_root.as_deref(), None)?,
+    };
+
+    let mut touched = Vec::new();
+    for file in &files {
+        let super_file = orphans::adopt(file, &options)?;
+        println!("declared {} in {}", file.display(), super_file.display());
+        touched.push(super_file);
+    }
+
+    if touched.is_empty() {
+        return Ok(());
+    }
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    if !matches.get_flag("no_fmt") {
+        if let Some(warning) = format(&project.root, &touched)? {
+            println!("note: {warning}");
+        }
+    }
+
+    record(&project.root, &touched)
+}
+
+/// Remove modules without items.
+fn prune(matches: &ArgMatches) -> Result {
+    let path = match matches.get_one::<PathBuf>("path") {
+        Some(path) => path.clone(),
+        None => env::current_dir()?,
+    };
+
+    let (project, _) = load_project(&path)?;
+    let empty = prune::find(&project.src)?;
+    if empty.is_empty() {
+        println!("No modules without items found");
+        return Ok(());
     }
 
-    println!("An error ocurred: {err_msg}");
+    println!("Modules without items:");
+    for file in &empty {
+        println!("    {}", file.display());
+    }
+
+    if !matches.get_flag("yes") {
+        print!("Remove {} module(s)? [y/N] ", empty.len());
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            return Ok(());
+        }
+    }
+
+    let mut touched = Vec::new();
+    for file in &empty {
+        touched.extend(remove::remove_module_file(file)?);
+    }
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record(&project.root, &touched)
+}
+
+/// Remove a module.
+/// Refuses if other files refer to the module, unless forced.
+fn rm(matches: &ArgMatches) -> Result {
+    let path = matches.get_one::<PathBuf>("path").expect("`path` must be provided");
+    let path = env::current_dir()?.join(path);
+    let (project, _) = load_project(&path)?;
+
+    if !matches.get_flag("force") {
+        let references = remove::references(&path, &project.src)?;
+        if !references.is_empty() {
+            println!("Module is referred to by:");
+            for file in &references {
+                println!("    {}", file.display());
+            }
+
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "module is in use, use --force to remove it anyway",
+            ).into());
+        }
+    }
+
+    let touched = remove::remove(&path)?;
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record(&project.root, &touched)
+}
+
+/// Rename or move a module.
+fn mv(matches: &ArgMatches) -> Result {
+    let from = matches.get_one::<PathBuf>("from").expect("`from` must be provided");
+    let to = matches.get_one::<PathBuf>("to").expect("`to` must be provided");
+    let from = env::current_dir()?.join(from);
+    let to = env::current_dir()?.join(to);
+    let (project, config) = load_project(&from)?;
+
+    let options = SuperOptions {
+        dirty_ok: matches.get_flag("dirty_ok"),
+        format: config.declaration.clone(),
+        src_root: config.project.src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let mut touched = rename::rename(&from, &to, &options)?;
+    if !matches.get_flag("no_rewrite") {
+        touched.extend(rename::rewrite_references(&project.src, &from, &to)?);
+    }
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record(&project.root, &touched)
+}
+
+/// Move items into a new module.
+fn extract(matches: &ArgMatches) -> Result {
+    let file = matches.get_one::<PathBuf>("file").expect("`file` must be provided");
+    let file = env::current_dir()?.join(file);
+    let items: Vec<String> = matches.get_many::<String>("item").expect("`item` must be provided").cloned().collect();
+    let into = matches.get_one::<String>("into").expect("`into` must be provided");
+    let (project, config) = load_project(&file)?;
+
+    let options = SuperOptions {
+        vis: Some(matches.get_one::<String>("vis").expect("`vis` has a default").parse()?),
+        dirty_ok: matches.get_flag("dirty_ok"),
+        format: config.declaration.clone(),
+        src_root: config.project.src_root.clone(),
+        ..SuperOptions::default()
+    };
+
+    let touched = extract::extract(&file, &items, into, &options)?;
+    println!("moved {} into {}", items.join(", "), touched[0].display());
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    if !matches.get_flag("no_fmt") {
+        if let Some(warning) = format(&project.root, &touched)? {
+            println!("note: {warning}");
+        }
+    }
+
+    record(&project.root, &touched)
+}
+
+/// Create the modules listed in a spec.
+fn batch(matches: &ArgMatches) -> Result {
+    let spec = matches.get_one::<PathBuf>("spec").expect("`spec` must be provided");
+    create_batch(&read_spec(spec)?, !matches.get_flag("no_fmt"), output(matches))
+}
+
+/// Read a batch spec from a file, or from stdin if the path is `-`.
+fn read_spec(path: &Path) -> Result<Spec> {
+    if path != Path::new("-") {
+        return Spec::load(path);
+    }
+
+    let mut content = String::new();
+    io::stdin().read_to_string(&mut content)?;
+    Spec::parse(&content)
+}
+
+/// Create the modules of a spec, with options from the configuration of the current project.
+fn create_batch(spec: &Spec, fmt: bool, output: Output) -> Result {
+    let cwd = env::current_dir()?;
+    let mut metrics = Metrics::default();
+    let config = metrics.root_discovery.time(|| load_config(&cwd))?;
+    let src_root = config.project.src_root.clone();
+    let content = ContentOptions {
+        attributes: config.module.attributes.clone(),
+        templates: config.templates.clone(),
+        ..ContentOptions::default()
+    };
+
+    let location = match config.module.test_style {
+        TestStyle::Inline => TestLocation::Inline,
+        _ => TestLocation::Sibling,
+    };
+
+    let base = CreateOptions::new("")
+        .content(content.clone())
+        .with_test(config.module.test_style != TestStyle::None)
+        .test(TestOptions {
+            location,
+            src_root: src_root.clone(),
+            framework: config.module.test_framework,
+            ..TestOptions::default()
+        })
+        .super_options(SuperOptions {
+            format: config.declaration.clone(),
+            src_root,
+            ..SuperOptions::default()
+        })
+        .parents(content);
+
+    let mut created = batch::create(spec, &base, |plan| {
+        match config.hooks.pre_create.is_empty() {
+            true => Ok(()),
+            false => pre_create(&config, plan),
+        }
+    })?;
+
+    let mut warnings = Vec::new();
+    if fmt {
+        warnings.extend(format_created(&cwd, &mut created, &mut metrics)?);
+    }
+
+    let mut report = Report { metrics, ..Report::default() };
+    let mut touched = Vec::new();
+    let mut generated_modules = Vec::new();
+    for (entry, module) in spec.modules.iter().zip(&created) {
+        if entry.kind.is_none() {
+            generated_modules.extend(generated(module, None)?);
+        }
+
+        if output == Output::Human {
+            let path = module.module_path.strip_prefix(&cwd).unwrap_or(&module.module_path);
+            println!("created     {}", path.display());
+            print_created(module)?;
+        }
+
+        report.add_module(module)?;
+        touched.extend(module.paths());
+    }
+
+    report.warnings = warnings;
+    report.warnings.extend(post_create(&config, &cwd, &touched, false, generated_modules)?);
+    finish(output, report)
+}
+
+/// Convert a module between a file and a directory module.
+fn convert(matches: &ArgMatches) -> Result {
+    let path = matches.get_one::<PathBuf>("path").expect("`path` must be provided");
+    let path = env::current_dir()?.join(path);
+    let (project, _) = load_project(&path)?;
+
+    let touched = if path.join("mod.rs").is_file() {
+        convert::to_file(&path)?
+    } else if path.file_name().is_some_and(|name| name == "mod.rs") {
+        convert::to_file(path.parent().unwrap_or(&path))?
+    } else if path.extension().is_some() {
+        convert::to_dir(&path)?
+    } else {
+        convert::to_dir(&path.with_extension("rs"))?
+    };
+
+    let touched: Vec<&Path> = touched.iter().map(PathBuf::as_path).collect();
+    record(&project.root, &touched)
+}
+
+/// Print the module tree of the project.
+fn ls() -> Result {
+    let (project, _) = load_project(&env::current_dir()?)?;
+    let tree = Tree::read(&project)?;
+    for root in &tree.roots {
+        print_module(root, &project.root, 0);
+    }
+
+    if !tree.orphans.is_empty() {
+        println!();
+        println!("Files not declared in any module:");
+        for file in &tree.orphans {
+            println!("    {}", file.strip_prefix(&project.root).unwrap_or(file).display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a module and its submodules, indented by depth.
+fn print_module(module: &Module, root: &Path, depth: usize) {
+    let file = module.file.strip_prefix(root).unwrap_or(&module.file);
+    let note = if module.missing {
+        " (missing)"
+    } else if module.unparsed {
+        " (does not parse)"
+    } else if module.inline {
+        " (inline)"
+    } else {
+        ""
+    };
+
+    println!("{}{} ({}){note}", "    ".repeat(depth), module.name, file.display());
+    for child in &module.children {
+        print_module(child, root, depth + 1);
+    }
+}
+
+/// Print the completion script of a shell.
+fn completions(matches: &ArgMatches) -> Result {
+    let shell = *matches.get_one::<Shell>("shell").expect("`shell` must be provided");
+    clap_complete::generate(shell, &mut cli(), "mkmod", &mut io::stdout());
+    Ok(())
+}
+
+/// Print the man page.
+fn man() -> Result {
+    clap_mangen::Man::new(cli()).render(&mut io::stdout())?;
+    Ok(())
+}
+
+/// Print the journal of operations.
+fn history(matches: &ArgMatches) -> Result {
+    let project = Project::discover(&env::current_dir()?)?;
+    let entries = journal::read(&project.root)?;
+    let skip = match matches.get_one::<usize>("limit") {
+        Some(limit) => entries.len().saturating_sub(*limit),
+        None => 0,
+    };
+
+    for entry in entries.iter().skip(skip) {
+        println!("{}  {}", journal::format_timestamp(entry.timestamp), entry.command.join(" "));
+        for file in &entry.files {
+            println!("    {}", file.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the project containing `path` and load its configuration.
+/// The project's source root is taken from the configuration.
+fn load_project(path: &Path) -> Result<(Project, Config)> {
+    let project = Project::discover(path)?;
+    let config = Config::load(&project.root)?;
+    let project = match &config.project.src_root {
+        Some(src_root) => project.with_src_root(src_root),
+        None => project,
+    };
+
+    Ok((project, config))
+}
+
+/// Load the configuration of the project containing `path`.
+/// Paths outside of a project use the default configuration.
+fn load_config(path: &Path) -> Result<Config> {
+    match Project::discover(path) {
+        Ok(project) => Config::load(&project.root),
+        Err(_) => Ok(Config::default()),
+    }
+}
+
+/// Record an operation in the journal of the project containing `path`.
+/// Operations outside of a project are not recorded.
+fn record(path: &Path, files: &[&Path]) -> Result {
+    record_generated(path, files, Vec::new())
+}
+
+/// Record an operation that generated modules from templates.
+/// See [`record`].
+fn record_generated(path: &Path, files: &[&Path], generated: Vec<journal::Generated>) -> Result {
+    let project = match Project::discover(path) {
+        Ok(project) => project,
+        Err(_) => return Ok(()),
+    };
+
+    let command = iter::once(String::from("mkmod")).chain(env::args().skip(1)).collect();
+    let entry = journal::Entry::new(&project.root, command, files)?.generated(generated);
+    journal::record(&project.root, &entry)
+}
+
+/// Record of a module created from a template, to upgrade it when the template changes.
+///
+/// # Arguments
+/// + `created`: Created module.
+/// + `template`: Name of the named template, or `None` for the module template.
+///
+/// # Returns
+/// `None` if the module already existed, or is not in a project.
+fn generated(created: &Created, template: Option<&str>) -> Result<Option<journal::Generated>> {
+    let project = match Project::discover(&created.module_path) {
+        Ok(project) if !created.existed => project,
+        _ => return Ok(None),
+    };
+
+    let file = match created.module_path.is_dir() {
+        true => created.module_path.join("mod.rs"),
+        false => created.module_path.clone(),
+    };
+
+    let content = fs::read_to_string(&file)?;
+    Ok(Some(journal::Generated::new(&project.root, &file, template.map(String::from), content)?))
 }
@@ -0,0 +1,272 @@
+//! Format preserving editing of `Cargo.toml`.
+//!
+//! Edits are made in memory. Use [`Manifest::diff`] to preview them,
+//! and [`Manifest::save`] to write them.
+use crate::diff;
+use crate::result::Result;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table};
+
+/// Kind of a Cargo target section.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetKind {
+    Bin,
+    Example,
+    Test,
+    Bench,
+}
+
+impl TargetKind {
+    /// Name of the array of tables holding targets of this kind.
+    pub fn section(&self) -> &'static str {
+        match self {
+            Self::Bin => "bin",
+            Self::Example => "example",
+            Self::Test => "test",
+            Self::Bench => "bench",
+        }
+    }
+}
+
+/// A Cargo target, e.g. a `[[bench]]` section.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Target {
+    pub name: String,
+
+    /// Path to the target's root file, relative to the crate root.
+    pub path: Option<String>,
+
+    /// Whether the target uses the libtest harness.
+    pub harness: Option<bool>,
+
+    /// Features required to build the target.
+    pub required_features: Vec<String>,
+}
+
+/// An editable `Cargo.toml`.
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    path: PathBuf,
+    original: String,
+    doc: DocumentMut,
+}
+
+impl Manifest {
+    /// Load a manifest.
+    ///
+    /// # Arguments
+    /// + `path`: Path to the `Cargo.toml`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let original = fs::read_to_string(path)?;
+        let doc = original.parse()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            original,
+            doc,
+        })
+    }
+
+    /// Path to the manifest.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The parsed manifest, including any edits.
+    pub fn document(&self) -> &DocumentMut {
+        &self.doc
+    }
+
+    /// Contents of the manifest, including any edits.
+    pub fn contents(&self) -> String {
+        self.doc.to_string()
+    }
+
+    /// Whether the manifest has been edited since it was loaded or last saved.
+    pub fn is_modified(&self) -> bool {
+        self.contents() != self.original
+    }
+
+    /// Unified diff of the edits since the manifest was loaded or last saved.
+    pub fn diff(&self) -> String {
+        let name = self.path.display();
+        diff::unified(&self.original, &self.contents(), &format!("a/{name}"), &format!("b/{name}"))
+    }
+
+    /// Write the manifest, if it was edited.
+    ///
+    /// # Returns
+    /// The 1-based lines inserted or changed.
+    pub fn save(&mut self) -> Result<Vec<usize>> {
+        if !self.is_modified() {
+            return Ok(Vec::new());
+        }
+
+        let contents = self.contents();
+        fs::write(&self.path, &contents)?;
+        let lines = diff::inserted_lines(&self.original, &contents);
+        self.original = contents;
+        Ok(lines)
+    }
+
+    /// Ensure a feature exists in the `[features]` table.
+    /// New features do not enable anything.
+    ///
+    /// # Returns
+    /// `true` if the feature was added.
+    pub fn ensure_feature(&mut self, name: &str) -> Result<bool> {
+        let features = table_mut(self.doc.as_table_mut(), "features")?;
+        if features.contains_key(name) {
+            return Ok(false);
+        }
+
+        features.insert(name, value(Array::new()));
+        Ok(true)
+    }
+
+    /// Ensure a crate is a dependency or a dev-dependency,
+    /// adding it to `[dev-dependencies]` if it is neither.
+    ///
+    /// # Arguments
+    /// + `name`: Name of the crate.
+    /// + `version`: Version requirement of an added dependency.
+    /// + `features`: Features of an added dependency.
+    ///
+    /// # Returns
+    /// `true` if the dependency was added.
+    pub fn ensure_dev_dependency(&mut self, name: &str, version: &str, features: &[&str]) -> Result<bool> {
+        let doc = self.doc.as_table();
+        let declared = ["dependencies", "dev-dependencies"]
+            .iter()
+            .any(|section| doc.get(section).and_then(Item::as_table_like).is_some_and(|deps| deps.contains_key(name)));
+
+        if declared {
+            return Ok(false);
+        }
+
+        let dependency = match features.is_empty() {
+            true => value(version),
+            false => {
+                let mut dependency = InlineTable::new();
+                dependency.insert("version", version.into());
+                dependency.insert("features", Array::from_iter(features.iter().copied()).into());
+                value(dependency)
+            },
+        };
+
+        table_mut(self.doc.as_table_mut(), "dev-dependencies")?.insert(name, dependency);
+        Ok(true)
+    }
+
+    /// Ensure a target exists.
+    /// If a target of the same kind and name exists, its properties are updated to include
+    /// those given, otherwise the target is appended.
+    ///
+    /// # Returns
+    /// `true` if the manifest was modified.
+    pub fn ensure_target(&mut self, kind: TargetKind, target: &Target) -> Result<bool> {
+        let section = kind.section();
+        let targets = self.doc
+            .entry(section)
+            .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+
+        let targets = match targets.as_array_of_tables_mut() {
+            Some(targets) => targets,
+            None => return Err(invalid(format!("`{section}` is not an array of tables"))),
+        };
+
+        let existing = targets
+            .iter_mut()
+            .find(|table| table.get("name").and_then(Item::as_str) == Some(target.name.as_str()));
+
+        // new targets are appended once filled in
+        let mut new_table = None;
+        let table = match existing {
+            Some(table) => table,
+            None => {
+                let mut table = Table::new();
+                table.insert("name", value(target.name.as_str()));
+                new_table.insert(table)
+            },
+        };
+
+        let before = table.to_string();
+        if let Some(path) = &target.path {
+            table.insert("path", value(path.as_str()));
+        }
+
+        if let Some(harness) = target.harness {
+            table.insert("harness", value(harness));
+        }
+
+        if !target.required_features.is_empty() {
+            let features = table
+                .entry("required-features")
+                .or_insert(value(Array::new()));
+
+            let features = match features.as_array_mut() {
+                Some(features) => features,
+                None => return Err(invalid(String::from("`required-features` is not an array"))),
+            };
+
+            for feature in &target.required_features {
+                if !features.iter().any(|f| f.as_str() == Some(feature.as_str())) {
+                    features.push(feature.as_str());
+                }
+            }
+        }
+
+        let modified = table.to_string() != before;
+        match new_table {
+            Some(table) => {
+                targets.push(table);
+                Ok(true)
+            },
+
+            None => Ok(modified),
+        }
+    }
+
+    /// Ensure a path is a member of the `[workspace]`.
+    ///
+    /// # Returns
+    /// `true` if the member was added.
+    pub fn ensure_workspace_member(&mut self, member: &str) -> Result<bool> {
+        let workspace = table_mut(self.doc.as_table_mut(), "workspace")?;
+        let members = workspace.entry("members").or_insert(value(Array::new()));
+        let members = match members.as_array_mut() {
+            Some(members) => members,
+            None => return Err(invalid(String::from("`workspace.members` is not an array"))),
+        };
+
+        if members.iter().any(|m| m.as_str() == Some(member)) {
+            return Ok(false);
+        }
+
+        members.push(member);
+        Ok(true)
+    }
+}
+
+/// Get a sub-table, creating it if it does not exist.
+fn table_mut<'a>(parent: &'a mut Table, key: &str) -> Result<&'a mut Table> {
+    let item = parent.entry(key).or_insert(Item::Table(Table::new()));
+    match item.as_table_mut() {
+        Some(table) => {
+            table.set_implicit(false);
+            Ok(table)
+        },
+
+        None => Err(invalid(format!("`{key}` is not a table"))),
+    }
+}
+
+/// Create an error for an unexpected manifest structure.
+fn invalid(msg: String) -> crate::result::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg).into()
+}
+
+#[cfg(test)]
+#[path = "./manifest_test.rs"]
+mod manifest_test;
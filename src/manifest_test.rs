@@ -0,0 +1,99 @@
+use super::*;
+
+const MANIFEST: &str = r#"[package]
+name = "demo" # keep this comment
+version = "0.1.0"
+
+[dependencies]
+regex = "1"
+"#;
+
+/// Write a manifest to a temporary directory and load it.
+fn load(content: &str) -> (tempfile::TempDir, Manifest) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Cargo.toml");
+    fs::write(&path, content).unwrap();
+    let manifest = Manifest::load(&path).unwrap();
+    (dir, manifest)
+}
+
+#[test]
+fn ensure_feature_should_preserve_formatting() {
+    let (_dir, mut manifest) = load(MANIFEST);
+    assert!(manifest.ensure_feature("telemetry").unwrap());
+    assert!(!manifest.ensure_feature("telemetry").unwrap());
+
+    assert_eq!(manifest.contents(), format!("{MANIFEST}\n[features]\ntelemetry = []\n"));
+}
+
+#[test]
+fn ensure_dev_dependency_should_skip_existing_dependencies() {
+    let (_dir, mut manifest) = load(MANIFEST);
+    assert!(!manifest.ensure_dev_dependency("regex", "1", &[]).unwrap());
+    assert!(manifest.ensure_dev_dependency("tokio", "1", &["macros", "rt"]).unwrap());
+    assert!(!manifest.ensure_dev_dependency("tokio", "1", &[]).unwrap());
+
+    assert_eq!(
+        manifest.contents(),
+        format!("{MANIFEST}\n[dev-dependencies]\ntokio = {{ version = \"1\", features = [\"macros\", \"rt\"] }}\n")
+    );
+}
+
+#[test]
+fn ensure_target_should_be_idempotent() {
+    let (_dir, mut manifest) = load(MANIFEST);
+    let bench = Target {
+        name: String::from("parse"),
+        path: Some(String::from("benches/parse.rs")),
+        harness: Some(false),
+        ..Target::default()
+    };
+
+    assert!(manifest.ensure_target(TargetKind::Bench, &bench).unwrap());
+    let contents = manifest.contents();
+    assert!(contents.ends_with(
+        "[[bench]]\nname = \"parse\"\npath = \"benches/parse.rs\"\nharness = false\n"
+    ));
+
+    assert!(!manifest.ensure_target(TargetKind::Bench, &bench).unwrap());
+    assert_eq!(manifest.contents(), contents);
+}
+
+#[test]
+fn ensure_target_should_add_required_features_to_existing_target() {
+    let (_dir, mut manifest) = load(&format!("{MANIFEST}\n[[example]]\nname = \"demo\"\n"));
+    let example = Target {
+        name: String::from("demo"),
+        required_features: vec![String::from("cli")],
+        ..Target::default()
+    };
+
+    assert!(manifest.ensure_target(TargetKind::Example, &example).unwrap());
+    assert!(manifest.contents().ends_with(
+        "[[example]]\nname = \"demo\"\nrequired-features = [\"cli\"]\n"
+    ));
+}
+
+#[test]
+fn ensure_workspace_member_should_append_member() {
+    let (_dir, mut manifest) = load("[workspace]\nmembers = [\"a\"]\n");
+    assert!(manifest.ensure_workspace_member("b").unwrap());
+    assert!(!manifest.ensure_workspace_member("a").unwrap());
+    assert_eq!(manifest.contents(), "[workspace]\nmembers = [\"a\", \"b\"]\n");
+}
+
+#[test]
+fn diff_and_save_should_reflect_edits() {
+    let (_dir, mut manifest) = load(MANIFEST);
+    assert!(!manifest.is_modified());
+    assert_eq!(manifest.diff(), "");
+
+    manifest.ensure_feature("telemetry").unwrap();
+    assert!(manifest.is_modified());
+    assert!(manifest.diff().contains("+[features]\n+telemetry = []\n"));
+    assert_eq!(fs::read_to_string(manifest.path()).unwrap(), MANIFEST);
+
+    assert_eq!(manifest.save().unwrap(), [7, 8, 9]);
+    assert!(!manifest.is_modified());
+    assert_eq!(fs::read_to_string(manifest.path()).unwrap(), manifest.contents());
+}
@@ -0,0 +1,69 @@
+//! Timings and counters of the steps of an operation, for tracking scaffolding performance.
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Time spent in each step of an operation, and how often each step ran.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Finding the project containing the module.
+    pub root_discovery: Step,
+
+    /// Rendering the contents of module and test files.
+    pub template_render: Step,
+
+    /// Declaring modules in their super files.
+    pub super_rewrite: Step,
+
+    /// Formatting created files with `rustfmt`.
+    pub format: Step,
+
+    /// Microseconds the whole operation took.
+    pub total_us: u64,
+}
+
+/// Time spent in a step, and how often it ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Step {
+    /// Microseconds spent in the step.
+    pub us: u64,
+
+    /// Number of times the step ran.
+    pub count: usize,
+}
+
+impl Step {
+    /// Record a run of the step.
+    pub fn record(&mut self, elapsed: Duration) {
+        self.us += u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.count += 1;
+    }
+
+    /// Run the step, recording how long it took.
+    pub fn time<T>(&mut self, step: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = step();
+        self.record(start.elapsed());
+        value
+    }
+
+    /// Add the runs of the same step in another operation.
+    fn merge(&mut self, other: &Self) {
+        self.us += other.us;
+        self.count += other.count;
+    }
+}
+
+impl Metrics {
+    /// Add the steps of another operation, e.g. of creating one module of a batch.
+    /// The total is kept.
+    pub fn merge(&mut self, other: &Self) {
+        self.root_discovery.merge(&other.root_discovery);
+        self.template_render.merge(&other.template_render);
+        self.super_rewrite.merge(&other.super_rewrite);
+        self.format.merge(&other.format);
+    }
+}
+
+#[cfg(test)]
+#[path = "./metrics_test.rs"]
+mod metrics_test;
@@ -0,0 +1,20 @@
+use super::*;
+
+#[test]
+fn merge_should_add_steps_and_keep_total() {
+    let mut metrics = Metrics { total_us: 10, ..Metrics::default() };
+    assert_eq!(metrics.format.time(|| 1), 1);
+    metrics.super_rewrite.record(Duration::from_micros(5));
+
+    let other = Metrics {
+        super_rewrite: Step { us: 3, count: 2 },
+        total_us: 100,
+        ..Metrics::default()
+    };
+
+    metrics.merge(&other);
+    assert_eq!(metrics.super_rewrite, Step { us: 8, count: 3 });
+    assert_eq!(metrics.format.count, 1);
+    assert_eq!(metrics.template_render, Step::default());
+    assert_eq!(metrics.total_us, 10);
+}
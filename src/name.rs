@@ -0,0 +1,103 @@
+//! Validation and sanitization of module names.
+use crate::result::{Error, Result};
+
+/// Keywords of every edition, including reserved ones.
+const KEYWORDS: [&str; 52] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+/// Keywords that can not be used as raw identifiers.
+const NON_RAW_KEYWORDS: [&str; 4] = ["crate", "self", "Self", "super"];
+
+/// Check if a name is a keyword.
+pub fn is_keyword(name: &str) -> bool {
+    KEYWORDS.contains(&name)
+}
+
+/// Check if a name is an identifier, ignoring keywords.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts = match chars.next() {
+        Some(first) => first == '_' || first.is_alphabetic(),
+        None => false,
+    };
+
+    starts && name != "_" && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+/// Ensure a name can be used as a module name.
+///
+/// # Errors
+/// + [`Error::InvalidModuleName`] if the name is not an identifier, or is a keyword.
+pub fn validate(name: &str) -> Result {
+    if !is_identifier(name) || is_keyword(name) {
+        return Err(Error::InvalidModuleName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Convert a name into a valid module name.
+///
+/// Words separated by `-`, whitespace, or other punctuation, and `camelCase` words,
+/// are joined in `snake_case`. Names starting with a digit are prefixed with `_`.
+/// Keywords are kept, and declared as raw identifiers, except those that can not be,
+/// which are suffixed with `_`.
+///
+/// # Errors
+/// + [`Error::InvalidModuleName`] if the name has no letters or digits.
+pub fn sanitize(name: &str) -> Result<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        let boundary = !c.is_alphanumeric() || (c.is_uppercase() && prev_lower);
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        if !c.is_alphanumeric() {
+            prev_lower = false;
+            continue;
+        }
+
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        word.extend(c.to_lowercase());
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    if words.is_empty() {
+        return Err(Error::InvalidModuleName(name.to_string()));
+    }
+
+    let mut sanitized = words.join("_");
+    if sanitized.starts_with(|c: char| c.is_numeric()) {
+        sanitized.insert(0, '_');
+    }
+
+    if NON_RAW_KEYWORDS.contains(&sanitized.as_str()) {
+        sanitized.push('_');
+    }
+
+    Ok(sanitized)
+}
+
+/// The identifier a module is declared with, a raw identifier for keywords.
+/// e.g. `r#type` for `type`.
+pub fn ident(name: &str) -> String {
+    match is_keyword(name) && !NON_RAW_KEYWORDS.contains(&name) {
+        true => format!("r#{name}"),
+        false => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "./name_test.rs"]
+mod name_test;
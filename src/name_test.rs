@@ -0,0 +1,27 @@
+use super::*;
+
+#[test]
+fn validate_should_reject_non_identifiers_and_keywords() {
+    for name in ["my_mod", "_private", "mod2", "größe"] {
+        assert!(validate(name).is_ok(), "{name}");
+    }
+
+    for name in ["my-mod", "my mod", "2mod", "_", "", "type", "self", "gen"] {
+        assert!(matches!(validate(name), Err(Error::InvalidModuleName(_))), "{name}");
+    }
+}
+
+#[test]
+fn sanitize_should_convert_to_snake_case() {
+    assert_eq!(sanitize("my-module").unwrap(), "my_module");
+    assert_eq!(sanitize("My Module").unwrap(), "my_module");
+    assert_eq!(sanitize("httpClient").unwrap(), "http_client");
+    assert_eq!(sanitize("--a--b--").unwrap(), "a_b");
+    assert_eq!(sanitize("2fa").unwrap(), "_2fa");
+    assert_eq!(sanitize("type").unwrap(), "type");
+    assert_eq!(sanitize("self").unwrap(), "self_");
+    assert!(sanitize("--").is_err());
+
+    assert_eq!(ident("type"), "r#type");
+    assert_eq!(ident("self_"), "self_");
+}
@@ -0,0 +1,116 @@
+//! Detection and registration of modules missing from their super.
+use crate::creator::DiskFileSystem;
+use crate::result::Result;
+use crate::tree::Tree;
+use crate::{git, project, SuperOptions};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Find the module files in a source tree that are not declared in their super.
+/// Modules are declared if they are part of the crate's module tree, so modules loaded with `#[path]` are declared.
+///
+/// # Arguments
+/// + `src`: Source directory to search.
+/// + `src_root`: Source root, relative to the crate root, or `None` for `src`.
+/// + `changed_since`: Only consider files added since this git commit, or `None` for all files.
+///
+/// # Returns
+/// Files of the undeclared modules, sorted.
+///
+/// # Errors
+/// + If the crate's module tree can not be read.
+/// + If the files added since `changed_since` can not be determined.
+pub fn find(src: &Path, src_root: Option<&Path>, changed_since: Option<&str>) -> Result<Vec<PathBuf>> {
+    let mut files = project::module_files(src)?;
+    if let Some(reference) = changed_since {
+        let added = git::added_since(src, reference)?;
+        files.retain(|file| added.contains(file));
+    }
+
+    let declared = declared_files(src, src_root)?;
+    files.retain(|file| !declared.contains(file));
+    Ok(files)
+}
+
+/// Declare a module in its super.
+///
+/// # Arguments
+/// + `file`: File of the module.
+/// + `options`: Options for adding the module.
+///
+/// # Returns
+/// Path to the modified super file.
+pub fn register(file: &Path, options: &SuperOptions) -> Result<PathBuf> {
+    let mod_path = crate::module_path(file);
+    let super_file = crate::super_path(&DiskFileSystem, &mod_path, options.main, options.src_root.as_deref(), options.target.as_deref())?;
+    crate::add_to_super(&mod_path, options)?;
+    Ok(super_file)
+}
+
+/// Declare an existing module file in its super, without modifying the file.
+///
+/// # Arguments
+/// + `path`: File of the module, or the directory of a directory module.
+/// + `options`: Options for adding the module.
+///
+/// # Returns
+/// Path to the modified super file.
+///
+/// # Errors
+/// + If the module file does not exist, or is not a Rust file.
+/// + If the module is already declared in its super.
+pub fn adopt(path: &Path, options: &SuperOptions) -> Result<PathBuf> {
+    let file = match path.is_dir() {
+        true => path.join("mod.rs"),
+        false => path.to_path_buf(),
+    };
+
+    if !file.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("module file `{}` does not exist", file.display()),
+        ).into());
+    }
+
+    if file.extension().is_none_or(|ext| ext != "rs") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` is not a Rust file", file.display()),
+        ).into());
+    }
+
+    if is_declared(&file, options.src_root.as_deref())? {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("module `{}` is already declared in its super", file.display()),
+        ).into());
+    }
+
+    register(&file, options)
+}
+
+/// Check if a module is part of its crate's module tree.
+///
+/// # Arguments
+/// + `file`: File of the module.
+/// + `src_root`: Source root, relative to the crate root, or `None` for `src`.
+fn is_declared(file: &Path, src_root: Option<&Path>) -> Result<bool> {
+    let file = project::absolute_path(file)?;
+    Ok(declared_files(&file, src_root)?.contains(&file))
+}
+
+/// Get the files of the modules in the module tree of the crate containing a path.
+///
+/// # Arguments
+/// + `path`: Path inside of the crate.
+/// + `src_root`: Source root, relative to the crate root, or `None` for `src`.
+fn declared_files(path: &Path, src_root: Option<&Path>) -> Result<HashSet<PathBuf>> {
+    let project = crate::project(&DiskFileSystem, path, src_root)?;
+    let tree = Tree::read(&project)?;
+    Ok(tree.files().into_iter().map(Path::to_path_buf).collect())
+}
+
+#[cfg(test)]
+#[path = "./orphans_test.rs"]
+mod orphans_test;
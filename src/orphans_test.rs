@@ -0,0 +1,89 @@
+use super::*;
+use crate::fixtures::project;
+use crate::visibility::Visibility;
+use std::fs;
+use std::process::Command;
+
+/// Modules `a`, declared, and `b`, undeclared, and a directory module `c`, declared under a `cfg`.
+const FILES: [(&str, &str); 6] = [
+    ("Cargo.toml", "[package]\nname = \"a\"\n"),
+    ("src/lib.rs", "pub mod a;\n#[cfg(unix)]\nmod c;\n"),
+    ("src/a.rs", ""),
+    ("src/b.rs", ""),
+    ("src/c/mod.rs", ""),
+    ("src/c/d.rs", ""),
+];
+
+#[test]
+fn find_should_return_undeclared_modules() {
+    let (_dir, root) = project(&FILES);
+    let orphans = find(&root.join("src"), None, None).unwrap();
+    assert_eq!(orphans, vec![root.join("src/b.rs"), root.join("src/c/d.rs")]);
+}
+
+#[test]
+fn register_should_declare_module() {
+    let (_dir, root) = project(&FILES);
+    let super_file = register(&root.join("src/c/d.rs"), &SuperOptions::default()).unwrap();
+    assert_eq!(super_file, root.join("src/c/mod.rs"));
+    assert_eq!(fs::read_to_string(&super_file).unwrap(), "pub mod d;\n");
+    assert_eq!(find(&root.join("src"), None, None).unwrap(), vec![root.join("src/b.rs")]);
+}
+
+#[test]
+fn changed_since_should_limit_to_added_files() {
+    let (_dir, root) = project(&FILES);
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(["-c", "user.name=mkmod", "-c", "user.email=mkmod@example.com"])
+            .args(args)
+            .current_dir(&root)
+            .output()
+            .unwrap();
+    };
+
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    fs::write(root.join("src/e.rs"), "").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "e"]);
+
+    let orphans = find(&root.join("src"), None, Some("HEAD~1")).unwrap();
+    assert_eq!(orphans, vec![root.join("src/e.rs")]);
+}
+
+#[test]
+fn adopt_should_declare_only_undeclared_files() {
+    let (_dir, root) = project(&FILES);
+    let options = SuperOptions {
+        vis: Some(Visibility::Crate),
+        ..SuperOptions::default()
+    };
+
+    let super_file = adopt(&root.join("src/b.rs"), &options).unwrap();
+    assert_eq!(super_file, root.join("src/lib.rs"));
+    assert_eq!(
+        fs::read_to_string(&super_file).unwrap(),
+        "pub mod a;\n#[cfg(unix)]\nmod c;\npub(crate) mod b;\n"
+    );
+    assert_eq!(fs::read_to_string(root.join("src/b.rs")).unwrap(), "");
+
+    assert!(adopt(&root.join("src/a.rs"), &options).is_err());
+    assert!(adopt(&root.join("src/c"), &options).is_err());
+    assert!(adopt(&root.join("src/z.rs"), &options).is_err());
+}
+
+#[test]
+fn modules_loaded_with_path_should_be_declared() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    fs::create_dir_all(root.join("src/other")).unwrap();
+    fs::write(root.join("src/main.rs"), "#[path = \"other/bar.rs\"]\nmod bar;\n\nfn main() {}\n").unwrap();
+    fs::write(root.join("src/other/bar.rs"), "").unwrap();
+
+    assert!(find(&root.join("src"), None, None).unwrap().is_empty());
+    assert!(register(&root.join("src/other/bar.rs"), &SuperOptions::default()).is_err());
+    assert_eq!(fs::read_to_string(root.join("src/other/bar.rs")).unwrap(), "");
+}
@@ -0,0 +1,155 @@
+//! Plans saved to be applied later.
+use crate::creator::{DiskFileSystem, ModuleCreator};
+use crate::result::{Error, Result};
+use crate::{ContentOptions, Created, Plan, SuperOptions, TestOptions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A plan along with everything needed to apply it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlanFile {
+    /// The planned operation.
+    pub plan: Plan,
+
+    /// Options for the module's contents.
+    pub content: ContentOptions,
+
+    /// Options for the test module, if one will be created.
+    pub test: Option<TestOptions>,
+
+    /// Options for adding the module to its super, if it will be added.
+    pub add_to_super: Option<SuperOptions>,
+
+    /// Options for the contents of the module's missing ancestors, if they will be created.
+    #[serde(default)]
+    pub parents: Option<ContentOptions>,
+
+    /// SHA-256 hash of each file the plan touches when the plan was made,
+    /// or `None` if the file did not exist.
+    pub hashes: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl PlanFile {
+    /// Record a plan along with the current state of the files it touches.
+    ///
+    /// # Arguments
+    /// + `plan`: Plan of the operation.
+    /// + `content`: Options for the module's contents.
+    /// + `test`: Options for the test module, or `None` to not create one.
+    /// + `add_to_super`: Options for adding the module to its super, or `None` to not add it.
+    pub fn new(
+        plan: Plan,
+        content: ContentOptions,
+        test: Option<TestOptions>,
+        add_to_super: Option<SuperOptions>,
+    ) -> Result<Self> {
+        let mut hashes = BTreeMap::new();
+        for path in plan.paths() {
+            hashes.insert(path.to_path_buf(), hash(path)?);
+        }
+
+        Ok(Self {
+            plan,
+            content,
+            test,
+            add_to_super,
+            parents: None,
+            hashes,
+        })
+    }
+
+    /// Create the module's missing ancestors when the plan is applied.
+    pub fn parents(mut self, content: ContentOptions) -> Self {
+        self.parents = Some(content);
+        self
+    }
+
+    /// Load a saved plan.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the plan.
+    pub fn save(&self, path: &Path) -> Result {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Ensure none of the files the plan touches changed since it was made.
+    ///
+    /// # Errors
+    /// + [`Error::Changed`] with the first file that changed.
+    pub fn verify(&self) -> Result {
+        for (path, expected) in &self.hashes {
+            if hash(path)? != *expected {
+                return Err(Error::Changed(path.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the plan, after verifying none of the files it touches changed.
+    ///
+    /// # Returns
+    /// Files created or modified.
+    ///
+    /// # Errors
+    /// + If a file changed since the plan was made.
+    /// + See [`CreateOptions::create`](crate::CreateOptions::create).
+    pub fn apply(&self) -> Result<Created> {
+        self.verify()?;
+
+        let path = if self.plan.dir {
+            self.plan.module_path.clone()
+        } else {
+            self.plan.module_path.with_extension("")
+        };
+
+        ModuleCreator::new(&mut DiskFileSystem).transaction(|creator| {
+            let parents = match &self.parents {
+                Some(content) => creator.create_parents(&path, content, self.add_to_super.as_ref())?,
+                None => Vec::new(),
+            };
+
+            let mut created = creator.create_module(
+                &path,
+                self.plan.dir,
+                &self.content,
+                self.test.as_ref(),
+                self.add_to_super.as_ref(),
+            )?;
+
+            created.parents = parents;
+            Ok(created)
+        })
+    }
+}
+
+/// Get the SHA-256 hash of a file as a hex string, or `None` if it does not exist.
+/// Directories hash to an empty string.
+fn hash(path: &Path) -> Result<Option<String>> {
+    if path.is_dir() {
+        return Ok(Some(String::new()));
+    }
+
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let digest = Sha256::digest(&content);
+    Ok(Some(digest.iter().map(|byte| format!("{byte:02x}")).collect()))
+}
+
+#[cfg(test)]
+#[path = "./plan_file_test.rs"]
+mod plan_file_test;
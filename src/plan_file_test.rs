@@ -0,0 +1,56 @@
+use super::*;
+use crate::fixtures::project;
+use crate::CreateOptions;
+
+/// Plan the creation of `src/my_mod` with a test, added to super.
+fn plan_file(root: &Path) -> PlanFile {
+    let test = TestOptions::default();
+    let options = SuperOptions::default();
+    let plan = crate::plan(&root.join("src/my_mod"), false, Some(&test), Some(&options)).unwrap();
+    PlanFile::new(plan, ContentOptions::default(), Some(test), Some(options)).unwrap()
+}
+
+#[test]
+fn saved_plan_should_apply() {
+    let (_dir, root) = project(&[]);
+    let path = root.join("plan.json");
+    plan_file(&root).save(&path).unwrap();
+    assert!(!root.join("src/my_mod.rs").exists());
+
+    let created = PlanFile::load(&path).unwrap().apply().unwrap();
+    assert_eq!(created.module_path, root.join("src/my_mod.rs"));
+    assert!(root.join("src/my_mod_test.rs").exists());
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub mod my_mod;\n");
+}
+
+#[test]
+fn changed_files_should_prevent_apply() {
+    let (_dir, root) = project(&[]);
+    let plan = plan_file(&root);
+    fs::write(root.join("src/lib.rs"), "fn f() {}\n").unwrap();
+
+    let res = plan.apply();
+    assert!(matches!(res, Err(Error::Changed(path)) if path == root.join("src/lib.rs")));
+    assert!(!root.join("src/my_mod.rs").exists());
+}
+
+#[test]
+fn plan_with_missing_parents_should_create_them_when_applied() {
+    let (_dir, root) = project(&[]);
+    let options = CreateOptions::new(root.join("src/a/b/c")).with_test(false).parents(ContentOptions::default());
+    let plan = options.plan().unwrap();
+    assert_eq!(plan.super_file, Some(root.join("src/a/b/mod.rs")));
+    assert!(plan.parents.contains(&root.join("src/a/mod.rs")));
+    assert!(plan.parents.contains(&root.join("src/lib.rs")));
+    assert!(!root.join("src/a").exists());
+
+    let path = root.join("plan.json");
+    let plan_file = PlanFile::new(plan, ContentOptions::default(), None, Some(SuperOptions::default())).unwrap();
+    plan_file.parents(ContentOptions::default()).save(&path).unwrap();
+
+    let created = PlanFile::load(&path).unwrap().apply().unwrap();
+    assert_eq!(created.parents.len(), 2);
+    assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub mod a;\n");
+    assert_eq!(fs::read_to_string(root.join("src/a/mod.rs")).unwrap(), "pub mod b;\n");
+    assert_eq!(fs::read_to_string(root.join("src/a/b/mod.rs")).unwrap(), "pub mod c;\n");
+}
@@ -0,0 +1,677 @@
+//! Project layout discovery.
+use crate::creator::{DiskFileSystem, FileSystem};
+use crate::result::{Error, Result};
+use std::path::{Component, Path, PathBuf};
+use std::io;
+
+/// Kind of a Cargo target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    /// The package's library.
+    Lib,
+
+    /// A binary.
+    Bin,
+}
+
+/// A library or binary target of a package.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    /// Name of the target.
+    pub name: String,
+
+    /// Whether the target is the library or a binary.
+    pub kind: TargetKind,
+
+    /// Root module of the target. e.g. `src/lib.rs`.
+    pub root: PathBuf,
+}
+
+/// Layout of the Cargo project containing a module.
+#[derive(Clone, Debug)]
+pub struct Project {
+    /// Directory containing the project's `Cargo.toml`.
+    pub root: PathBuf,
+
+    /// Source directory of the project.
+    pub src: PathBuf,
+}
+
+impl Project {
+    /// Find the project containing the given path.
+    /// The project is the nearest package, so virtual workspace manifests are skipped.
+    ///
+    /// # Arguments
+    /// + `path`: Path inside the project. Does not need to exist, but one of its ancestors must.
+    ///
+    /// # Errors
+    /// + If no package's `Cargo.toml` is found in any of the path's ancestors.
+    pub fn discover(path: &Path) -> Result<Self> {
+        Self::discover_in(&DiskFileSystem, path)
+    }
+
+    /// Find the project containing the given path in a file system. See [`Self::discover`].
+    pub fn discover_in(fs: &dyn FileSystem, path: &Path) -> Result<Self> {
+        let path = absolute_path_in(fs, path)?;
+        for dir in path.ancestors() {
+            if is_package_root_in(fs, dir) {
+                return Ok(Self::from_root(dir));
+            }
+        }
+
+        Err(Error::NotInCargoProject(path.to_path_buf()))
+    }
+
+    /// Select the package modules should be created in, when working from a directory.
+    ///
+    /// If a package is named, it is found among the members of the workspace containing `dir`.
+    /// Otherwise, if `dir` is the root of a workspace, the package is inferred as cargo would:
+    /// from the workspace's `default-members` if set, else the root package.
+    ///
+    /// # Arguments
+    /// + `dir`: Directory being worked from.
+    /// + `package`: Name of the package, or `None` to infer it.
+    ///
+    /// # Returns
+    /// The selected package, or `None` if no package is named and `dir` is not the root of a
+    /// workspace with `default-members` or is the root package, in which case paths should be
+    /// used as given.
+    ///
+    /// # Errors
+    /// + If the named package is not found.
+    /// + If the package can not be inferred because the workspace does not have exactly one
+    ///   default member.
+    pub fn select_package(dir: &Path, package: Option<&str>) -> Result<Option<Self>> {
+        let dir = absolute_path(dir)?;
+        let manifests = dir
+            .ancestors()
+            .filter(|dir| dir.join("Cargo.toml").is_file())
+            .map(|dir| Ok((dir.to_path_buf(), read_manifest(&DiskFileSystem, dir)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let workspace = manifests.iter().find(|(_, manifest)| manifest.contains_key("workspace"));
+        let Some(package) = package else {
+            // infer package only when working from a workspace root
+            let (root, manifest) = match manifests.first() {
+                Some((root, manifest)) if manifest.contains_key("workspace") => (root, manifest),
+                _ => return Ok(None),
+            };
+
+            let default_members = expand_members(&DiskFileSystem, root, manifest, "default-members")?;
+            return match (default_members.as_slice(), manifest.contains_key("package")) {
+                ([member], _) => Ok(Some(Self::from_root(&normalize_path(member)))),
+                ([], true) => Ok(None),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "package could not be inferred from the workspace's default members, use --package"
+                ).into()),
+            };
+        };
+
+        let candidates = match workspace {
+            Some((root, manifest)) => {
+                let mut candidates = workspace_members(&DiskFileSystem, root, manifest)?;
+                candidates.push(root.clone());
+                candidates
+            },
+
+            None => manifests.iter().map(|(root, _)| root.clone()).take(1).collect(),
+        };
+
+        for candidate in candidates {
+            let name = read_manifest(&DiskFileSystem, &candidate)?
+                .get("package")
+                .and_then(|package| package.get("name"))
+                .and_then(toml::Value::as_str)
+                .map(String::from);
+
+            if name.as_deref() == Some(package) {
+                return Ok(Some(Self::from_root(&candidate)));
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound, format!("package `{package}` could not be found")
+        ).into())
+    }
+
+    /// Create the project for a crate root.
+    fn from_root(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            src: root.join("src"),
+        }
+    }
+
+    /// Use a source directory other than `src`.
+    ///
+    /// # Arguments
+    /// + `src_root`: Source directory, relative to the crate root. e.g. `rust/src`.
+    pub fn with_src_root(mut self, src_root: &Path) -> Self {
+        self.src = self.root.join(src_root);
+        self
+    }
+
+    /// Path to the project's `Cargo.toml`.
+    pub fn manifest_path(&self) -> PathBuf {
+        self.root.join("Cargo.toml")
+    }
+
+    /// Read the project's `Cargo.toml`.
+    pub fn manifest(&self) -> Result<toml::Table> {
+        self.manifest_in(&DiskFileSystem)
+    }
+
+    /// Read the project's `Cargo.toml` from a file system.
+    pub fn manifest_in(&self, fs: &dyn FileSystem) -> Result<toml::Table> {
+        read_manifest(fs, &self.root)
+    }
+
+    /// Get the project's edition.
+    ///
+    /// # Returns
+    /// The edition declared in the manifest, `2015` if none is declared,
+    /// or `None` if it is inherited from the workspace.
+    pub fn edition(&self) -> Result<Option<String>> {
+        let manifest = self.manifest()?;
+        let edition = manifest.get("package").and_then(|package| package.get("edition"));
+        let edition = match edition {
+            None => Some(String::from("2015")),
+            Some(toml::Value::String(edition)) => Some(edition.clone()),
+            Some(_) => None,
+        };
+
+        Ok(edition)
+    }
+
+    /// Get the project's minimum supported Rust version.
+    ///
+    /// # Returns
+    /// The `rust-version` declared in the manifest, or `None` if none is declared
+    /// or it is inherited from the workspace.
+    pub fn rust_version(&self) -> Result<Option<String>> {
+        self.rust_version_in(&DiskFileSystem)
+    }
+
+    /// Get the project's minimum supported Rust version from a file system. See [`Self::rust_version`].
+    pub fn rust_version_in(&self, fs: &dyn FileSystem) -> Result<Option<String>> {
+        let manifest = self.manifest_in(fs)?;
+        let version = manifest
+            .get("package")
+            .and_then(|package| package.get("rust-version"))
+            .and_then(toml::Value::as_str)
+            .map(String::from);
+
+        Ok(version)
+    }
+
+    /// Get the name of the crate, with `-` replaced by `_`.
+    ///
+    /// # Returns
+    /// `None` if the manifest has no package, e.g. a virtual workspace manifest.
+    pub fn crate_name(&self) -> Result<Option<String>> {
+        self.crate_name_in(&DiskFileSystem)
+    }
+
+    /// Get the name of the crate from a file system. See [`Self::crate_name`].
+    pub fn crate_name_in(&self, fs: &dyn FileSystem) -> Result<Option<String>> {
+        let manifest = self.manifest_in(fs)?;
+        let name = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+            .map(|name| name.replace('-', "_"));
+
+        Ok(name)
+    }
+
+    /// Get the name of the project's first author, without their email.
+    /// e.g. `Jane Doe` for `Jane Doe <jane@example.com>`.
+    ///
+    /// # Returns
+    /// `None` if the manifest lists no authors, or inherits them from the workspace.
+    pub fn author(&self) -> Result<Option<String>> {
+        self.author_in(&DiskFileSystem)
+    }
+
+    /// Get the name of the project's first author from a file system. See [`Self::author`].
+    pub fn author_in(&self, fs: &dyn FileSystem) -> Result<Option<String>> {
+        let manifest = self.manifest_in(fs)?;
+        let author = manifest
+            .get("package")
+            .and_then(|package| package.get("authors"))
+            .and_then(toml::Value::as_array)
+            .and_then(|authors| authors.first())
+            .and_then(toml::Value::as_str)
+            .map(|author| author.split('<').next().unwrap_or(author).trim().to_string())
+            .filter(|author| !author.is_empty());
+
+        Ok(author)
+    }
+
+    /// Get the library and binary targets of the project, as `cargo` determines them.
+    ///
+    /// The library is `[lib] path`, or `src/lib.rs` if it exists.
+    /// Binaries are the `[[bin]]` targets, and unless `autobins = false`,
+    /// `src/main.rs`, `src/bin/<name>.rs`, and `src/bin/<name>/main.rs` if they exist.
+    /// Targets are found relative to the crate root, not the configured source directory.
+    pub fn targets(&self) -> Result<Vec<Target>> {
+        self.targets_in(&DiskFileSystem)
+    }
+
+    /// Get the library and binary targets of the project in a file system. See [`Self::targets`].
+    pub fn targets_in(&self, fs: &dyn FileSystem) -> Result<Vec<Target>> {
+        let manifest = self.manifest_in(fs)?;
+        let package = manifest.get("package");
+        let package_name = package
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+            .map(String::from)
+            .or_else(|| self.root.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        let src = self.root.join("src");
+        let mut targets = Vec::new();
+
+        // library
+        let lib = manifest.get("lib");
+        let lib_root = match lib.and_then(|lib| lib.get("path")).and_then(toml::Value::as_str) {
+            Some(path) => Some(self.root.join(path)),
+            None => Some(src.join("lib.rs")).filter(|file| fs.is_file(file)),
+        };
+
+        if let Some(root) = lib_root {
+            let name = lib
+                .and_then(|lib| lib.get("name"))
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+                .unwrap_or_else(|| package_name.replace('-', "_"));
+
+            targets.push(Target { name, kind: TargetKind::Lib, root: normalize_path(&root) });
+        }
+
+        // binaries
+        let bins = manifest
+            .get("bin")
+            .and_then(toml::Value::as_array)
+            .map(|bins| bins.as_slice())
+            .unwrap_or_default();
+
+        for bin in bins {
+            let Some(name) = bin.get("name").and_then(toml::Value::as_str) else {
+                continue;
+            };
+
+            let root = match bin.get("path").and_then(toml::Value::as_str) {
+                Some(path) => Some(self.root.join(path)),
+                None => {
+                    let mut candidates = vec![
+                        src.join("bin").join(format!("{name}.rs")),
+                        src.join("bin").join(name).join("main.rs"),
+                    ];
+
+                    if name == package_name {
+                        candidates.push(src.join("main.rs"));
+                    }
+
+                    candidates.into_iter().find(|file| fs.is_file(file))
+                },
+            };
+
+            if let Some(root) = root {
+                let name = name.to_string();
+                targets.push(Target { name, kind: TargetKind::Bin, root: normalize_path(&root) });
+            }
+        }
+
+        let autobins = package
+            .and_then(|package| package.get("autobins"))
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(true);
+
+        if autobins {
+            let mut candidates = vec![(package_name, src.join("main.rs"))];
+            if let Ok(entries) = fs.read_dir(&src.join("bin")) {
+                for path in entries {
+                    if fs.is_dir(&path) {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                        candidates.push((name, path.join("main.rs")));
+                    } else if path.extension().is_some_and(|ext| ext == "rs") {
+                        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                        candidates.push((name, path));
+                    }
+                }
+            }
+
+            for (name, root) in candidates {
+                let exists = targets
+                    .iter()
+                    .any(|target| target.kind == TargetKind::Bin && (target.name == name || target.root == root));
+
+                if fs.is_file(&root) && !exists {
+                    targets.push(Target { name, kind: TargetKind::Bin, root });
+                }
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Get the crate's root module.
+    ///
+    /// # Returns
+    /// `lib.rs` if it exists, otherwise `main.rs` if it exists.
+    pub fn root_module(&self) -> Option<PathBuf> {
+        ["lib.rs", "main.rs"]
+            .iter()
+            .map(|file| self.src.join(file))
+            .find(|file| file.exists())
+    }
+}
+
+/// Check if a directory is the root of a package,
+/// i.e. it contains a `Cargo.toml` that is not a virtual workspace manifest.
+/// Manifests that can not be read are assumed to belong to a package.
+pub fn is_package_root(dir: &Path) -> bool {
+    is_package_root_in(&DiskFileSystem, dir)
+}
+
+/// Check if a directory in a file system is the root of a package. See [`is_package_root`].
+pub fn is_package_root_in(fs: &dyn FileSystem, dir: &Path) -> bool {
+    if !fs.is_file(&dir.join("Cargo.toml")) {
+        return false;
+    }
+
+    match read_manifest(fs, dir) {
+        Ok(manifest) => manifest.contains_key("package") || !manifest.contains_key("workspace"),
+        Err(_) => true,
+    }
+}
+
+/// Read the `Cargo.toml` in a directory.
+fn read_manifest(fs: &dyn FileSystem, dir: &Path) -> Result<toml::Table> {
+    let content = fs.read_to_string(&dir.join("Cargo.toml"))?;
+    Ok(content.parse()?)
+}
+
+/// Get the member directories of a workspace, as cargo resolves them.
+/// Members are glob patterns expanded to the directories they match with a `Cargo.toml`.
+/// Directories under a path in `workspace.exclude` are excluded, unless listed as a member
+/// explicitly.
+///
+/// # Arguments
+/// + `fs`: File system the workspace is in.
+/// + `root`: Workspace root.
+/// + `manifest`: Workspace manifest.
+fn workspace_members(fs: &dyn FileSystem, root: &Path, manifest: &toml::Table) -> Result<Vec<PathBuf>> {
+    let paths = |key| {
+        manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get(key))
+            .and_then(toml::Value::as_array)
+            .map(|paths| paths.iter().filter_map(toml::Value::as_str).map(|path| root.join(path)).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    let explicit = paths("members");
+    let exclude = paths("exclude");
+    let mut members = expand_members(fs, root, manifest, "members")?;
+    members.retain(|member| {
+        explicit.iter().any(|path| member.starts_with(path))
+            || !exclude.iter().any(|path| member.starts_with(path))
+    });
+
+    Ok(members)
+}
+
+/// Expand the glob patterns of a workspace's member list.
+///
+/// # Arguments
+/// + `fs`: File system the workspace is in.
+/// + `root`: Workspace root.
+/// + `manifest`: Workspace manifest.
+/// + `key`: Key of the list in the `[workspace]` table. e.g. `members` or `default-members`.
+///
+/// # Returns
+/// Directories matched by the patterns that contain a `Cargo.toml`, sorted.
+fn expand_members(fs: &dyn FileSystem, root: &Path, manifest: &toml::Table, key: &str) -> Result<Vec<PathBuf>> {
+    let patterns = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get(key))
+        .and_then(toml::Value::as_array)
+        .map(|members| members.iter().filter_map(toml::Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut members = Vec::new();
+    for pattern in patterns {
+        let components = pattern.split(['/', '\\']).filter(|c| !c.is_empty() && *c != ".").collect::<Vec<_>>();
+        expand_glob(fs, root, &components, &mut members)?;
+    }
+
+    members.retain(|member| fs.is_file(&member.join("Cargo.toml")));
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+/// Collect the directories matching the remaining components of a glob pattern.
+/// Components may contain `*`, `?`, and `[...]` wildcards, and `**` matches any number of
+/// directories.
+///
+/// # Arguments
+/// + `fs`: File system to search.
+/// + `dir`: Directory matched so far.
+/// + `components`: Remaining components of the pattern.
+/// + `matches`: Collected directories.
+fn expand_glob(fs: &dyn FileSystem, dir: &Path, components: &[&str], matches: &mut Vec<PathBuf>) -> Result {
+    let Some((component, rest)) = components.split_first() else {
+        matches.push(dir.to_path_buf());
+        return Ok(());
+    };
+
+    if !component.contains(['*', '?', '[']) {
+        return expand_glob(fs, &dir.join(component), rest, matches);
+    }
+
+    if !fs.is_dir(dir) {
+        return Ok(());
+    }
+
+    let mut entries = fs.read_dir(dir)?;
+    entries.retain(|entry| fs.is_dir(entry));
+    entries.sort();
+    if *component == "**" {
+        expand_glob(fs, dir, rest, matches)?;
+        for entry in entries {
+            expand_glob(fs, &entry, components, matches)?;
+        }
+
+        return Ok(());
+    }
+
+    let pattern = glob_regex(component);
+    for entry in entries {
+        let name = entry.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if pattern.is_match(name) {
+            expand_glob(fs, &entry, rest, matches)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a glob pattern for a single path component to a regex.
+fn glob_regex(pattern: &str) -> regex::Regex {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let mut class = String::new();
+                let negated = chars.next_if_eq(&'!').is_some();
+                if negated {
+                    class.push('^');
+                }
+
+                let mut raw = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' && !raw.is_empty() {
+                        closed = true;
+                        break;
+                    }
+
+                    if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                        class.push('\\');
+                    }
+                    class.push(c);
+                    raw.push(c);
+                }
+
+                // an unclosed class is matched literally
+                if closed {
+                    regex.push_str(&format!("[{class}]"));
+                } else {
+                    let negation = if negated { "!" } else { "" };
+                    regex.push_str(&regex::escape(&format!("[{negation}{raw}")));
+                }
+            },
+
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex::Regex::new(&regex).expect("glob pattern should be a valid regex")
+}
+
+/// Get the module files of a source tree, excluding crate roots and test files.
+///
+/// # Arguments
+/// + `src`: Source directory.
+///
+/// # Returns
+/// Module files, sorted.
+pub fn module_files(src: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_module_files(src, src, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Collect the module files of a directory in a source tree.
+///
+/// # Arguments
+/// + `src`: Source directory.
+/// + `dir`: Directory being searched.
+/// + `files`: Collected files.
+fn collect_module_files(src: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_module_files(src, &path, files)?;
+            continue;
+        }
+
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let is_crate_root = (dir == src && (name == "lib.rs" || name == "main.rs"))
+            || dir == src.join("bin")
+            || (dir.parent() == Some(&src.join("bin")) && name == "main.rs");
+
+        if name.ends_with(".rs") && !name.ends_with("_test.rs") && !is_crate_root {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the canonical path of a path that may not exist.
+///
+/// The deepest existing ancestor is canonicalized and the remaining components are appended.
+///
+/// # Errors
+/// + If none of the path's ancestors exist.
+pub fn absolute_path(path: &Path) -> Result<PathBuf> {
+    absolute_path_in(&DiskFileSystem, path)
+}
+
+/// Get the canonical path of a path in a file system. See [`absolute_path`].
+pub fn absolute_path_in(fs: &dyn FileSystem, path: &Path) -> Result<PathBuf> {
+    let path = if path.is_relative() {
+        fs.current_dir()?.join(path)
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut tail = Vec::new();
+    let mut existing = path.as_path();
+    while !fs.exists(existing) {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                tail.push(name.to_os_string());
+                existing = parent;
+            },
+
+            _ => return Err(io::Error::new(
+                io::ErrorKind::NotFound, "path has no existing ancestor"
+            ).into()),
+        }
+    }
+
+    let mut abs_path = fs.canonicalize(existing)?;
+    for name in tail.into_iter().rev() {
+        abs_path.push(name);
+    }
+
+    Ok(abs_path)
+}
+
+/// Lexically resolve `.` and `..` components of a path.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            },
+
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Get the relative path from a directory to a path, using `/` as the separator.
+///
+/// # Arguments
+/// + `from`: Absolute path of the directory the result is relative to.
+/// + `to`: Absolute path of the target.
+pub fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec![String::from(".."); from.len() - common];
+    if parts.is_empty() {
+        parts.push(String::from("."));
+    }
+
+    for component in &to[common..] {
+        parts.push(component.as_os_str().to_string_lossy().into_owned());
+    }
+
+    parts.join("/")
+}
+
+#[cfg(test)]
+#[path = "./project_test.rs"]
+mod project_test;
@@ -0,0 +1,195 @@
+use super::*;
+use std::fs;
+
+#[test]
+fn discover_should_find_crate_root() {
+    let (_dir, root) = crate::fixtures::project(&[]);
+    fs::create_dir(root.join("src/a")).unwrap();
+
+    let project = Project::discover(&root.join("src/a/b")).unwrap();
+    assert_eq!(project.root, root);
+    assert_eq!(project.src, root.join("src"));
+}
+
+#[test]
+fn relative_path_should_walk_up_and_down() {
+    let from = Path::new("/crate/src/a");
+    assert_eq!(relative_path(from, Path::new("/crate/src/a/b_test.rs")), "./b_test.rs");
+    assert_eq!(
+        relative_path(from, Path::new("/crate/src/tests/a/b_test.rs")),
+        "../tests/a/b_test.rs"
+    );
+}
+
+#[test]
+fn normalize_path_should_resolve_dots() {
+    assert_eq!(normalize_path(Path::new("/crate/src/./a/../tests/b.rs")), Path::new("/crate/src/tests/b.rs"));
+    assert_eq!(normalize_path(Path::new("../a")), Path::new("../a"));
+}
+
+#[test]
+fn edition_should_default_to_2015() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    let project = Project::discover(dir.path()).unwrap();
+    assert_eq!(project.edition().unwrap().as_deref(), Some("2015"));
+
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nedition = \"2021\"\n").unwrap();
+    assert_eq!(project.edition().unwrap().as_deref(), Some("2021"));
+
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nedition.workspace = true\n").unwrap();
+    assert_eq!(project.edition().unwrap(), None);
+}
+
+#[test]
+fn rust_version_should_be_read_from_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    let project = Project::discover(dir.path()).unwrap();
+    assert_eq!(project.rust_version().unwrap(), None);
+
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nrust-version = \"1.56\"\n").unwrap();
+    assert_eq!(project.rust_version().unwrap().as_deref(), Some("1.56"));
+}
+
+#[test]
+fn root_module_should_prefer_lib() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    let project = Project::discover(dir.path()).unwrap();
+    assert_eq!(project.root_module(), None);
+
+    fs::write(dir.path().join("src/main.rs"), "").unwrap();
+    assert_eq!(project.root_module(), Some(project.src.join("main.rs")));
+
+    fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+    assert_eq!(project.root_module(), Some(project.src.join("lib.rs")));
+}
+
+#[test]
+fn targets_should_be_read_from_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("rust")).unwrap();
+    fs::create_dir_all(root.join("src/bin/server")).unwrap();
+    fs::write(root.join("rust/core.rs"), "").unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/bin/cli.rs"), "").unwrap();
+    fs::write(root.join("src/bin/server/main.rs"), "").unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"my-app\"\n\n[lib]\npath = \"rust/core.rs\"\n\n[[bin]]\nname = \"tool\"\npath = \"src/main.rs\"\n"
+    ).unwrap();
+
+    let project = Project::discover(&root).unwrap();
+    let targets: Vec<_> = project
+        .targets()
+        .unwrap()
+        .into_iter()
+        .map(|target| (target.name, target.kind, target.root))
+        .collect();
+
+    assert_eq!(targets, [
+        (String::from("my_app"), TargetKind::Lib, root.join("rust/core.rs")),
+        (String::from("tool"), TargetKind::Bin, root.join("src/main.rs")),
+        (String::from("cli"), TargetKind::Bin, root.join("src/bin/cli.rs")),
+        (String::from("server"), TargetKind::Bin, root.join("src/bin/server/main.rs")),
+    ]);
+}
+
+/// Create a workspace with members `crates/a` and `crates/b`.
+fn workspace(workspace: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), workspace).unwrap();
+    for name in ["a", "b"] {
+        let member = dir.path().join("crates").join(name);
+        fs::create_dir_all(member.join("src")).unwrap();
+        fs::write(member.join("Cargo.toml"), format!("[package]\nname = \"{name}\"\n")).unwrap();
+    }
+
+    dir
+}
+
+#[test]
+fn select_package_should_use_single_default_member() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\"]\ndefault-members = [\"crates/b\"]\n");
+    let root = dir.path().canonicalize().unwrap();
+
+    let project = Project::select_package(&root, None).unwrap().unwrap();
+    assert_eq!(project.root, root.join("crates/b"));
+    assert_eq!(project.src, root.join("crates/b/src"));
+}
+
+#[test]
+fn select_package_without_default_member_should_error() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\"]\n");
+    assert!(Project::select_package(dir.path(), None).is_err());
+}
+
+#[test]
+fn select_package_should_find_named_member() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/a\", \"crates/b\"]\n");
+    let root = dir.path().canonicalize().unwrap();
+
+    let project = Project::select_package(&root.join("crates/b/src"), Some("a")).unwrap().unwrap();
+    assert_eq!(project.root, root.join("crates/a"));
+    assert!(Project::select_package(&root, Some("c")).is_err());
+}
+
+#[test]
+fn discover_should_skip_virtual_workspace_manifests() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\"]\n");
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+
+    let project = Project::discover(&root.join("crates/a/src/b/c")).unwrap();
+    assert_eq!(project.root, root.join("crates/a"));
+    assert!(!is_package_root(&root));
+    assert!(is_package_root(&root.join("crates/a")));
+}
+
+#[test]
+fn select_package_inside_member_should_use_paths_as_given() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\"]\n");
+    let member = dir.path().join("crates/a/src");
+    assert!(Project::select_package(&member, None).unwrap().is_none());
+}
+
+#[test]
+fn select_package_should_use_default_member_of_root_package_workspace() {
+    let dir = workspace("[package]\nname = \"root\"\n\n[workspace]\nmembers = [\"crates/*\"]\ndefault-members = [\"crates/a\"]\n");
+    let root = dir.path().canonicalize().unwrap();
+
+    let project = Project::select_package(&root, None).unwrap().unwrap();
+    assert_eq!(project.root, root.join("crates/a"));
+
+    let dir = workspace("[package]\nname = \"root\"\n\n[workspace]\nmembers = [\"crates/*\"]\n");
+    assert!(Project::select_package(dir.path(), None).unwrap().is_none());
+}
+
+#[test]
+fn select_package_should_expand_member_globs() {
+    let dir = workspace("[workspace]\nmembers = [\"cr?tes/[!b]\", \"**/nested\"]\n");
+    let root = dir.path().canonicalize().unwrap();
+    let nested = root.join("tools/deep/nested");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(nested.join("Cargo.toml"), "[package]\nname = \"nested\"\n").unwrap();
+
+    let project = Project::select_package(&root, Some("a")).unwrap().unwrap();
+    assert_eq!(project.root, root.join("crates/a"));
+    let project = Project::select_package(&root, Some("nested")).unwrap().unwrap();
+    assert_eq!(project.root, nested);
+    assert!(Project::select_package(&root, Some("b")).is_err());
+}
+
+#[test]
+fn select_package_should_skip_excluded_members() {
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/b\"]\n");
+    assert!(Project::select_package(dir.path(), Some("a")).is_ok());
+    assert!(Project::select_package(dir.path(), Some("b")).is_err());
+
+    let dir = workspace("[workspace]\nmembers = [\"crates/*\", \"crates/b\"]\nexclude = [\"crates\"]\n");
+    assert!(Project::select_package(dir.path(), Some("a")).is_err());
+    assert!(Project::select_package(dir.path(), Some("b")).is_ok());
+}
@@ -0,0 +1,127 @@
+//! Line based prompts for interactive use.
+use crate::result::Result;
+use std::io::{self, BufRead, Write};
+
+/// Asks questions on an output, reading the answers from an input.
+/// Invalid answers are reported, and the question asked again.
+pub struct Prompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl Prompt<io::StdinLock<'static>, io::Stdout> {
+    /// Prompt on the terminal.
+    pub fn stdio() -> Self {
+        Self::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    /// Prompt on an input and output.
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Ask for text.
+    ///
+    /// # Arguments
+    /// + `question`: Question to ask.
+    /// + `default`: Answer used if the answer is empty.
+    /// + `check`: Check of the answer, returning an error message if it is invalid.
+    pub fn text(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        check: impl Fn(&str) -> std::result::Result<(), String>,
+    ) -> Result<String> {
+        loop {
+            match default {
+                Some(default) => write!(self.output, "{question} [{default}]: ")?,
+                None => write!(self.output, "{question}: ")?,
+            }
+
+            let answer = self.read_line()?;
+            let answer = match (answer.as_str(), default) {
+                ("", Some(default)) => default.to_string(),
+                ("", None) => continue,
+                (answer, _) => answer.to_string(),
+            };
+
+            match check(&answer) {
+                Ok(()) => return Ok(answer),
+                Err(msg) => writeln!(self.output, "  {msg}")?,
+            }
+        }
+    }
+
+    /// Ask to choose one of several options, by number or by name.
+    ///
+    /// # Arguments
+    /// + `question`: Question to ask.
+    /// + `choices`: Options to choose from.
+    /// + `default`: Index of the option used if the answer is empty.
+    ///
+    /// # Returns
+    /// Index of the chosen option.
+    pub fn select(&mut self, question: &str, choices: &[&str], default: usize) -> Result<usize> {
+        writeln!(self.output, "{question}")?;
+        for (index, choice) in choices.iter().enumerate() {
+            let marker = if index == default { '*' } else { ' ' };
+            writeln!(self.output, " {marker} {}) {choice}", index + 1)?;
+        }
+
+        loop {
+            write!(self.output, "Choice [{}]: ", default + 1)?;
+            let answer = self.read_line()?;
+            if answer.is_empty() {
+                return Ok(default);
+            }
+
+            let chosen = match answer.parse::<usize>() {
+                Ok(number) => number.checked_sub(1).filter(|index| *index < choices.len()),
+                Err(_) => choices.iter().position(|choice| *choice == answer),
+            };
+
+            match chosen {
+                Some(index) => return Ok(index),
+                None => writeln!(self.output, "  enter a number from 1 to {}", choices.len())?,
+            }
+        }
+    }
+
+    /// Ask a yes or no question.
+    ///
+    /// # Arguments
+    /// + `question`: Question to ask.
+    /// + `default`: Answer used if the answer is empty.
+    pub fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let options = if default { "Y/n" } else { "y/N" };
+        loop {
+            write!(self.output, "{question} [{options}] ")?;
+            match self.read_line()?.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => writeln!(self.output, "  answer y or n")?,
+            }
+        }
+    }
+
+    /// Read a trimmed line of input.
+    ///
+    /// # Errors
+    /// + If the input ended.
+    fn read_line(&mut self) -> Result<String> {
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input ended before all questions were answered").into());
+        }
+
+        Ok(line.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+#[path = "./prompt_test.rs"]
+mod prompt_test;
@@ -0,0 +1,33 @@
+use super::*;
+
+#[test]
+fn prompts_should_use_defaults_and_ask_again_on_invalid_answers() {
+    let input = "\nnope\n3\n\nmaybe\nn\n";
+    let mut output = Vec::new();
+    let mut prompt = Prompt::new(input.as_bytes(), &mut output);
+
+    let check = |answer: &str| match answer {
+        "nope" => Err(String::from("not that")),
+        _ => Ok(()),
+    };
+
+    assert_eq!(prompt.text("Name", Some("a"), check).unwrap(), "a");
+    assert_eq!(prompt.text("Name", None, check).unwrap(), "3");
+    assert_eq!(prompt.select("Kind", &["file", "directory"], 1).unwrap(), 1);
+    assert!(!prompt.confirm("Create?", true).unwrap());
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("  not that\n"));
+    assert!(output.contains(" * 2) directory\n"));
+    assert!(output.contains("  answer y or n\n"));
+}
+
+#[test]
+fn select_should_accept_names_and_reject_out_of_range_numbers() {
+    let mut output = Vec::new();
+    let mut prompt = Prompt::new("0\ndirectory\n".as_bytes(), &mut output);
+    assert_eq!(prompt.select("Kind", &["file", "directory"], 0).unwrap(), 1);
+
+    let mut prompt = Prompt::new("".as_bytes(), &mut output);
+    assert!(prompt.confirm("Create?", true).is_err());
+}
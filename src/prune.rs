@@ -0,0 +1,69 @@
+//! Removal of modules without items.
+use crate::result::Result;
+use crate::project;
+use crate::remove::{strip_comments, test_file};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Find the modules in a source tree that contain no items.
+///
+/// A module contains no items if it only has comments, inner attributes,
+/// and test scaffolding whose test file or block has nothing but imports.
+/// Crate roots and test files are never reported.
+///
+/// # Arguments
+/// + `src`: Source directory to search.
+///
+/// # Returns
+/// Files of the modules without items, sorted.
+pub fn find(src: &Path) -> Result<Vec<PathBuf>> {
+    let files = project::module_files(src)?;
+    let mut empty = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(&file)?;
+        if !is_item_less(&content)? {
+            continue;
+        }
+
+        let test_is_empty = match test_file(&file, &content)? {
+            Some(test_file) if test_file.exists() => {
+                is_import_only(&fs::read_to_string(test_file)?)?
+            },
+
+            _ => true,
+        };
+
+        if test_is_empty {
+            empty.push(file);
+        }
+    }
+
+    Ok(empty)
+}
+
+/// Check if a module contains no items.
+/// Comments, inner attributes, test module declarations,
+/// and test blocks containing only imports are ignored.
+fn is_item_less(content: &str) -> Result<bool> {
+    let re_inner_attr = Regex::new(r"#!\[[^\]]*\]")?;
+    let re_test_decl = Regex::new(r"(?:#\[[^\]]*\]\s*)*mod\s+\w+_test\s*;")?;
+    let re_test_block = Regex::new(r"(?:#\[[^\]]*\]\s*)*mod\s+tests\s*\{(?:\s*use\s+[^;]+;)*\s*\}")?;
+
+    let content = strip_comments(content)?;
+    let content = re_inner_attr.replace_all(&content, "");
+    let content = re_test_decl.replace_all(&content, "");
+    let content = re_test_block.replace_all(&content, "");
+    Ok(content.trim().is_empty())
+}
+
+/// Check if a file only contains imports and comments.
+fn is_import_only(content: &str) -> Result<bool> {
+    let re_use = Regex::new(r"(?:pub(?:\([^)]*\))?\s+)?use\s+[^;]+;")?;
+    let content = strip_comments(content)?;
+    Ok(re_use.replace_all(&content, "").trim().is_empty())
+}
+
+#[cfg(test)]
+#[path = "./prune_test.rs"]
+mod prune_test;
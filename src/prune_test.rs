@@ -0,0 +1,38 @@
+use super::*;
+use crate::{ContentOptions, SuperOptions, TestOptions};
+
+#[test]
+fn is_item_less_should_ignore_comments_and_test_scaffolding() {
+    assert!(is_item_less("").unwrap());
+    assert!(is_item_less("//! Docs.\n#![allow(dead_code)]\n/* block */\n").unwrap());
+    assert!(is_item_less("\n#[cfg(test)]\n#[path = \"./a_test.rs\"]\nmod a_test;\n").unwrap());
+    assert!(is_item_less("#[cfg(test)]\nmod tests {\n    use super::*;\n}\n").unwrap());
+    assert!(!is_item_less("fn f() {}\n").unwrap());
+    assert!(!is_item_less("pub mod b;\n").unwrap());
+    assert!(!is_item_less("#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {}\n}\n").unwrap());
+}
+
+#[test]
+fn find_and_remove_should_prune_empty_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("lib.rs"), "").unwrap();
+
+    let options = SuperOptions::default();
+    let test = TestOptions::default();
+    crate::fixtures::create(&src.join("empty"), false, &ContentOptions::default(), Some(&test), Some(&options)).unwrap();
+    crate::fixtures::create(&src.join("tested"), false, &ContentOptions::default(), Some(&test), Some(&options)).unwrap();
+    crate::fixtures::create(&src.join("full"), true, &ContentOptions::default(), None, Some(&options)).unwrap();
+    fs::write(src.join("tested_test.rs"), "use super::*;\n\n#[test]\nfn t() {}\n").unwrap();
+    fs::write(src.join("full/mod.rs"), "pub struct Full;\n").unwrap();
+
+    let empty = find(&src).unwrap();
+    assert_eq!(empty, vec![src.join("empty.rs")]);
+
+    let touched = crate::remove::remove_module_file(&empty[0]).unwrap();
+    assert_eq!(touched, vec![src.join("lib.rs"), src.join("empty.rs"), src.join("empty_test.rs")]);
+    assert!(!src.join("empty_test.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod tested;\npub mod full;\n");
+}
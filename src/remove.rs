@@ -0,0 +1,205 @@
+//! Removal of modules.
+use crate::creator::{DiskFileSystem, FileSystem};
+use crate::result::{Error, Result};
+use crate::SuperOptions;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Remove a module, its test file, and its declaration from its super file.
+/// Directory modules are removed along with everything in their directory.
+///
+/// # Arguments
+/// + `path`: Path to the module. May be the module's file, with or without extension,
+///   or the directory for a directory module.
+///
+/// # Returns
+/// Files removed or modified.
+pub fn remove(path: &Path) -> Result<Vec<PathBuf>> {
+    let file = crate::module_file(path);
+    if !file.is_file() {
+        return Err(Error::ModuleNotFound(path.to_path_buf()));
+    }
+
+    let mut touched = remove_module_file(&file)?;
+    let mod_path = crate::module_path(&file);
+    if mod_path.is_dir() {
+        touched.extend(rust_files(&mod_path)?);
+        fs::remove_dir_all(&mod_path)?;
+    }
+
+    Ok(touched)
+}
+
+/// Find the files in a source tree that refer to a module, other than the module itself.
+///
+/// A file refers to the module if it contains a path into it, e.g. `name::Item`,
+/// or a path to it from `crate`, `super`, or `self`, e.g. `use crate::parent::name;`.
+/// The module's declaration in its super does not count.
+///
+/// # Arguments
+/// + `path`: Path to the module.
+/// + `src`: Source directory to search.
+///
+/// # Returns
+/// Files referring to the module, sorted.
+pub fn references(path: &Path, src: &Path) -> Result<Vec<PathBuf>> {
+    let file = crate::module_file(path);
+    let mod_path = crate::module_path(&file);
+    let name = crate::module_name(&mod_path)?.to_string_lossy().into_owned();
+    let test_file = test_file(&file, &fs::read_to_string(&file)?)?;
+
+    let re_ref = Regex::new(&format!(
+        r"\b{0}\s*::|\b(?:crate|super|self)(?:\s*::\s*\w+)*\s*::\s*{0}\b",
+        regex::escape(&name)
+    ))?;
+
+    let mut files = Vec::new();
+    for candidate in rust_files(src)? {
+        let is_own = candidate == file
+            || Some(&candidate) == test_file.as_ref()
+            || (file.ends_with("mod.rs") && candidate.starts_with(&mod_path));
+
+        if is_own {
+            continue;
+        }
+
+        let content = strip_comments(&fs::read_to_string(&candidate)?)?;
+        let content = remove_declaration(&content, &name)?;
+        if re_ref.is_match(&content) {
+            files.push(candidate);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Remove a module's file, its test file, and its declaration from its super file.
+/// The directory of a directory module is removed only if it is left empty.
+///
+/// # Arguments
+/// + `file`: File of the module. i.e. `a.rs` for a file module or `a/mod.rs` for a directory module.
+///
+/// # Returns
+/// Files removed or modified.
+pub fn remove_module_file(file: &Path) -> Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(file)?;
+    let test_file = test_file(file, &content)?;
+    let is_dir = file.file_name().is_some_and(|name| name == "mod.rs");
+    let mod_path = crate::module_path(file);
+
+    let mut touched = Vec::new();
+    if let Ok(super_file) = crate::resolve_super(&DiskFileSystem, &mod_path, &SuperOptions::default()) {
+        let name = crate::module_name(&mod_path)?.to_string_lossy().into_owned();
+        let super_content = fs::read_to_string(&super_file)?;
+        let new_content = remove_declaration(&super_content, &name)?;
+        if new_content != super_content {
+            fs::write(&super_file, new_content)?;
+            touched.push(super_file);
+        }
+    }
+
+    fs::remove_file(file)?;
+    touched.push(file.to_path_buf());
+    if let Some(test_file) = test_file.filter(|test_file| test_file.exists()) {
+        fs::remove_file(&test_file)?;
+        touched.push(test_file);
+    }
+
+    if is_dir && fs::read_dir(&mod_path)?.next().is_none() {
+        fs::remove_dir(&mod_path)?;
+    }
+
+    Ok(touched)
+}
+
+/// Get the test file declared by a module.
+///
+/// # Arguments
+/// + `file`: File of the module.
+/// + `content`: Content of the module.
+pub(crate) fn test_file(file: &Path, content: &str) -> Result<Option<PathBuf>> {
+    let re_path = Regex::new(r#"#\[path\s*=\s*"([^"]+)"\]\s*mod\s+\w+_test\s*;"#)?;
+    let test_file = re_path.captures(content).map(|caps| match file.parent() {
+        Some(parent) => crate::project::normalize_path(&parent.join(&caps[1])),
+        None => PathBuf::from(&caps[1]),
+    });
+
+    Ok(test_file)
+}
+
+/// Remove line and block comments from source code.
+pub(crate) fn strip_comments(content: &str) -> Result<String> {
+    let re_comment = Regex::new(r"(?s)/\*.*?\*/|//[^\n]*")?;
+    Ok(re_comment.replace_all(content, "").into_owned())
+}
+
+/// Remove the declaration of a module, and its glob re-export, from a super file.
+/// Attributes on the lines preceding the declaration are removed with it.
+///
+/// # Arguments
+/// + `content`: Content of the super file.
+/// + `name`: Name of the module.
+///
+/// # Returns
+/// The new content of the super file.
+pub(crate) fn remove_declaration(content: &str, name: &str) -> Result<String> {
+    let name = regex::escape(name);
+    let re_decl = Regex::new(&format!(
+        r"^\s*(?:#\[[^\]]*\]\s*)*(?:pub(?:\([^)]*\))?\s+)?mod\s+{name}\s*;"
+    ))?;
+    let re_reexport = Regex::new(&format!(
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+(?:self::)?{name}::\*\s*;"
+    ))?;
+    let re_attr = Regex::new(r"^\s*#\[[^\]]*\]\s*$")?;
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if re_decl.is_match(line) {
+            while lines.last().is_some_and(|line| re_attr.is_match(line)) {
+                lines.pop();
+            }
+
+            continue;
+        }
+
+        if !re_reexport.is_match(line) {
+            lines.push(line);
+        }
+    }
+
+    let newline = crate::newline(content);
+    let mut new_content = lines.join(newline);
+    if content.ends_with('\n') && !new_content.is_empty() {
+        new_content.push_str(newline);
+    }
+
+    Ok(new_content)
+}
+
+/// Get all Rust files in a directory, recursively.
+///
+/// # Returns
+/// Files, sorted.
+pub(crate) fn rust_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    rust_files_in(&DiskFileSystem, dir)
+}
+
+/// Get all Rust files in a directory of a file system, recursively. See [`rust_files`].
+pub(crate) fn rust_files_in(fs: &dyn FileSystem, dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in fs.read_dir(dir)? {
+        if fs.is_dir(&path) {
+            files.extend(rust_files_in(fs, &path)?);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+#[path = "./remove_test.rs"]
+mod remove_test;
@@ -0,0 +1,59 @@
+use super::*;
+use crate::fixtures::project_with_modules;
+
+/// A directory module `a`, containing `b`, and a file module `c`.
+const MODULES: [(&str, bool); 3] = [("a", true), ("a/b", false), ("c", false)];
+
+#[test]
+fn remove_declaration_should_remove_attributes_and_reexport() {
+    let content = "mod a;\npub use a::*;\n#[cfg(unix)]\npub mod b; // note\npub use b::*;\n";
+    assert_eq!(remove_declaration(content, "b").unwrap(), "mod a;\npub use a::*;\n");
+    assert_eq!(remove_declaration("pub mod ab;\n", "a").unwrap(), "pub mod ab;\n");
+    assert_eq!(remove_declaration("mod a;\r\nmod b;\r\nmod c;\r\n", "b").unwrap(), "mod a;\r\nmod c;\r\n");
+}
+
+#[test]
+fn remove_should_delete_directory_module() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    let touched = remove(&src.join("a")).unwrap();
+    assert!(touched.contains(&src.join("a/b.rs")));
+    assert!(!src.join("a").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod c;\n");
+    assert!(remove(&src.join("a")).is_err());
+}
+
+#[test]
+fn remove_should_follow_path_attributes_to_super() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    fs::create_dir(src.join("other")).unwrap();
+    fs::write(src.join("lib.rs"), "pub mod a;\r\n#[path = \"other/d.rs\"]\r\nmod d;\r\npub mod c;\r\n").unwrap();
+    fs::write(src.join("other/d.rs"), "mod e;\r\nmod f;\r\n").unwrap();
+    fs::write(src.join("other/e.rs"), "").unwrap();
+
+    remove(&src.join("other/e.rs")).unwrap();
+    assert_eq!(fs::read_to_string(src.join("other/d.rs")).unwrap(), "mod f;\r\n");
+}
+
+#[test]
+fn remove_should_delete_file_module_and_test() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    remove(&src.join("c.rs")).unwrap();
+    assert!(!src.join("c.rs").exists());
+    assert!(!src.join("c_test.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\n");
+}
+
+#[test]
+fn references_should_find_paths_to_module() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    assert!(references(&src.join("a"), &src).unwrap().is_empty());
+
+    fs::write(src.join("c.rs"), "// a::b is mentioned\nuse crate::a::b::Thing;\n").unwrap();
+    assert_eq!(references(&src.join("a"), &src).unwrap(), vec![src.join("c.rs")]);
+    assert_eq!(references(&src.join("a/b"), &src).unwrap(), vec![src.join("c.rs")]);
+
+    // references from within the module do not count
+    fs::write(src.join("a/b.rs"), "use super::b::Other;\n").unwrap();
+    fs::write(src.join("c.rs"), "").unwrap();
+    assert!(references(&src.join("a"), &src).unwrap().is_empty());
+}
@@ -0,0 +1,281 @@
+//! Renaming and moving of modules.
+use crate::creator::DiskFileSystem;
+use crate::remove::{remove_declaration, rust_files, strip_comments, test_file};
+use crate::result::{Error, Result};
+use crate::project;
+use crate::visibility::Visibility;
+use crate::SuperOptions;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Rename or move a module.
+///
+/// The module's file, or directory for a directory module, is moved along with its test file.
+/// `#[path]` attributes in the module are updated to point at the moved files,
+/// and its declaration is renamed in its super, or moved if its super changes.
+///
+/// # Arguments
+/// + `from`: Path to the module. May be the module's file, with or without extension,
+///   or the directory for a directory module.
+/// + `to`: New path of the module, without extension. Its parent must exist.
+/// + `options`: Options for declaring the module if its super changes.
+///   The visibility of the existing declaration is kept.
+///
+/// # Returns
+/// Files removed, created, or modified.
+///
+/// # Errors
+/// + If the module does not exist, or a module already exists at `to`.
+/// + If a super file has unstaged changes, unless allowed.
+pub fn rename(from: &Path, to: &Path, options: &SuperOptions) -> Result<Vec<PathBuf>> {
+    let file = project::absolute_path(&crate::module_file(from))?;
+    if !file.is_file() {
+        return Err(Error::ModuleNotFound(from.to_path_buf()));
+    }
+
+    let to = match to.extension() {
+        Some(ext) if ext == "rs" => to.with_extension(""),
+        _ => to.to_path_buf(),
+    };
+
+    let old_mod = crate::module_path(&file);
+    let new_mod = project::absolute_path(&to)?;
+    if new_mod.exists() || new_mod.with_extension("rs").exists() {
+        return Err(Error::ModuleExists(new_mod));
+    }
+
+    if !new_mod.parent().is_some_and(Path::is_dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound, "destination directory does not exist"
+        ).into());
+    }
+
+    let old_name = crate::module_name(&old_mod)?.to_string_lossy().into_owned();
+    let new_name = crate::module_name(&new_mod)?.to_string_lossy().into_owned();
+    let old_super = crate::super_path(&DiskFileSystem, &old_mod, options.main, options.src_root.as_deref(), options.target.as_deref()).ok();
+    if let Some(old_super) = &old_super {
+        crate::check_modifiable(&DiskFileSystem, old_super, options)?;
+    }
+
+    let new_super = crate::checked_super_path(&DiskFileSystem, &new_mod, options)?;
+
+    // move files
+    let is_dir = file.file_name().is_some_and(|name| name == "mod.rs");
+    let content = fs::read_to_string(&file)?;
+    let old_test = test_file(&file, &content)?;
+    let new_file = match is_dir {
+        true => new_mod.join("mod.rs"),
+        false => new_mod.with_extension("rs"),
+    };
+
+    let new_test = old_test
+        .as_ref()
+        .map(|test| moved_test_file(test, &file, &new_file, &old_name, &new_name));
+
+    if !is_dir {
+        fs::rename(&file, &new_file)?;
+    }
+
+    if old_mod.is_dir() {
+        fs::rename(&old_mod, &new_mod)?;
+    }
+
+    let mut touched = vec![file.clone(), new_file.clone()];
+    if let (Some(old_test), Some(new_test)) = (&old_test, &new_test) {
+        let moved_test = moved_path(old_test, &old_mod, &new_mod);
+        if moved_test.exists() && moved_test != *new_test {
+            fs::rename(&moved_test, new_test)?;
+        }
+
+        touched.extend([old_test.clone(), new_test.clone()]);
+    }
+
+    // update test module
+    let old_dir = file.parent().unwrap_or(Path::new(""));
+    let new_dir = new_file.parent().unwrap_or(Path::new(""));
+    let re_path = Regex::new(r#"#\[path\s*=\s*"([^"]+)"\]"#)?;
+    let re_test_mod = Regex::new(&format!(r"\bmod\s+{}_test\s*;", regex::escape(&old_name)))?;
+    let content = re_path.replace_all(&content, |caps: &regex::Captures| {
+        let target = project::normalize_path(&old_dir.join(&caps[1]));
+        let target = match (&old_test, &new_test) {
+            (Some(old_test), Some(new_test)) if target == *old_test => new_test.clone(),
+            _ => moved_path(&target, &old_mod, &new_mod),
+        };
+
+        format!("#[path = \"{}\"]", project::relative_path(new_dir, &target))
+    });
+
+    let content = re_test_mod.replace_all(&content, format!("mod {new_name}_test;"));
+    fs::write(&new_file, content.as_bytes())?;
+
+    // update declaration
+    if let Some(old_super) = &old_super {
+        let super_content = fs::read_to_string(old_super)?;
+        if *old_super == new_super {
+            let new_content = rename_declaration(&super_content, &old_name, &new_name)?;
+            fs::write(old_super, new_content)?;
+        } else {
+            let options = SuperOptions {
+                vis: declared_visibility(&super_content, &old_name)?.or(options.vis.clone()),
+                ..options.clone()
+            };
+
+            fs::write(old_super, remove_declaration(&super_content, &old_name)?)?;
+            crate::add_to_super(&new_mod, &options)?;
+            touched.push(new_super);
+        }
+
+        touched.push(old_super.clone());
+    } else {
+        crate::add_to_super(&new_mod, options)?;
+        touched.push(new_super);
+    }
+
+    Ok(touched)
+}
+
+/// Rewrite paths to a moved module starting from `crate`, e.g. `use crate::a::old::Item;`.
+///
+/// # Arguments
+/// + `src`: Source directory of the crate.
+/// + `from`: Old path of the module, without extension, or its directory.
+/// + `to`: New path of the module, without extension, or its directory.
+///
+/// # Returns
+/// Files modified.
+pub fn rewrite_references(src: &Path, from: &Path, to: &Path) -> Result<Vec<PathBuf>> {
+    let src = project::absolute_path(src)?;
+    let (from, to) = match (
+        crate_path(&project::absolute_path(from)?, &src),
+        crate_path(&project::absolute_path(to)?, &src),
+    ) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return Ok(Vec::new()),
+    };
+
+    let pattern = from
+        .iter()
+        .map(|segment| regex::escape(segment))
+        .collect::<Vec<_>>()
+        .join(r"\s*::\s*");
+
+    let re_ref = Regex::new(&format!(r"\bcrate\s*::\s*{pattern}\b"))?;
+    let replacement = format!("crate::{}", to.join("::"));
+
+    let mut touched = Vec::new();
+    for file in rust_files(&src)? {
+        if file.starts_with(src.join("bin")) {
+            continue;
+        }
+
+        let content = fs::read_to_string(&file)?;
+        if !re_ref.is_match(&strip_comments(&content)?) {
+            continue;
+        }
+
+        let new_content = re_ref.replace_all(&content, regex::NoExpand(&replacement));
+        if new_content != content {
+            fs::write(&file, new_content.as_bytes())?;
+            touched.push(file);
+        }
+    }
+
+    Ok(touched)
+}
+
+/// Rename the declaration of a module, and its glob re-export, in a super file.
+///
+/// # Arguments
+/// + `content`: Content of the super file.
+/// + `old_name`: Current name of the module.
+/// + `new_name`: New name of the module.
+fn rename_declaration(content: &str, old_name: &str, new_name: &str) -> Result<String> {
+    let old_name = regex::escape(old_name);
+    let re_decl = Regex::new(&format!(
+        r"(?m)^(\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+){old_name}(\s*;)"
+    ))?;
+    let re_reexport = Regex::new(&format!(
+        r"(?m)^(\s*(?:pub(?:\([^)]*\))?\s+)?use\s+(?:self::)?){old_name}(::\*\s*;)"
+    ))?;
+
+    let replacement = format!("${{1}}{new_name}${{2}}");
+    let content = re_decl.replace_all(content, replacement.as_str());
+    let content = re_reexport.replace_all(&content, replacement.as_str());
+    Ok(content.into_owned())
+}
+
+/// Get the visibility a module is declared with.
+///
+/// # Returns
+/// `None` if the module is not declared.
+fn declared_visibility(content: &str, name: &str) -> Result<Option<Visibility>> {
+    let re_decl = Regex::new(&format!(
+        r"(?m)^\s*(pub(?:\([^)]*\))?\s+)?mod\s+{}\s*;", regex::escape(name)
+    ))?;
+
+    let vis = match re_decl.captures(content) {
+        Some(caps) => caps.get(1).map_or("", |vis| vis.as_str()),
+        None => return Ok(None),
+    };
+
+    Ok(Some(vis.parse()?))
+}
+
+/// Get the new location of a module's test file.
+///
+/// Test files inside the module's directory move with it.
+/// Test files next to the module move with the module file, and others stay in their directory.
+/// Test files named after the module are renamed.
+fn moved_test_file(test: &Path, file: &Path, new_file: &Path, old_name: &str, new_name: &str) -> PathBuf {
+    let old_mod = crate::module_path(file);
+    let new_mod = crate::module_path(new_file);
+    if test.starts_with(&old_mod) {
+        return moved_path(test, &old_mod, &new_mod);
+    }
+
+    let dir = match (test.parent(), file.parent(), new_file.parent()) {
+        (Some(dir), Some(old_dir), Some(new_dir)) if dir == old_dir => new_dir,
+        (Some(dir), _, _) => dir,
+        _ => Path::new(""),
+    };
+
+    let test_name = format!("{old_name}_test.rs");
+    match test.file_name() {
+        Some(name) if *name == *test_name => dir.join(format!("{new_name}_test.rs")),
+        Some(name) => dir.join(name),
+        None => test.to_path_buf(),
+    }
+}
+
+/// Get the new location of a path after a directory moves.
+fn moved_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Get the path of a module from the crate root, e.g. `["a", "b"]` for `src/a/b.rs`.
+///
+/// # Returns
+/// `None` if the module is not in `src`, or is part of a binary in `src/bin`.
+fn crate_path(mod_path: &Path, src: &Path) -> Option<Vec<String>> {
+    let rel_path = mod_path.strip_prefix(src).ok()?;
+    let segments: Vec<String> = rel_path
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    match segments.first() {
+        None => None,
+        Some(first) if first == "bin" => None,
+        Some(_) => Some(segments),
+    }
+}
+
+#[cfg(test)]
+#[path = "./rename_test.rs"]
+mod rename_test;
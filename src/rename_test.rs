@@ -0,0 +1,68 @@
+use super::*;
+use crate::fixtures::project_with_modules;
+
+/// A directory module `a`, containing `b`, and a file module `c`.
+const MODULES: [(&str, bool); 3] = [("a", true), ("a/b", false), ("c", false)];
+
+#[test]
+fn rename_should_rename_file_module_and_test() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    fs::write(src.join("lib.rs"), "pub mod a;\nmod c;\npub use c::*;\n").unwrap();
+    rename(&src.join("c"), &src.join("d"), &SuperOptions::default()).unwrap();
+
+    assert!(!src.join("c.rs").exists());
+    assert!(!src.join("c_test.rs").exists());
+    assert!(src.join("d_test.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\nmod d;\npub use d::*;\n");
+
+    let content = fs::read_to_string(src.join("d.rs")).unwrap();
+    assert!(content.contains("#[path = \"./d_test.rs\"]\nmod d_test;"));
+}
+
+#[test]
+fn rename_should_move_module_to_new_super() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    fs::write(src.join("lib.rs"), "pub mod a;\nmod c;\n").unwrap();
+    rename(&src.join("c.rs"), &src.join("a/d"), &SuperOptions::default()).unwrap();
+
+    assert!(src.join("a/d.rs").exists());
+    assert!(src.join("a/d_test.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod a;\n");
+
+    let super_content = fs::read_to_string(src.join("a/mod.rs")).unwrap();
+    assert!(super_content.contains("\nmod d;\n"));
+    assert!(!super_content.contains("pub mod d;"));
+}
+
+#[test]
+fn rename_should_move_directory_module() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    rename(&src.join("a"), &src.join("e"), &SuperOptions::default()).unwrap();
+
+    assert!(!src.join("a").exists());
+    assert!(src.join("e/mod.rs").exists());
+    assert!(src.join("e/mod_test.rs").exists());
+    assert!(src.join("e/b.rs").exists());
+    assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap(), "pub mod e;\npub mod c;\n");
+}
+
+#[test]
+fn rename_should_not_overwrite_modules() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    let res = rename(&src.join("c"), &src.join("a"), &SuperOptions::default());
+    assert!(res.is_err());
+    assert!(src.join("c.rs").exists());
+}
+
+#[test]
+fn rewrite_references_should_rewrite_crate_paths() {
+    let (_dir, src) = project_with_modules(&MODULES);
+    fs::write(src.join("c.rs"), "use crate::a::b::Item;\nuse crate::a::bc;\nuse crate :: a :: b;\n").unwrap();
+    let touched = rewrite_references(&src, &src.join("a/b"), &src.join("f")).unwrap();
+
+    assert_eq!(touched, vec![src.join("c.rs")]);
+    assert_eq!(
+        fs::read_to_string(src.join("c.rs")).unwrap(),
+        "use crate::f::Item;\nuse crate::a::bc;\nuse crate::f;\n"
+    );
+}
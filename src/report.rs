@@ -0,0 +1,167 @@
+//! Machine readable reports of operations, for editors and scripts wrapping mkmod.
+use crate::metrics::Metrics;
+use crate::project;
+use crate::result::Result;
+use crate::{Created, Location};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Files an operation touched, and the modules it created.
+/// Paths are absolute.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Report {
+    /// Modules created.
+    pub modules: Vec<Module>,
+
+    /// Files created.
+    pub created: Vec<PathBuf>,
+
+    /// Files modified.
+    pub modified: Vec<Modified>,
+
+    /// Files removed. e.g. file modules converted into directory modules.
+    pub removed: Vec<PathBuf>,
+
+    /// Problems that did not stop the operation.
+    pub warnings: Vec<String>,
+
+    /// Time spent in each step of the operation.
+    pub metrics: Metrics,
+}
+
+/// A created module.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Module {
+    /// Path to the module.
+    /// The file for a file module, or the directory for a directory module.
+    pub path: PathBuf,
+
+    /// Super file the module was declared in, if it was added to one.
+    pub super_file: Option<PathBuf>,
+
+    /// Location of the module's declaration, if it was added to its super.
+    pub declaration: Option<Location>,
+
+    /// Location where tests should be written, if a test file was created.
+    pub tests: Option<Location>,
+}
+
+/// A modified file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Modified {
+    pub path: PathBuf,
+
+    /// 1-based lines inserted in the file, if known.
+    /// Each insertion's lines are as they were when it was made, before any later insertions.
+    pub inserted_lines: Vec<usize>,
+}
+
+impl Report {
+    /// Add a created module, and the files it and its parents touched.
+    /// Modules that already existed are listed, but not their files unless they were modified.
+    /// Their metrics are added to the report's.
+    pub fn add_module(&mut self, created: &Created) -> Result {
+        for parent in &created.parents {
+            self.add_files(parent)?;
+            self.metrics.merge(&parent.metrics);
+        }
+
+        self.metrics.merge(&created.metrics);
+
+        let absolute = |location: &Location| -> Result<Location> {
+            Ok(Location { path: project::absolute_path(&location.path)?, ..location.clone() })
+        };
+
+        self.modules.push(Module {
+            path: project::absolute_path(&created.module_path)?,
+            super_file: created.super_modified.as_deref().map(project::absolute_path).transpose()?,
+            declaration: created.declaration_location.as_ref().map(absolute).transpose()?,
+            tests: created.test_location.as_ref().map(absolute).transpose()?,
+        });
+
+        self.add_files(created)
+    }
+
+    /// Add the files a created module touched.
+    fn add_files(&mut self, created: &Created) -> Result {
+        if !created.existed {
+            self.add_created(&created.module_path)?;
+        }
+
+        if let Some(module_file) = &created.module_modified {
+            self.add_modified(module_file, &[])?;
+        }
+
+        if let Some(test_path) = &created.test_path {
+            self.add_created(test_path)?;
+        }
+
+        if let Some(super_file) = &created.super_modified {
+            self.add_modified(super_file, &created.declaration_lines)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a file the operation touched.
+    ///
+    /// # Arguments
+    /// + `path`: Path of the file.
+    /// + `existed`: Whether the file existed before the operation.
+    ///   Existing files are modified if they still exist, and removed otherwise.
+    pub fn add_touched(&mut self, path: &Path, existed: bool) -> Result {
+        match (existed, path.exists()) {
+            (true, true) => self.add_modified(path, &[]),
+            (true, false) => {
+                let path = project::absolute_path(path)?;
+                if !self.removed.contains(&path) {
+                    self.removed.push(path);
+                }
+
+                Ok(())
+            },
+
+            (false, _) => self.add_created(path),
+        }
+    }
+
+    /// Add a created file.
+    /// Directories of directory modules are listed by their `mod.rs`.
+    fn add_created(&mut self, path: &Path) -> Result {
+        let mut path = project::absolute_path(path)?;
+        if path.is_dir() {
+            path.push("mod.rs");
+        }
+
+        if !self.created.contains(&path) {
+            self.created.push(path);
+        }
+
+        Ok(())
+    }
+
+    /// Add a modified file, merging its inserted lines with those already known.
+    /// Files created by the operation are not listed as modified.
+    pub fn add_modified(&mut self, path: &Path, lines: &[usize]) -> Result {
+        let path = project::absolute_path(path)?;
+        if self.created.contains(&path) {
+            return Ok(());
+        }
+
+        match self.modified.iter_mut().find(|modified| modified.path == path) {
+            Some(modified) => {
+                modified.inserted_lines.extend(lines);
+                modified.inserted_lines.sort_unstable();
+                modified.inserted_lines.dedup();
+            },
+
+            None => self.modified.push(Modified { path, inserted_lines: lines.to_vec() }),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./report_test.rs"]
+mod report_test;
@@ -0,0 +1,36 @@
+use super::*;
+use crate::{ContentOptions, CreateOptions};
+use std::fs;
+
+#[test]
+fn report_should_list_touched_files_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::write(root.join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "fn f() {}\n").unwrap();
+
+    let created = CreateOptions::new(root.join("src/a"))
+        .content(ContentOptions::default())
+        .create()
+        .unwrap();
+
+    let mut report = Report::default();
+    report.add_module(&created).unwrap();
+    report.add_touched(&root.join("src/lib.rs"), true).unwrap();
+    report.add_touched(&root.join("src/a.rs"), false).unwrap();
+    report.add_touched(&root.join("src/gone.rs"), true).unwrap();
+
+    assert_eq!(report.created, [root.join("src/a.rs"), root.join("src/a_test.rs")]);
+    assert_eq!(report.modified, [Modified { path: root.join("src/lib.rs"), inserted_lines: vec![1] }]);
+    assert_eq!(report.removed, [root.join("src/gone.rs")]);
+    assert_eq!(report.modules[0].super_file.as_deref(), Some(root.join("src/lib.rs").as_path()));
+    assert_eq!(report.modules[0].declaration.as_ref().unwrap().line, 1);
+
+    assert_eq!(report.metrics.template_render.count, 1);
+    assert_eq!(report.metrics.super_rewrite.count, 1);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["modified"][0]["inserted_lines"], serde_json::json!([1]));
+    assert_eq!(json["metrics"]["super_rewrite"]["count"], 1);
+}
@@ -1,10 +1,175 @@
+use std::fmt;
 use std::io;
+use std::path::PathBuf;
 use std::result::Result as StdResult;
 
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
     Regex(regex::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    TomlSerialize(toml::ser::Error),
+    TomlEdit(toml_edit::TomlError),
+    Template(minijinja::Error),
+
+    /// No Cargo package contains the path.
+    NotInCargoProject(PathBuf),
+
+    /// The super file a module should be declared in does not exist.
+    ParentNotFound(PathBuf),
+
+    /// A path does not name a module, e.g. it has no file name or parent.
+    InvalidPath(PathBuf),
+
+    /// A module name is not a valid Rust identifier.
+    InvalidModuleName(String),
+
+    /// A module, or file that would be created, already exists.
+    ModuleExists(PathBuf),
+
+    /// A module does not exist.
+    ModuleNotFound(PathBuf),
+
+    /// A module is already declared in a super file in a way that conflicts with a new declaration.
+    Declared { path: PathBuf, line: usize, reason: String },
+
+    /// An item is not defined at the top level of a module.
+    ItemNotFound { path: PathBuf, item: String },
+
+    /// No named template is configured with a name.
+    TemplateNotFound(String),
+
+    /// The journal does not record a module as generated from a template.
+    NotGenerated(PathBuf),
+
+    /// A module is outside of its crate's source directory.
+    NotInSource {
+        path: PathBuf,
+
+        /// Source directory of the crate.
+        src: PathBuf,
+    },
+
+    /// Declaring a module would break a super file that parsed before.
+    SuperParseFailed {
+        path: PathBuf,
+
+        /// 1-based line of the error.
+        line: usize,
+        message: String,
+    },
+
+    /// A file that would be modified has unstaged changes.
+    Dirty(PathBuf),
+
+    /// A file that would be modified has uncommitted changes, and a clean file is required.
+    Uncommitted(PathBuf),
+
+    /// A file changed since a plan was made.
+    Changed(PathBuf),
+
+    /// Generated code does not parse.
+    Parse {
+        /// Name of what was generated. e.g. `module`.
+        template: String,
+
+        /// 1-based line of the error.
+        line: usize,
+
+        /// 1-based column of the error.
+        column: usize,
+        message: String,
+    },
+
+    /// A hook command failed.
+    Hook {
+        command: String,
+
+        /// Exit code of the command, if it exited normally.
+        code: Option<i32>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Regex(err) => write!(f, "invalid pattern: {err}"),
+            Self::Json(err) => write!(f, "invalid JSON: {err}"),
+            Self::Toml(err) => write!(f, "invalid configuration file: {err}"),
+            Self::TomlSerialize(err) => write!(f, "could not write TOML: {err}"),
+            Self::TomlEdit(err) => write!(f, "invalid Cargo.toml: {err}"),
+            Self::Template(err) => write!(f, "invalid template: {err}"),
+            Self::NotInCargoProject(path) => write!(
+                f, "{} is not in a Cargo package, no Cargo.toml with a [package] was found above it",
+                path.display()
+            ),
+
+            Self::ParentNotFound(path) => write!(
+                f, "parent module {} does not exist, create it first", path.display()
+            ),
+
+            Self::InvalidPath(path) => write!(f, "{} is not a valid module path", path.display()),
+            Self::InvalidModuleName(name) => write!(
+                f, "`{name}` is not a valid module name, use an identifier that is not a keyword, or --sanitize to convert it"
+            ),
+
+            Self::ModuleExists(path) => write!(f, "{} already exists", path.display()),
+            Self::ModuleNotFound(path) => write!(f, "module {} does not exist", path.display()),
+            Self::Declared { path, line, reason } => write!(f, "{reason} at {}:{line}", path.display()),
+            Self::ItemNotFound { path, item } => write!(f, "`{item}` is not defined in {}", path.display()),
+            Self::TemplateNotFound(name) => write!(f, "no template named `{name}` is configured in [templates.named]"),
+            Self::NotGenerated(path) => write!(
+                f, "{} was not generated from a template, or not by mkmod with a journal", path.display()
+            ),
+
+            Self::NotInSource { path, src } => write!(
+                f, "{} is not in the source directory {}", path.display(), src.display()
+            ),
+
+            Self::SuperParseFailed { path, line, message } => write!(
+                f, "declaring the module would break {} at line {line}: {message}", path.display()
+            ),
+
+            Self::Dirty(path) => write!(
+                f, "{} has unstaged changes, use --dirty-ok to modify it anyway", path.display()
+            ),
+
+            Self::Uncommitted(path) => write!(
+                f, "{} has uncommitted changes, commit or stash them first", path.display()
+            ),
+
+            Self::Changed(path) => write!(
+                f, "{} changed since the plan was made, create a new plan", path.display()
+            ),
+
+            Self::Parse { template, line, column, message } => write!(
+                f, "generated {template} does not parse at {line}:{column}: {message}"
+            ),
+
+            Self::Hook { command, code: Some(code) } => {
+                write!(f, "hook `{command}` failed with exit code {code}")
+            },
+
+            Self::Hook { command, code: None } => write!(f, "hook `{command}` was terminated"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Regex(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::Toml(err) => Some(err),
+            Self::TomlSerialize(err) => Some(err),
+            Self::TomlEdit(err) => Some(err),
+            Self::Template(err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -19,4 +184,34 @@ impl From<regex::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Toml(err)
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(err: toml::ser::Error) -> Self {
+        Error::TomlSerialize(err)
+    }
+}
+
+impl From<toml_edit::TomlError> for Error {
+    fn from(err: toml_edit::TomlError) -> Self {
+        Error::TomlEdit(err)
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(err: minijinja::Error) -> Self {
+        Error::Template(err)
+    }
+}
+
 pub type Result<T = ()> = StdResult<T, Error>;
@@ -0,0 +1,60 @@
+//! Rust versions, for generating code a project's minimum supported Rust version accepts.
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// A Rust version, as written in a manifest's `rust-version`. e.g. `1.56` or `1.56.1`.
+/// Missing components are `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RustVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RustVersion {
+    /// `Self` in struct expressions, e.g. `Self {}`.
+    pub const SELF_STRUCT: Self = Self::new(1, 32, 0);
+
+    /// `Self` in paths to enum variants, e.g. `Self::First`.
+    pub const SELF_VARIANT: Self = Self::new(1, 37, 0);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Whether a project with this minimum supported version can use what stabilized in another.
+    pub fn supports(&self, version: Self) -> bool {
+        *self >= version
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for RustVersion {
+    type Err = io::Error;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("invalid Rust version `{version}`"));
+        let components = version
+            .trim()
+            .split('.')
+            .map(|component| component.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match components.as_slice() {
+            [major] => Ok(Self::new(*major, 0, 0)),
+            [major, minor] => Ok(Self::new(*major, *minor, 0)),
+            [major, minor, patch] => Ok(Self::new(*major, *minor, *patch)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./rust_version_test.rs"]
+mod rust_version_test;
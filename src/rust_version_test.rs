@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn version_should_parse_with_missing_components() {
+    assert_eq!("1.56".parse::<RustVersion>().unwrap(), RustVersion::new(1, 56, 0));
+    assert_eq!("1.56.1".parse::<RustVersion>().unwrap(), RustVersion::new(1, 56, 1));
+    assert_eq!("1".parse::<RustVersion>().unwrap(), RustVersion::new(1, 0, 0));
+    assert!("1.x".parse::<RustVersion>().is_err());
+    assert!("1.2.3.4".parse::<RustVersion>().is_err());
+    assert!("".parse::<RustVersion>().is_err());
+}
+
+#[test]
+fn versions_should_be_ordered_by_component() {
+    assert!(RustVersion::new(1, 9, 0) < RustVersion::new(1, 37, 0));
+    assert!(RustVersion::new(1, 37, 0).supports(RustVersion::SELF_VARIANT));
+    assert!(!RustVersion::new(1, 36, 9).supports(RustVersion::SELF_VARIANT));
+}
@@ -0,0 +1,129 @@
+//! Formatting of touched files with `rustfmt`.
+use crate::project::Project;
+use crate::result::Result;
+use crate::Created;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Environment variable overriding the `rustfmt` executable, as with `cargo fmt`.
+pub const RUSTFMT_VAR: &str = "RUSTFMT";
+
+/// Format Rust files with `rustfmt`, using the project's edition and its `rustfmt.toml`.
+///
+/// Each file is formatted on its own through `rustfmt`'s standard input,
+/// so modules the files declare are not formatted along with them.
+/// Formatting is best effort. Files are left as they are if `rustfmt` is not installed,
+/// or can not format them, e.g. because a super file does not parse.
+///
+/// # Arguments
+/// + `project`: Project the files are in.
+/// + `files`: Files to format. Paths without an `.rs` extension, or that do not exist, are skipped.
+///
+/// # Returns
+/// `true` if all of the files were formatted.
+pub fn format(project: &Project, files: &[&Path]) -> Result<bool> {
+    let files: Vec<&Path> = files
+        .iter()
+        .copied()
+        .filter(|file| file.extension().is_some_and(|ext| ext == "rs") && file.is_file())
+        .collect();
+
+    // editions inherited from the workspace are left to `rustfmt.toml`
+    let edition = project.edition()?;
+    let mut formatted = true;
+    for file in files {
+        let content = fs::read_to_string(file)?;
+        match format_source(&content, edition.as_deref(), file.parent().unwrap_or(&project.root))? {
+            Some(new) if new != content => crate::write_atomic(file, &new)?,
+            Some(_) => {},
+            None => formatted = false,
+        }
+    }
+
+    Ok(formatted)
+}
+
+/// Format source code as a file of a project would be formatted.
+///
+/// # Arguments
+/// + `project`: Project the source belongs to.
+/// + `content`: Source to format.
+/// + `dir`: Directory the source would be in, where `rustfmt.toml` is searched from.
+///
+/// # Returns
+/// The formatted source, or the source as it is if `rustfmt` is not installed or could not format it.
+pub fn format_str(project: &Project, content: &str, dir: &Path) -> Result<String> {
+    let edition = project.edition()?;
+    let formatted = format_source(content, edition.as_deref(), dir)?;
+    Ok(formatted.unwrap_or_else(|| content.to_string()))
+}
+
+/// Format the files created for modules, and locate their declarations and tests again.
+///
+/// Existing files the modules were declared in are not formatted,
+/// so their declarations keep their order and the inserted lines stay where they were put.
+///
+/// # Arguments
+/// + `project`: Project the modules are in.
+/// + `created`: Created modules.
+///
+/// # Returns
+/// `true` if all of the created files were formatted.
+pub fn format_created(project: &Project, created: &mut [Created]) -> Result<bool> {
+    let files: Vec<&Path> = created.iter().flat_map(Created::created_paths).collect();
+    let formatted = format(project, &files)?;
+    for module in created {
+        module.relocate()?;
+    }
+
+    Ok(formatted)
+}
+
+/// Format source code with `rustfmt`.
+///
+/// # Arguments
+/// + `content`: Source to format.
+/// + `edition`: Edition to format for, or `None` to leave it to `rustfmt.toml`.
+/// + `dir`: Directory `rustfmt.toml` is searched from.
+///
+/// # Returns
+/// The formatted source, or `None` if `rustfmt` is not installed or could not format it.
+fn format_source(content: &str, edition: Option<&str>, dir: &Path) -> Result<Option<String>> {
+    let rustfmt = env::var_os(RUSTFMT_VAR).unwrap_or_else(|| "rustfmt".into());
+    let mut command = Command::new(rustfmt);
+    if let Some(edition) = edition {
+        command.args(["--edition", edition]);
+    }
+
+    let child = command
+        .args(["--emit", "stdout"])
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    // `rustfmt` may exit before reading everything if the source does not parse
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(content.as_bytes());
+    }
+
+    let output = child.wait_with_output()?;
+    match (output.status.success(), String::from_utf8(output.stdout)) {
+        (true, Ok(formatted)) => Ok(Some(formatted)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+#[path = "./rustfmt_test.rs"]
+mod rustfmt_test;
@@ -0,0 +1,87 @@
+use super::*;
+use std::fs;
+
+#[test]
+fn format_should_format_rust_files_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\nedition = \"2021\"\n").unwrap();
+    let file = dir.path().join("a.rs");
+    fs::write(&file, "pub   mod b;\nfn  main( ) {}\n").unwrap();
+
+    let project = Project::discover(dir.path()).unwrap();
+    if !format(&project, &[&file, &dir.path().join("Cargo.toml")]).unwrap() {
+        // `rustfmt` is not installed
+        return;
+    }
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "pub mod b;\nfn main() {}\n");
+}
+
+#[test]
+fn unformattable_files_should_be_left_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    let file = dir.path().join("a.rs");
+    fs::write(&file, "fn  main( {\n").unwrap();
+
+    let project = Project::discover(dir.path()).unwrap();
+    assert!(!format(&project, &[&file]).unwrap());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "fn  main( {\n");
+}
+
+#[test]
+fn format_should_not_format_declared_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\nedition = \"2021\"\n").unwrap();
+    let file = dir.path().join("a.rs");
+    fs::write(&file, "pub   mod b;\n").unwrap();
+    fs::write(dir.path().join("b.rs"), "fn  f( ) {}\n").unwrap();
+
+    let project = Project::discover(dir.path()).unwrap();
+    if !format(&project, &[&file]).unwrap() {
+        // `rustfmt` is not installed
+        return;
+    }
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "pub mod b;\n");
+    assert_eq!(fs::read_to_string(dir.path().join("b.rs")).unwrap(), "fn  f( ) {}\n");
+}
+
+#[test]
+fn format_created_should_leave_existing_supers_as_they_are() {
+    let (_dir, root) = crate::fixtures::project(&[
+        ("Cargo.toml", "[package]\nname = \"a\"\nedition = \"2021\"\n"),
+        ("src/lib.rs", "pub mod foo;\nmod zeta;\nmod alpha;\n\nfn f() {}\n"),
+    ]);
+
+    let project = Project::discover(&root).unwrap();
+    let options = crate::SuperOptions { at: Some(1), ..crate::SuperOptions::default() };
+    let mid = crate::CreateOptions::new(root.join("src/mid"))
+        .with_test(false)
+        .super_options(options)
+        .create()
+        .unwrap();
+
+    let options = crate::SuperOptions { comment: Some(String::from("owned by infra")), ..crate::SuperOptions::default() };
+    let baz = crate::CreateOptions::new(root.join("src/baz"))
+        .with_test(false)
+        .super_options(options)
+        .create()
+        .unwrap();
+
+    let mut created = [mid, baz];
+    if !format_created(&project, &mut created).unwrap() {
+        // `rustfmt` is not installed
+        return;
+    }
+
+    assert_eq!(
+        fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+        "pub mod mid;\npub mod foo;\nmod zeta;\nmod alpha;\npub mod baz; // owned by infra\n\nfn f() {}\n"
+    );
+
+    assert_eq!(created[0].declaration_lines, vec![1]);
+    assert_eq!(created[0].declaration_location.as_ref().unwrap().line, 1);
+    assert_eq!(created[1].declaration_lines, vec![5]);
+    assert_eq!(created[1].declaration_location.as_ref().unwrap().line, 5);
+}
@@ -0,0 +1,86 @@
+//! Snapshots of files, used to undo operations that fail part way through.
+use crate::result::Result;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// State of a set of paths before an operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    /// Files that existed, and their contents.
+    files: Vec<(PathBuf, Vec<u8>)>,
+
+    /// Paths that did not exist.
+    /// For each missing path, its outermost missing ancestor is also recorded,
+    /// so directories created along the way are removed on restore.
+    missing: Vec<PathBuf>,
+}
+
+impl Snapshot {
+    /// Record the current state of paths.
+    /// Existing directories are not recorded, so files an operation may create in them
+    /// must be given individually.
+    ///
+    /// # Arguments
+    /// + `paths`: Paths an operation may create or modify.
+    pub fn take(paths: &[&Path]) -> Result<Self> {
+        let mut snapshot = Self::default();
+        for path in paths {
+            if path.is_file() {
+                snapshot.files.push((path.to_path_buf(), fs::read(path)?));
+            } else if !path.exists() {
+                let outermost = path
+                    .ancestors()
+                    .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+                    .last()
+                    .unwrap_or(path);
+
+                snapshot.missing.push(outermost.to_path_buf());
+                snapshot.missing.push(path.to_path_buf());
+            }
+        }
+
+        snapshot.missing.dedup();
+        Ok(snapshot)
+    }
+
+    /// Restore the recorded state, removing paths that were created
+    /// and restoring the contents of files that were modified or removed.
+    ///
+    /// Every path is restored even if some fail.
+    ///
+    /// # Errors
+    /// + The first error encountered.
+    pub fn restore(&self) -> Result {
+        let mut res = Ok(());
+        for path in &self.missing {
+            let removed = match fs::symlink_metadata(path) {
+                Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+                Ok(_) => fs::remove_file(path),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = removed {
+                res = res.and(Err(err.into()));
+            }
+        }
+
+        for (path, content) in &self.files {
+            let restored = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(path, content));
+
+            if let Err(err) = restored {
+                res = res.and(Err(err.into()));
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+#[path = "./snapshot_test.rs"]
+mod snapshot_test;
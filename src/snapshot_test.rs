@@ -0,0 +1,35 @@
+use super::*;
+
+#[test]
+fn restore_should_undo_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    let nested = dir.path().join("a/b/c.rs");
+    fs::write(&file, "mod a;\n").unwrap();
+
+    let snapshot = Snapshot::take(&[&file, &nested, &dir.path().join("d.rs")]).unwrap();
+    fs::write(&file, "mod a;\nmod d;\n").unwrap();
+    fs::create_dir_all(nested.parent().unwrap()).unwrap();
+    fs::write(&nested, "").unwrap();
+    fs::write(dir.path().join("d.rs"), "").unwrap();
+
+    snapshot.restore().unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "mod a;\n");
+    assert!(!dir.path().join("a").exists());
+    assert!(!dir.path().join("d.rs").exists());
+}
+
+#[test]
+fn restore_should_recreate_removed_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("a.rs");
+    fs::write(&file, "fn a() {}\n").unwrap();
+
+    let snapshot = Snapshot::take(&[&file, &dir.path().join("a")]).unwrap();
+    fs::create_dir(dir.path().join("a")).unwrap();
+    fs::rename(&file, dir.path().join("a/mod.rs")).unwrap();
+
+    snapshot.restore().unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "fn a() {}\n");
+    assert!(!dir.path().join("a").exists());
+}
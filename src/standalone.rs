@@ -0,0 +1,219 @@
+//! Targets other than the package's modules: integration tests, benches, examples, and binaries.
+//! Their files are not modules, so they are not added to a super.
+use crate::creator::DiskFileSystem;
+use crate::manifest::{self, Manifest};
+use crate::project::Project;
+use crate::report::Modified;
+use crate::result::Result;
+use crate::snapshot::Snapshot;
+use crate::ContentOptions;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Directory of standalone targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetDir {
+    /// Integration tests, in `tests/`.
+    Tests,
+
+    /// Benchmarks, in `benches/`.
+    /// Benches are declared in the manifest with their path and `harness = false`,
+    /// as criterion requires.
+    Benches,
+
+    /// Examples, in `examples/`.
+    Examples,
+
+    /// Binaries, in `src/bin/`.
+    Bins,
+}
+
+impl TargetDir {
+    /// All directories of standalone targets.
+    pub const ALL: [Self; 4] = [Self::Tests, Self::Benches, Self::Examples, Self::Bins];
+
+    /// Path of a target's file.
+    ///
+    /// # Arguments
+    /// + `project`: Project containing the target.
+    /// + `name`: Name of the target.
+    pub fn path(&self, project: &Project, name: &str) -> PathBuf {
+        project.root.join(self.to_string()).join(format!("{name}.rs"))
+    }
+
+    /// Render the default contents of a target's file.
+    ///
+    /// # Arguments
+    /// + `name`: Name of the target.
+    pub fn skeleton(&self, name: &str) -> String {
+        let ident = name.replace('-', "_");
+        match self {
+            Self::Tests => format!(r#"#[test]
+fn {ident}() {{
+}}
+"#),
+
+            Self::Benches => format!(r#"use criterion::{{criterion_group, criterion_main, Criterion}};
+
+fn {ident}(c: &mut Criterion) {{
+    c.bench_function("{name}", |b| b.iter(|| {{}}));
+}}
+
+criterion_group!(benches, {ident});
+criterion_main!(benches);
+"#),
+
+            Self::Examples | Self::Bins => String::from(r#"fn main() {
+}
+"#),
+        }
+    }
+
+    /// Kind of the targets in the directory, as declared in the manifest.
+    fn kind(&self) -> manifest::TargetKind {
+        match self {
+            Self::Tests => manifest::TargetKind::Test,
+            Self::Benches => manifest::TargetKind::Bench,
+            Self::Examples => manifest::TargetKind::Example,
+            Self::Bins => manifest::TargetKind::Bin,
+        }
+    }
+
+    /// Get the configured template of a target's file.
+    fn template<'a>(&self, content: &'a ContentOptions) -> Option<&'a String> {
+        match self {
+            Self::Tests => content.templates.integration_test.as_ref(),
+            Self::Benches => content.templates.bench.as_ref(),
+            Self::Examples => content.templates.example.as_ref(),
+            Self::Bins => None,
+        }
+    }
+}
+
+impl fmt::Display for TargetDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dir = match self {
+            Self::Tests => "tests",
+            Self::Benches => "benches",
+            Self::Examples => "examples",
+            Self::Bins => "src/bin",
+        };
+
+        write!(f, "{dir}")
+    }
+}
+
+impl FromStr for TargetDir {
+    type Err = io::Error;
+
+    fn from_str(dir: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == dir)
+            .ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput, format!("unknown target directory `{dir}`")
+            ))
+    }
+}
+
+/// Create the file of a standalone target, e.g. `tests/foo.rs`.
+/// Benches, and targets gated behind a feature, are also added to the manifest,
+/// e.g. as a `[[bench]]` target.
+///
+/// # Arguments
+/// + `project`: Project to create the target in.
+/// + `dir`: Directory of the target.
+/// + `name`: Name of the target, optionally with the `.rs` extension.
+/// + `content`: Options for the file's contents.
+///   The file's body is rendered from the directory's template if one is set,
+///   otherwise its skeleton is used. The module kind and test file templates are not used.
+/// + `feature`: Feature the target requires, written to its `required-features`.
+///
+/// # Returns
+/// The target's file, and the manifest with the lines inserted in it if it was modified.
+///
+/// # Errors
+/// + If the name is not a plain file name.
+/// + If the target's file already exists.
+pub fn create(
+    project: &Project,
+    dir: TargetDir,
+    name: &str,
+    content: &ContentOptions,
+    feature: Option<&str>,
+) -> Result<(PathBuf, Option<Modified>)> {
+    let name = name.strip_suffix(".rs").unwrap_or(name);
+    if name.is_empty() || Path::new(name).components().count() != 1 || name.starts_with('.') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput, format!("`{name}` is not a valid target name")
+        ).into());
+    }
+
+    let file = dir.path(project, name);
+    if file.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, "target file already exists").into());
+    }
+
+    let vars = crate::template_vars(&DiskFileSystem, &file, name)?;
+    let mut file_content = crate::preamble(content, Some(&vars))?;
+    match dir.template(content) {
+        Some(template) => file_content.push_str(&crate::render_template(template, vars)?),
+        None => file_content.push_str(&dir.skeleton(name)),
+    }
+
+    crate::validate(&dir.to_string(), &file_content)?;
+
+    let manifest_path = project.manifest_path();
+    let snapshot = Snapshot::take(&[&file, &manifest_path])?;
+    let res = write(&file, &file_content, dir, name, feature, &manifest_path);
+    if res.is_err() {
+        let _ = snapshot.restore();
+    }
+
+    res
+}
+
+/// Write a target's file, and add benches and targets requiring a feature to the manifest.
+///
+/// # Returns
+/// See [`create`].
+fn write(
+    file: &Path,
+    content: &str,
+    dir: TargetDir,
+    name: &str,
+    feature: Option<&str>,
+    manifest_path: &Path,
+) -> Result<(PathBuf, Option<Modified>)> {
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(file, content)?;
+    let mut modified = None;
+    if dir == TargetDir::Benches || feature.is_some() {
+        let mut manifest = Manifest::load(manifest_path)?;
+        let target = manifest::Target {
+            name: name.to_string(),
+            path: Some(format!("{dir}/{name}.rs")).filter(|_| dir == TargetDir::Benches),
+            harness: Some(false).filter(|_| dir == TargetDir::Benches),
+            required_features: feature.map(String::from).into_iter().collect(),
+        };
+
+        if manifest.ensure_target(dir.kind(), &target)? {
+            let inserted_lines = manifest.save()?;
+            modified = Some(Modified { path: manifest_path.to_path_buf(), inserted_lines });
+        }
+    }
+
+    Ok((file.to_path_buf(), modified))
+}
+
+#[cfg(test)]
+#[path = "./standalone_test.rs"]
+mod standalone_test;
@@ -0,0 +1,100 @@
+use super::*;
+use crate::config::Templates;
+
+/// Create a package named `a`.
+fn package() -> (tempfile::TempDir, Project) {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    let project = Project::discover(dir.path()).unwrap();
+    (dir, project)
+}
+
+#[test]
+fn integration_test_should_be_created_from_template() {
+    let (_dir, project) = package();
+    let content = ContentOptions {
+        templates: Templates {
+            integration_test: Some(String::from("use {{ crate_name }}::*;\n")),
+            ..Templates::default()
+        },
+        ..ContentOptions::default()
+    };
+
+    let (file, manifest) = create(&project, TargetDir::Tests, "api", &content, None).unwrap();
+    assert_eq!(file, project.root.join("tests/api.rs"));
+    assert_eq!(manifest, None);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "use a::*;\n");
+}
+
+#[test]
+fn bench_should_be_added_to_manifest() {
+    let (_dir, project) = package();
+    let (file, manifest) = create(&project, TargetDir::Benches, "parse-file", &ContentOptions::default(), None).unwrap();
+    assert_eq!(file, project.root.join("benches/parse-file.rs"));
+    assert_eq!(
+        manifest,
+        Some(Modified { path: project.manifest_path(), inserted_lines: vec![3, 4, 5, 6, 7] })
+    );
+
+    let bench = fs::read_to_string(&file).unwrap();
+    assert!(bench.contains("criterion_group!(benches, parse_file);"));
+    assert_eq!(
+        fs::read_to_string(project.manifest_path()).unwrap(),
+        "[package]\nname = \"a\"\n\n[[bench]]\nname = \"parse-file\"\npath = \"benches/parse-file.rs\"\nharness = false\n"
+    );
+}
+
+#[test]
+fn registered_bench_should_not_be_added_again() {
+    let (_dir, project) = package();
+    let manifest = "[package]\nname = \"a\"\n\n[[bench]]\nname = \"parse\"\npath = \"benches/parse.rs\"\nharness = false\n";
+    fs::write(project.manifest_path(), manifest).unwrap();
+
+    let (file, modified) = create(&project, TargetDir::Benches, "parse", &ContentOptions::default(), None).unwrap();
+    assert!(file.exists());
+    assert_eq!(modified, None);
+    assert_eq!(fs::read_to_string(project.manifest_path()).unwrap(), manifest);
+}
+
+#[test]
+fn existing_or_invalid_target_should_error() {
+    let (_dir, project) = package();
+    create(&project, TargetDir::Examples, "demo.rs", &ContentOptions::default(), None).unwrap();
+    assert_eq!(
+        fs::read_to_string(project.root.join("examples/demo.rs")).unwrap(),
+        "fn main() {\n}\n"
+    );
+
+    assert!(create(&project, TargetDir::Examples, "demo", &ContentOptions::default(), None).is_err());
+    assert!(create(&project, TargetDir::Examples, "a/b", &ContentOptions::default(), None).is_err());
+}
+
+#[test]
+fn feature_should_be_required_by_target() {
+    let (_dir, project) = package();
+    let (file, manifest) = create(&project, TargetDir::Examples, "demo", &ContentOptions::default(), Some("cli")).unwrap();
+    assert_eq!(file, project.root.join("examples/demo.rs"));
+    assert_eq!(manifest.map(|edit| edit.path), Some(project.manifest_path()));
+    assert_eq!(
+        fs::read_to_string(project.manifest_path()).unwrap(),
+        "[package]\nname = \"a\"\n\n[[example]]\nname = \"demo\"\nrequired-features = [\"cli\"]\n"
+    );
+}
+
+#[test]
+fn feature_should_be_required_by_binary() {
+    let (_dir, project) = package();
+    let (file, manifest) = create(&project, TargetDir::Bins, "tool", &ContentOptions::default(), None).unwrap();
+    assert_eq!(file, project.root.join("src/bin/tool.rs"));
+    assert_eq!(manifest, None);
+
+    let (_, manifest) = create(&project, TargetDir::Bins, "server", &ContentOptions::default(), Some("net")).unwrap();
+    assert_eq!(
+        manifest,
+        Some(Modified { path: project.manifest_path(), inserted_lines: vec![3, 4, 5, 6] })
+    );
+    assert_eq!(
+        fs::read_to_string(project.manifest_path()).unwrap(),
+        "[package]\nname = \"a\"\n\n[[bin]]\nname = \"server\"\nrequired-features = [\"net\"]\n"
+    );
+}
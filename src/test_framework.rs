@@ -0,0 +1,135 @@
+//! Frameworks generated tests are written for, e.g. async runtimes.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Framework generated tests run with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestFramework {
+    /// Synchronous `#[test]` functions.
+    #[default]
+    Plain,
+
+    /// `#[tokio::test] async fn`.
+    Tokio,
+
+    /// `#[async_std::test] async fn`.
+    AsyncStd,
+}
+
+/// A dependency a test framework needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    /// Name of the crate.
+    pub name: &'static str,
+
+    pub version: &'static str,
+
+    /// Features the tests need.
+    pub features: &'static [&'static str],
+}
+
+impl TestFramework {
+    /// All test frameworks.
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Tokio, Self::AsyncStd];
+
+    /// Attribute marking a test function. e.g. `#[tokio::test]`.
+    pub fn attribute(&self) -> &'static str {
+        match self {
+            Self::Plain => "#[test]",
+            Self::Tokio => "#[tokio::test]",
+            Self::AsyncStd => "#[async_std::test]",
+        }
+    }
+
+    /// Whether test functions are `async`.
+    pub fn is_async(&self) -> bool {
+        *self != Self::Plain
+    }
+
+    /// Dependency the tests need, if any.
+    pub fn dependency(&self) -> Option<Dependency> {
+        match self {
+            Self::Plain => None,
+            Self::Tokio => Some(Dependency { name: "tokio", version: "1", features: &["macros", "rt"] }),
+            Self::AsyncStd => Some(Dependency { name: "async-std", version: "1", features: &["attributes"] }),
+        }
+    }
+
+    /// Render a starter test, placed after the test file's imports.
+    /// Empty for plain tests, which need no setup to write.
+    pub fn skeleton(&self) -> String {
+        match self {
+            Self::Plain => String::new(),
+            _ => format!("\n{}\nasync fn it_works() {{}}\n", self.attribute()),
+        }
+    }
+
+    /// Convert plain tests to the framework's, i.e. each `#[test] fn` to `#[<framework>::test] async fn`.
+    ///
+    /// # Arguments
+    /// + `tests`: Tests to convert.
+    pub fn convert(&self, tests: &str) -> String {
+        if !self.is_async() {
+            return tests.to_string();
+        }
+
+        let mut converted = String::new();
+        let mut lines = tests.lines().peekable();
+        while let Some(line) = lines.next() {
+            let is_test = line.trim() == "#[test]"
+                && lines.peek().is_some_and(|next| next.trim_start().starts_with("fn "));
+
+            match is_test {
+                true => {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    let function = lines.next().unwrap_or_default();
+                    converted.push_str(&format!("{indent}{}\n", self.attribute()));
+                    converted.push_str(&format!("{indent}async {}\n", function.trim_start()));
+                },
+
+                false => {
+                    converted.push_str(line);
+                    converted.push('\n');
+                },
+            }
+        }
+
+        if !tests.ends_with('\n') {
+            converted.pop();
+        }
+
+        converted
+    }
+}
+
+impl fmt::Display for TestFramework {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let framework = match self {
+            Self::Plain => "plain",
+            Self::Tokio => "tokio",
+            Self::AsyncStd => "async-std",
+        };
+
+        write!(f, "{framework}")
+    }
+}
+
+impl FromStr for TestFramework {
+    type Err = io::Error;
+
+    fn from_str(framework: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == framework)
+            .ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput, format!("unknown test framework `{framework}`")
+            ))
+    }
+}
+
+#[cfg(test)]
+#[path = "./test_framework_test.rs"]
+mod test_framework_test;
@@ -0,0 +1,28 @@
+use super::*;
+use crate::kind::ModuleKind;
+
+#[test]
+fn framework_should_parse_from_display() {
+    for framework in TestFramework::ALL {
+        assert_eq!(framework.to_string().parse::<TestFramework>().unwrap(), framework);
+    }
+
+    assert!("smol".parse::<TestFramework>().is_err());
+}
+
+#[test]
+fn convert_should_make_tests_async() {
+    let tests = "\n#[test]\nfn a() {}\n\n    #[test]\n    fn b() {}\n";
+    assert_eq!(
+        TestFramework::Tokio.convert(tests),
+        "\n#[tokio::test]\nasync fn a() {}\n\n    #[tokio::test]\n    async fn b() {}\n"
+    );
+
+    assert_eq!(TestFramework::Plain.convert(tests), tests);
+    for framework in TestFramework::ALL {
+        syn::parse_file(&framework.skeleton()).unwrap();
+        for kind in ModuleKind::ALL {
+            syn::parse_file(&framework.convert(&kind.test("my_mod"))).unwrap();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! The module tree of a crate, found by following `mod` declarations from its targets' roots.
+use crate::creator::{DiskFileSystem, FileSystem};
+use crate::project::{self, Project};
+use crate::remove::rust_files_in;
+use crate::result::Result;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A module in the tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    /// Name of the module. For crate roots, the name of the target.
+    pub name: String,
+
+    /// File of the module. For inline modules, the file they are defined in.
+    /// For missing modules, the file the declaration expects.
+    pub file: PathBuf,
+
+    /// Whether the module is defined inline, i.e. `mod name { ... }`.
+    pub inline: bool,
+
+    /// Whether the module is declared, but its file does not exist.
+    pub missing: bool,
+
+    /// Whether the module's file could not be parsed, so its submodules are unknown.
+    pub unparsed: bool,
+
+    /// Directory the module's submodule files are in.
+    pub dir: PathBuf,
+
+    /// Submodules, in order of declaration.
+    pub children: Vec<Module>,
+}
+
+/// Module tree of a crate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tree {
+    /// Root module of each of the crate's targets.
+    pub roots: Vec<Module>,
+
+    /// Rust files in the source directory that are not part of the tree, sorted.
+    pub orphans: Vec<PathBuf>,
+}
+
+impl Tree {
+    /// Read the module tree of a project.
+    ///
+    /// # Arguments
+    /// + `project`: Project to read. Orphans are searched for in its source directory.
+    pub fn read(project: &Project) -> Result<Self> {
+        Self::read_in(&DiskFileSystem, project)
+    }
+
+    /// Read the module tree of a project in a file system. See [`Self::read`].
+    pub fn read_in(fs: &dyn FileSystem, project: &Project) -> Result<Self> {
+        let mut visited = HashSet::new();
+        let mut roots = Vec::new();
+        for target in project.targets_in(fs)? {
+            // submodules of crate roots are next to them
+            let dir = target.root.parent().unwrap_or(Path::new("")).to_path_buf();
+            let mut root = Module::new(target.name, target.root.clone(), dir, false);
+            visited.insert(target.root.clone());
+            read_file(fs, &mut root, &target.root, &mut visited)?;
+            roots.push(root);
+        }
+
+        let orphans = match fs.is_dir(&project.src) {
+            true => rust_files_in(fs, &project.src)?
+                .into_iter()
+                .filter(|file| !visited.contains(file))
+                .collect(),
+
+            false => Vec::new(),
+        };
+
+        Ok(Self { roots, orphans })
+    }
+
+    /// Get the modules whose files are missing, in tree order.
+    pub fn missing(&self) -> Vec<&Module> {
+        let mut missing = Vec::new();
+        let mut stack: Vec<&Module> = self.roots.iter().rev().collect();
+        while let Some(module) = stack.pop() {
+            if module.missing {
+                missing.push(module);
+            }
+
+            stack.extend(module.children.iter().rev());
+        }
+
+        missing
+    }
+
+    /// Get the files of the modules in the tree, including crate roots and test files
+    /// loaded with `#[path]`. Missing modules are not included.
+    pub fn files(&self) -> HashSet<&Path> {
+        let mut files = HashSet::new();
+        let mut stack: Vec<&Module> = self.roots.iter().collect();
+        while let Some(module) = stack.pop() {
+            if !module.missing {
+                files.insert(module.file.as_path());
+            }
+
+            stack.extend(module.children.iter());
+        }
+
+        files
+    }
+
+    /// Get the module whose submodule files are in a directory, found by following declarations,
+    /// so modules loaded with `#[path]` are found wherever their files are.
+    /// Crate roots and inline modules are not considered.
+    ///
+    /// # Arguments
+    /// + `dir`: Directory of the submodules.
+    /// + `target`: Name of the target whose modules to consider, or `None` for all targets.
+    ///
+    /// # Returns
+    /// `None` if no module, or several modules in different files, own the directory.
+    pub fn owner(&self, dir: &Path, target: Option<&str>) -> Option<&Module> {
+        let mut owners: Vec<&Module> = Vec::new();
+        let mut stack: Vec<&Module> = self.roots
+            .iter()
+            .filter(|root| target.is_none_or(|target| root.name == target))
+            .flat_map(|root| root.children.iter())
+            .collect();
+
+        while let Some(module) = stack.pop() {
+            if !module.inline && !module.missing && module.dir == dir
+                && owners.iter().all(|owner| owner.file != module.file)
+            {
+                owners.push(module);
+            }
+
+            stack.extend(module.children.iter());
+        }
+
+        match owners.as_slice() {
+            [owner] => Some(owner),
+            _ => None,
+        }
+    }
+}
+
+impl Module {
+    /// Get the path a submodule has, following declarations, so modules loaded with `#[path]` are found.
+    /// Segments that are not declared are joined onto the directory of the last declared one,
+    /// as parents to be created.
+    ///
+    /// # Arguments
+    /// + `segments`: Path of the submodule from this module. e.g. `["a", "b", "c"]` for `a::b::c`.
+    ///
+    /// # Returns
+    /// Path of the submodule, without extension. e.g. `src/a/b/c`.
+    ///
+    /// # Errors
+    /// + If a parent is declared inline, its file is missing, or it could not be parsed.
+    pub fn resolve(&self, segments: &[&str]) -> Result<PathBuf> {
+        let Some((name, parents)) = segments.split_last() else {
+            return Ok(self.dir.clone());
+        };
+
+        let mut module = self;
+        let mut dir = self.dir.clone();
+        for (i, segment) in parents.iter().enumerate() {
+            let Some(child) = module.children.iter().find(|child| child.name == *segment) else {
+                dir.extend(&parents[i..]);
+                break;
+            };
+
+            if child.inline || child.missing || child.unparsed {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("module `{}` is declared inline, missing, or could not be parsed", parents[..=i].join("::")),
+                ).into());
+            }
+
+            module = child;
+            dir = child.dir.clone();
+        }
+
+        Ok(dir.join(name))
+    }
+
+    /// Create a module without submodules.
+    fn new(name: String, file: PathBuf, dir: PathBuf, inline: bool) -> Self {
+        Self {
+            name,
+            file,
+            dir,
+            inline,
+            missing: false,
+            unparsed: false,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Read the submodules declared in a module's file.
+///
+/// # Arguments
+/// + `fs`: File system the crate is in.
+/// + `module`: Module to add the submodules to. Its submodules are in its `dir`.
+/// + `file`: File of the module.
+/// + `visited`: Files already in the tree.
+fn read_file(fs: &dyn FileSystem, module: &mut Module, file: &Path, visited: &mut HashSet<PathBuf>) -> Result {
+    let content = fs.read_to_string(file)?;
+    match syn::parse_file(&content) {
+        Ok(parsed) => {
+            let parent = file.parent().unwrap_or(Path::new(""));
+            let dir = module.dir.clone();
+            read_items(fs, module, &parsed.items, file, parent, &dir, visited)
+        },
+
+        Err(_) => {
+            module.unparsed = true;
+            Ok(())
+        },
+    }
+}
+
+/// Read the submodules declared by items.
+///
+/// # Arguments
+/// + `fs`: File system the crate is in.
+/// + `module`: Module to add the submodules to.
+/// + `items`: Items of the module.
+/// + `file`: File the items are in.
+/// + `path_dir`: Directory `#[path]` attributes are relative to.
+/// + `dir`: Directory submodule files are in.
+/// + `visited`: Files already in the tree.
+fn read_items(
+    fs: &dyn FileSystem,
+    module: &mut Module,
+    items: &[syn::Item],
+    file: &Path,
+    path_dir: &Path,
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+) -> Result {
+    for item in items {
+        let syn::Item::Mod(item) = item else {
+            continue;
+        };
+
+        let name = item.ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+        let path_attr = item.attrs.iter().find_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) if meta.path.is_ident("path") => match &meta.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. }) => Some(path.value()),
+                _ => None,
+            },
+
+            _ => None,
+        });
+
+        if let Some((_, content)) = &item.content {
+            let child_dir = dir.join(&name);
+            let mut child = Module::new(name.clone(), file.to_path_buf(), child_dir.clone(), true);
+            let child_path_dir = match &path_attr {
+                Some(path) => path_dir.join(path),
+                None => child_dir.clone(),
+            };
+
+            read_items(fs, &mut child, content, file, &child_path_dir, &child_dir, visited)?;
+            module.children.push(child);
+            continue;
+        }
+
+        let child_file = match &path_attr {
+            Some(path) => project::normalize_path(&path_dir.join(path)),
+            None => {
+                let named = dir.join(format!("{name}.rs"));
+                let mod_rs = dir.join(&name).join("mod.rs");
+                match !fs.exists(&named) && fs.exists(&mod_rs) {
+                    true => mod_rs,
+                    false => named,
+                }
+            },
+        };
+
+        // `mod.rs` files and files loaded with `#[path]` own their directory
+        let owns_dir = path_attr.is_some() || child_file.file_name().is_some_and(|name| name == "mod.rs");
+        let child_dir = match owns_dir {
+            true => child_file.parent().unwrap_or(Path::new("")).to_path_buf(),
+            false => child_file.with_extension(""),
+        };
+
+        let mut child = Module::new(name, child_file.clone(), child_dir, false);
+        if !fs.is_file(&child_file) {
+            child.missing = true;
+        } else if visited.insert(child_file.clone()) {
+            read_file(fs, &mut child, &child_file, visited)?;
+        }
+
+        module.children.push(child);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./tree_test.rs"]
+mod tree_test;
@@ -0,0 +1,89 @@
+use super::*;
+use std::fs;
+
+/// Create a library package named `a` with the given source files.
+fn package(files: &[(&str, &str)]) -> (tempfile::TempDir, Project) {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"a\"\n").unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    let project = Project::discover(dir.path()).unwrap();
+    (dir, project)
+}
+
+#[test]
+fn tree_should_follow_declarations() {
+    let (_dir, project) = package(&[
+        ("src/lib.rs", "pub mod a;\nmod b;\n"),
+        ("src/a/mod.rs", "mod c;\n"),
+        ("src/a/c.rs", "#[cfg(test)]\n#[path = \"./c_test.rs\"]\nmod c_test;\n"),
+        ("src/a/c_test.rs", ""),
+        ("src/b.rs", "mod d;\nmod tests {\n    mod e {}\n}\n"),
+        ("src/b/d.rs", ""),
+    ]);
+
+    let tree = Tree::read(&project).unwrap();
+    assert!(tree.orphans.is_empty());
+    assert_eq!(tree.roots.len(), 1);
+
+    let root = &tree.roots[0];
+    assert_eq!(root.name, "a");
+    assert_eq!(root.file, project.src.join("lib.rs"));
+
+    let names = |module: &Module| module.children.iter().map(|child| child.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(root), ["a", "b"]);
+
+    let a = &root.children[0];
+    assert_eq!(a.file, project.src.join("a/mod.rs"));
+    assert_eq!(a.children[0].children[0].file, project.src.join("a/c_test.rs"));
+
+    let b = &root.children[1];
+    assert_eq!(names(b), ["d", "tests"]);
+    assert_eq!(b.children[0].file, project.src.join("b/d.rs"));
+    assert!(b.children[1].inline);
+    assert_eq!(names(&b.children[1]), ["e"]);
+}
+
+#[test]
+fn tree_should_flag_missing_and_orphan_files() {
+    let (_dir, project) = package(&[
+        ("src/lib.rs", "mod a;\nmod gone;\n"),
+        ("src/a.rs", "mod {\n"),
+        ("src/stray.rs", ""),
+        ("src/old/mod.rs", ""),
+    ]);
+
+    let tree = Tree::read(&project).unwrap();
+    assert!(tree.roots[0].children[0].unparsed);
+
+    let missing = tree.missing();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].name, "gone");
+    assert_eq!(missing[0].file, project.src.join("gone.rs"));
+
+    assert_eq!(
+        tree.orphans,
+        [project.src.join("old/mod.rs"), project.src.join("stray.rs")]
+    );
+}
+
+#[test]
+fn resolve_should_follow_path_attributes() {
+    let (_dir, project) = package(&[
+        ("src/lib.rs", "#[path = \"other/bar.rs\"]\npub mod bar;\nmod inline {}\n"),
+        ("src/other/bar.rs", "mod baz;\n"),
+        ("src/other/baz.rs", ""),
+    ]);
+
+    let tree = Tree::read(&project).unwrap();
+    let root = &tree.roots[0];
+    assert_eq!(root.resolve(&["bar", "qux"]).unwrap(), project.src.join("other/qux"));
+    assert_eq!(root.resolve(&["bar", "baz", "qux"]).unwrap(), project.src.join("other/baz/qux"));
+    assert_eq!(root.resolve(&["bar", "new", "qux"]).unwrap(), project.src.join("other/new/qux"));
+    assert_eq!(root.resolve(&["qux"]).unwrap(), project.src.join("qux"));
+    assert!(root.resolve(&["inline", "qux"]).is_err());
+}
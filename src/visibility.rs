@@ -0,0 +1,174 @@
+//! Visibility of module declarations.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Visibility of a module declaration.
+/// Written as in Rust source, e.g. `pub(crate)`, or `private` for no visibility.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Visibility {
+    /// Visible only to the super module and its descendants.
+    Private,
+
+    /// `pub`.
+    #[default]
+    Pub,
+
+    /// `pub(crate)`.
+    Crate,
+
+    /// `pub(super)`.
+    Super,
+
+    /// `pub(in path)`, visible within the given module path. e.g. `crate::a`.
+    In(String),
+}
+
+impl Visibility {
+    /// The visibility as written before `mod` in a declaration.
+    /// Empty for private modules.
+    pub fn keyword(&self) -> String {
+        match self {
+            Self::Private => String::new(),
+            Self::Pub => String::from("pub"),
+            Self::Crate => String::from("pub(crate)"),
+            Self::Super => String::from("pub(super)"),
+            Self::In(path) => format!("pub(in {path})"),
+        }
+    }
+
+    /// Whether items with this visibility are visible everywhere items with another one are.
+    /// Visibilities that can not be compared, e.g. `pub(super)` and `pub(in crate::a)`,
+    /// do not contain each other.
+    pub fn contains(&self, other: &Self) -> bool {
+        match (self, other) {
+            _ if self == other => true,
+            (Self::Pub, _) | (_, Self::Private) => true,
+            (Self::Crate, Self::Super | Self::In(_)) => true,
+            (Self::In(outer), Self::In(inner)) => inner.starts_with(&format!("{outer}::")),
+            _ => false,
+        }
+    }
+}
+
+impl From<&syn::Visibility> for Visibility {
+    fn from(vis: &syn::Visibility) -> Self {
+        match vis {
+            syn::Visibility::Public(_) => Self::Pub,
+            syn::Visibility::Inherited => Self::Private,
+            syn::Visibility::Restricted(restricted) if restricted.in_token.is_none() => {
+                match restricted.path.is_ident("crate") {
+                    true => Self::Crate,
+                    false => Self::Super,
+                }
+            },
+
+            syn::Visibility::Restricted(restricted) => {
+                let segments: Vec<String> = restricted.path.segments.iter().map(|s| s.ident.to_string()).collect();
+                Self::In(segments.join("::"))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Private => write!(f, "private"),
+            _ => write!(f, "{}", self.keyword()),
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = io::Error;
+
+    fn from_str(vis: &str) -> Result<Self, Self::Err> {
+        let compact: String = vis.split_whitespace().collect::<Vec<_>>().join(" ");
+        let restriction = compact
+            .strip_prefix("pub")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            .map(str::trim);
+
+        match (compact.as_str(), restriction) {
+            ("" | "private", _) => Ok(Self::Private),
+            ("pub", _) => Ok(Self::Pub),
+            (_, Some("crate")) => Ok(Self::Crate),
+            (_, Some("super")) => Ok(Self::Super),
+            (_, Some(restriction)) => match restriction.strip_prefix("in ") {
+                Some(path) if !path.trim().is_empty() => {
+                    Ok(Self::In(path.replace(' ', "")))
+                },
+
+                _ => Err(invalid(vis)),
+            },
+
+            _ => Err(invalid(vis)),
+        }
+    }
+}
+
+impl From<Visibility> for String {
+    fn from(vis: Visibility) -> Self {
+        vis.to_string()
+    }
+}
+
+impl TryFrom<String> for Visibility {
+    type Error = io::Error;
+
+    fn try_from(vis: String) -> Result<Self, Self::Error> {
+        vis.parse()
+    }
+}
+
+impl From<bool> for Visibility {
+    /// `pub` if public, otherwise private.
+    fn from(public: bool) -> Self {
+        match public {
+            true => Self::Pub,
+            false => Self::Private,
+        }
+    }
+}
+
+/// Find the visibility most module declarations in a file have.
+/// Inline modules are ignored, and ties are broken in favor of the visibility declared first.
+///
+/// # Returns
+/// `None` if the file has no module declarations, or does not parse.
+pub fn most_common(content: &str) -> Option<Visibility> {
+    let file = syn::parse_file(content).ok()?;
+    let mut counts: Vec<(Visibility, usize)> = Vec::new();
+    for item in &file.items {
+        let syn::Item::Mod(item) = item else {
+            continue;
+        };
+
+        if item.content.is_some() {
+            continue;
+        }
+
+        let vis = Visibility::from(&item.vis);
+        match counts.iter_mut().find(|(counted, _)| *counted == vis) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((vis, 1)),
+        }
+    }
+
+    // `max_by_key` returns the last maximum
+    counts.into_iter().rev().max_by_key(|(_, count)| *count).map(|(vis, _)| vis)
+}
+
+/// Create an error for an invalid visibility.
+fn invalid(vis: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("invalid visibility `{vis}`"))
+}
+
+#[cfg(test)]
+#[path = "./visibility_test.rs"]
+mod visibility_test;
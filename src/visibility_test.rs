@@ -0,0 +1,55 @@
+use super::*;
+
+#[test]
+fn visibility_should_parse_rust_syntax() {
+    assert_eq!("private".parse::<Visibility>().unwrap(), Visibility::Private);
+    assert_eq!("pub".parse::<Visibility>().unwrap(), Visibility::Pub);
+    assert_eq!("pub(crate)".parse::<Visibility>().unwrap(), Visibility::Crate);
+    assert_eq!("pub( super )".parse::<Visibility>().unwrap(), Visibility::Super);
+    assert_eq!(
+        "pub(in crate::a)".parse::<Visibility>().unwrap(),
+        Visibility::In(String::from("crate::a"))
+    );
+
+    assert!("pub(in)".parse::<Visibility>().is_err());
+    assert!("public".parse::<Visibility>().is_err());
+}
+
+#[test]
+fn visibility_should_round_trip() {
+    let all = [
+        Visibility::Private,
+        Visibility::Pub,
+        Visibility::Crate,
+        Visibility::Super,
+        Visibility::In(String::from("super::super")),
+    ];
+
+    for vis in all {
+        assert_eq!(vis.to_string().parse::<Visibility>().unwrap(), vis);
+    }
+
+    assert_eq!(Visibility::Private.keyword(), "");
+    assert_eq!(Visibility::In(String::from("crate::a")).keyword(), "pub(in crate::a)");
+}
+
+#[test]
+fn most_common_visibility_should_be_found() {
+    assert_eq!(most_common("mod a;\npub(crate) mod b;\npub(crate) mod c;\nfn f() {}\n"), Some(Visibility::Crate));
+    assert_eq!(most_common("mod tests {}\npub(crate) mod a;\n"), Some(Visibility::Crate));
+    assert_eq!(most_common("pub mod a;\nmod b;\n"), Some(Visibility::Pub));
+    assert_eq!(most_common("pub(in crate::a) mod b;\n"), Some(Visibility::In(String::from("crate::a"))));
+    assert_eq!(most_common("fn f() {}\n"), None);
+}
+
+#[test]
+fn contains_should_compare_visibilities() {
+    let in_a = Visibility::In(String::from("crate::a"));
+    assert!(Visibility::Pub.contains(&Visibility::Crate));
+    assert!(Visibility::Crate.contains(&in_a));
+    assert!(in_a.contains(&Visibility::In(String::from("crate::a::b"))));
+    assert!(!in_a.contains(&Visibility::In(String::from("crate::ab"))));
+    assert!(!Visibility::Super.contains(&in_a));
+    assert!(!in_a.contains(&Visibility::Super));
+    assert!(!Visibility::Private.contains(&Visibility::Super));
+}